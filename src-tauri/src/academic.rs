@@ -0,0 +1,421 @@
+//! Academic paper search across Semantic Scholar, arXiv and Crossref.
+//! Each source can be toggled and given its own result count, and a year
+//! range / open-access-only filter applies across all of them. The
+//! defaults persist in settings so the search panel remembers them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::AppResult;
+use crate::settings;
+
+const DEFAULTS_KEY: &str = "academic_search_defaults";
+
+fn default_limit() -> usize {
+    5
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        SourceConfig {
+            enabled: true,
+            limit: default_limit(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AcademicSearchOptions {
+    pub semantic_scholar: SourceConfig,
+    pub arxiv: SourceConfig,
+    pub crossref: SourceConfig,
+    pub year_from: Option<i32>,
+    pub year_to: Option<i32>,
+    pub open_access_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Paper {
+    pub title: String,
+    pub authors: Vec<String>,
+    pub year: Option<i32>,
+    pub venue: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    pub source: String,
+    pub open_access: bool,
+}
+
+/// True when `year` falls inside the (optional) range. Papers with no
+/// known year are kept unless a range is set, since dropping them would
+/// silently hide otherwise valid results.
+pub fn within_year_range(year: Option<i32>, from: Option<i32>, to: Option<i32>) -> bool {
+    match year {
+        Some(year) => from.is_none_or(|f| year >= f) && to.is_none_or(|t| year <= t),
+        None => from.is_none() && to.is_none(),
+    }
+}
+
+/// Semantic Scholar's `year` query parameter: a single year or an
+/// open/closed range like `2019-2023`, `2019-` or `-2023`.
+pub fn semantic_scholar_year_param(from: Option<i32>, to: Option<i32>) -> Option<String> {
+    match (from, to) {
+        (None, None) => None,
+        (Some(f), Some(t)) if f == t => Some(f.to_string()),
+        (f, t) => Some(format!(
+            "{}-{}",
+            f.map(|y| y.to_string()).unwrap_or_default(),
+            t.map(|y| y.to_string()).unwrap_or_default()
+        )),
+    }
+}
+
+/// Crossref's `filter` parameter for the shared year range and
+/// open-access constraint.
+pub fn crossref_filter(from: Option<i32>, to: Option<i32>, open_access_only: bool) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(from) = from {
+        parts.push(format!("from-pub-date:{}-01-01", from));
+    }
+    if let Some(to) = to {
+        parts.push(format!("until-pub-date:{}-12-31", to));
+    }
+    if open_access_only {
+        parts.push("has-license:true".to_string());
+    }
+    (!parts.is_empty()).then(|| parts.join(","))
+}
+
+async fn search_semantic_scholar(
+    client: &reqwest::Client,
+    query: &str,
+    options: &AcademicSearchOptions,
+) -> AppResult<Vec<Paper>> {
+    let mut request = client
+        .get("https://api.semanticscholar.org/graph/v1/paper/search")
+        .query(&[
+            ("query", query),
+            ("limit", &options.semantic_scholar.limit.to_string()),
+            (
+                "fields",
+                "title,authors,year,venue,url,abstract,isOpenAccess,externalIds",
+            ),
+        ]);
+    if let Some(year) = semantic_scholar_year_param(options.year_from, options.year_to) {
+        request = request.query(&[("year", year)]);
+    }
+    if options.open_access_only {
+        request = request.query(&[("openAccessPdf", "")]);
+    }
+    let response: Value = request.send().await?.json().await?;
+    let papers = response
+        .get("data")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .map(|paper| Paper {
+            title: paper
+                .get("title")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            authors: paper
+                .get("authors")
+                .and_then(Value::as_array)
+                .map(|authors| {
+                    authors
+                        .iter()
+                        .filter_map(|a| a.get("name").and_then(Value::as_str))
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            year: paper.get("year").and_then(Value::as_i64).map(|y| y as i32),
+            venue: paper
+                .get("venue")
+                .and_then(Value::as_str)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string),
+            doi: paper
+                .pointer("/externalIds/DOI")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            url: paper.get("url").and_then(Value::as_str).map(str::to_string),
+            abstract_text: paper
+                .get("abstract")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            source: "semantic_scholar".to_string(),
+            open_access: paper
+                .get("isOpenAccess")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        })
+        .collect();
+    Ok(papers)
+}
+
+/// One `<entry>` element's child text content, entity-decoded.
+fn atom_field(entry: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}", tag);
+    let start = entry.find(&open)?;
+    let content_start = entry[start..].find('>')? + start + 1;
+    let close = format!("</{}>", tag);
+    let end = entry[content_start..].find(&close)? + content_start;
+    let text = crate::web::decode_entities(entry[content_start..end].trim());
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Parse arXiv's Atom feed by splitting on `<entry>` elements; the feed
+/// is machine-generated and regular enough that a full XML parser is not
+/// worth a dependency.
+pub fn parse_arxiv_feed(xml: &str) -> Vec<Paper> {
+    xml.split("<entry>")
+        .skip(1)
+        .map(|entry| {
+            let authors = entry
+                .split("<author>")
+                .skip(1)
+                .filter_map(|author| atom_field(author, "name"))
+                .collect();
+            Paper {
+                title: atom_field(entry, "title").unwrap_or_default(),
+                authors,
+                year: atom_field(entry, "published")
+                    .and_then(|p| p.get(..4).and_then(|y| y.parse().ok())),
+                venue: Some("arXiv".to_string()),
+                doi: atom_field(entry, "arxiv:doi"),
+                url: atom_field(entry, "id"),
+                abstract_text: atom_field(entry, "summary"),
+                source: "arxiv".to_string(),
+                // arXiv preprints are always freely accessible.
+                open_access: true,
+            }
+        })
+        .collect()
+}
+
+async fn search_arxiv(
+    client: &reqwest::Client,
+    query: &str,
+    options: &AcademicSearchOptions,
+) -> AppResult<Vec<Paper>> {
+    // arXiv has no year filter, so over-fetch and filter client-side.
+    let fetch = if options.year_from.is_some() || options.year_to.is_some() {
+        options.arxiv.limit * 4
+    } else {
+        options.arxiv.limit
+    };
+    let xml = client
+        .get("https://export.arxiv.org/api/query")
+        .query(&[
+            ("search_query", format!("all:{}", query).as_str()),
+            ("max_results", &fetch.to_string()),
+            ("sortBy", "relevance"),
+        ])
+        .send()
+        .await?
+        .text()
+        .await?;
+    let mut papers: Vec<Paper> = parse_arxiv_feed(&xml)
+        .into_iter()
+        .filter(|p| within_year_range(p.year, options.year_from, options.year_to))
+        .collect();
+    papers.truncate(options.arxiv.limit);
+    Ok(papers)
+}
+
+async fn search_crossref(
+    client: &reqwest::Client,
+    query: &str,
+    options: &AcademicSearchOptions,
+) -> AppResult<Vec<Paper>> {
+    let mut request = client
+        .get("https://api.crossref.org/works")
+        .query(&[
+            ("query", query),
+            ("rows", &options.crossref.limit.to_string()),
+        ])
+        .header("User-Agent", "cortex-ai-desktop");
+    if let Some(filter) = crossref_filter(options.year_from, options.year_to, options.open_access_only)
+    {
+        request = request.query(&[("filter", filter)]);
+    }
+    let response: Value = request.send().await?.json().await?;
+    let papers = response
+        .pointer("/message/items")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .map(|work| Paper {
+            title: work
+                .pointer("/title/0")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            authors: work
+                .get("author")
+                .and_then(Value::as_array)
+                .map(|authors| {
+                    authors
+                        .iter()
+                        .map(|a| {
+                            let given = a.get("given").and_then(Value::as_str).unwrap_or_default();
+                            let family = a.get("family").and_then(Value::as_str).unwrap_or_default();
+                            format!("{} {}", given, family).trim().to_string()
+                        })
+                        .filter(|name| !name.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            year: work
+                .pointer("/published/date-parts/0/0")
+                .and_then(Value::as_i64)
+                .map(|y| y as i32),
+            venue: work
+                .pointer("/container-title/0")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            doi: work.get("DOI").and_then(Value::as_str).map(str::to_string),
+            url: work.get("URL").and_then(Value::as_str).map(str::to_string),
+            abstract_text: work
+                .get("abstract")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            source: "crossref".to_string(),
+            open_access: work
+                .get("license")
+                .and_then(Value::as_array)
+                .is_some_and(|l| !l.is_empty()),
+        })
+        .collect();
+    Ok(papers)
+}
+
+pub(crate) fn load_defaults(db: &Db) -> AcademicSearchOptions {
+    settings::get(db, DEFAULTS_KEY)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_academic_search_defaults(db: State<Db>) -> AcademicSearchOptions {
+    load_defaults(&db)
+}
+
+#[tauri::command]
+pub fn set_academic_search_defaults(
+    db: State<Db>,
+    options: AcademicSearchOptions,
+) -> AppResult<()> {
+    settings::set(&db, DEFAULTS_KEY, &serde_json::to_string(&options)?)
+}
+
+/// Query the enabled sources and pool their results. A failing source is
+/// logged and skipped rather than failing the whole search; the
+/// remaining providers still return useful results.
+pub(crate) async fn run_search(query: &str, options: &AcademicSearchOptions) -> Vec<Paper> {
+    let client = reqwest::Client::new();
+    let mut papers = Vec::new();
+    if options.semantic_scholar.enabled && options.semantic_scholar.limit > 0 {
+        match search_semantic_scholar(&client, query, options).await {
+            Ok(results) => papers.extend(results),
+            Err(e) => tracing::warn!("semantic scholar search failed: {}", e),
+        }
+    }
+    if options.arxiv.enabled && options.arxiv.limit > 0 {
+        match search_arxiv(&client, query, options).await {
+            Ok(results) => papers.extend(results),
+            Err(e) => tracing::warn!("arxiv search failed: {}", e),
+        }
+    }
+    if options.crossref.enabled && options.crossref.limit > 0 {
+        match search_crossref(&client, query, options).await {
+            Ok(results) => papers.extend(results),
+            Err(e) => tracing::warn!("crossref search failed: {}", e),
+        }
+    }
+    if options.open_access_only {
+        papers.retain(|p| p.open_access);
+    }
+    papers.retain(|p| within_year_range(p.year, options.year_from, options.year_to));
+    papers
+}
+
+#[tauri::command]
+pub async fn search_academic(
+    db: State<'_, Db>,
+    query: String,
+    options: Option<AcademicSearchOptions>,
+) -> AppResult<Vec<Paper>> {
+    let options = options.unwrap_or_else(|| load_defaults(&db));
+    Ok(run_search(&query, &options).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crossref_filter, parse_arxiv_feed, semantic_scholar_year_param, within_year_range};
+
+    #[test]
+    fn year_range_bounds_are_inclusive() {
+        assert!(within_year_range(Some(2020), Some(2020), Some(2022)));
+        assert!(within_year_range(Some(2022), Some(2020), Some(2022)));
+        assert!(!within_year_range(Some(2019), Some(2020), None));
+        assert!(within_year_range(None, None, None));
+        assert!(!within_year_range(None, Some(2020), None));
+    }
+
+    #[test]
+    fn year_params_cover_open_ranges() {
+        assert_eq!(semantic_scholar_year_param(None, None), None);
+        assert_eq!(
+            semantic_scholar_year_param(Some(2021), Some(2021)),
+            Some("2021".to_string())
+        );
+        assert_eq!(
+            semantic_scholar_year_param(Some(2019), None),
+            Some("2019-".to_string())
+        );
+        assert_eq!(
+            crossref_filter(Some(2019), Some(2023), true),
+            Some("from-pub-date:2019-01-01,until-pub-date:2023-12-31,has-license:true".to_string())
+        );
+        assert_eq!(crossref_filter(None, None, false), None);
+    }
+
+    #[test]
+    fn arxiv_feed_entries_are_parsed() {
+        let xml = "<feed><entry><title>Paper\n  One</title>\
+                   <id>http://arxiv.org/abs/1234.5678</id>\
+                   <published>2023-04-01T00:00:00Z</published>\
+                   <summary>An abstract.</summary>\
+                   <author><name>Ada Lovelace</name></author>\
+                   <author><name>Alan Turing</name></author></entry></feed>";
+        let papers = parse_arxiv_feed(xml);
+        assert_eq!(papers.len(), 1);
+        assert_eq!(papers[0].title, "Paper One");
+        assert_eq!(papers[0].year, Some(2023));
+        assert_eq!(papers[0].authors, vec!["Ada Lovelace", "Alan Turing"]);
+        assert!(papers[0].open_access);
+    }
+}
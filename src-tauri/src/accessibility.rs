@@ -0,0 +1,159 @@
+//! Accessibility export: stream a chat as clean plain text over an
+//! event channel so screen readers and external TTS tools can consume
+//! conversations without scraping the UI. Markdown is stripped and code
+//! blocks are announced rather than read character by character.
+
+use regex::Regex;
+use rusqlite::params;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::compression;
+use crate::db::Db;
+use crate::error::AppResult;
+
+/// Upper bound per emitted chunk; long messages split on paragraph
+/// boundaries so TTS consumers can start speaking before the whole
+/// message arrives.
+const CHUNK_CHARS: usize = 2000;
+
+/// Flatten markdown into speakable plain text: headings, emphasis, list
+/// and quote markers are dropped, links read as their text, and fenced
+/// code blocks become labeled "Code block" sections.
+pub fn markdown_to_plaintext(markdown: &str) -> String {
+    let link = Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").expect("link regex is valid");
+    let mut out = Vec::new();
+    for (index, segment) in markdown.split("```").enumerate() {
+        if index % 2 == 1 {
+            // Odd segments are fenced code; the first line may name the
+            // language.
+            let mut lines = segment.lines();
+            let language = lines.next().unwrap_or_default().trim();
+            let body = lines.collect::<Vec<_>>().join("\n");
+            let label = if language.is_empty() {
+                "Code block:".to_string()
+            } else {
+                format!("Code block ({}):", language)
+            };
+            out.push(format!("{}\n{}\nEnd of code block.", label, body.trim_end()));
+            continue;
+        }
+        let mut text = Vec::new();
+        for line in segment.lines() {
+            let line = line
+                .trim_start_matches(|c: char| c == '#' || c == '>' || c.is_whitespace())
+                .trim_start_matches("- ")
+                .trim_start_matches("* ");
+            let line = link.replace_all(line, "$1");
+            let line = line.replace(['*', '_', '`'], "");
+            text.push(line);
+        }
+        let joined = text.join("\n");
+        if !joined.trim().is_empty() {
+            out.push(joined.trim().to_string());
+        }
+    }
+    out.join("\n")
+}
+
+/// Split text into chunks of at most `CHUNK_CHARS` along paragraph
+/// boundaries (a single oversized paragraph becomes its own chunk).
+fn chunk_plaintext(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split('\n') {
+        if !current.is_empty() && current.len() + paragraph.len() > CHUNK_CHARS {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(paragraph);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaintextChunk {
+    pub chat_id: String,
+    pub message_id: String,
+    pub role: String,
+    pub text: String,
+    /// Position of this chunk in the whole stream.
+    pub seq: usize,
+    /// Set on the final chunk of the chat.
+    pub done: bool,
+}
+
+/// Emit the chat's live messages as `plaintext-chunk` events, in
+/// conversation order, markdown stripped. The final event carries
+/// `done: true` so consumers know the stream is complete.
+#[tauri::command]
+pub fn stream_chat_plaintext(app: AppHandle, db: State<Db>, chat_id: String) -> AppResult<usize> {
+    let messages = {
+        let conn = db.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, role, content, compressed, content_zstd FROM messages
+             WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+        )?;
+        let rows = stmt
+            .query_map(params![chat_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    compression::stored(row.get(2)?, row.get::<_, i64>(3)? != 0, row.get(4)?),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows
+    };
+
+    let mut pending: Vec<(String, String, String)> = Vec::new();
+    for (message_id, role, content) in messages {
+        for chunk in chunk_plaintext(&markdown_to_plaintext(&content)) {
+            pending.push((message_id.clone(), role.clone(), chunk));
+        }
+    }
+    let total = pending.len();
+    for (seq, (message_id, role, text)) in pending.into_iter().enumerate() {
+        app.emit(
+            "plaintext-chunk",
+            &PlaintextChunk {
+                chat_id: chat_id.clone(),
+                message_id,
+                role,
+                text,
+                seq,
+                done: seq + 1 == total,
+            },
+        )?;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::markdown_to_plaintext;
+
+    #[test]
+    fn markdown_markup_is_stripped() {
+        let text = markdown_to_plaintext("# Title\n\nSome **bold** and a [link](https://x.dev).\n> quoted");
+        assert!(text.contains("Title"));
+        assert!(text.contains("Some bold and a link."));
+        assert!(text.contains("quoted"));
+        assert!(!text.contains('#'));
+        assert!(!text.contains("https://x.dev"));
+    }
+
+    #[test]
+    fn code_blocks_are_labeled() {
+        let text = markdown_to_plaintext("Before\n```rust\nfn main() {}\n```\nAfter");
+        assert!(text.contains("Code block (rust):"));
+        assert!(text.contains("fn main() {}"));
+        assert!(text.contains("End of code block."));
+        assert!(text.contains("After"));
+    }
+}
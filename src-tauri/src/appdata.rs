@@ -0,0 +1,264 @@
+//! Whole-application export and import: one archive containing a
+//! consistent database snapshot (chats, personas, templates, settings —
+//! everything in SQLite) plus the attachments directory, with a
+//! versioned manifest. Moving to a new machine becomes export, copy,
+//! import instead of hunting down the app data directory.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::operations;
+use crate::profiles;
+
+/// Bumped when the archive layout changes incompatibly.
+const FORMAT_VERSION: u32 = 1;
+
+/// Content tables merged on import. Infrastructure tables (journal,
+/// pulls, sync state, watchers) stay machine-local.
+const MERGE_TABLES: [&str; 8] = [
+    "chats",
+    "messages",
+    "attachments",
+    "personas",
+    "templates",
+    "settings",
+    "kb_documents",
+    "kb_chunks",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    app_version: String,
+    exported_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppDataExport {
+    pub path: String,
+    pub size: u64,
+}
+
+fn ensure_plaintext(app: &AppHandle) -> AppResult<std::path::PathBuf> {
+    let data_dir = profiles::data_dir(app).map_err(AppError::Io)?;
+    if crate::crypto::is_encrypted(&data_dir) {
+        return Err(AppError::InvalidInput(
+            "app data export/import requires an unencrypted database — disable encryption first"
+                .to_string(),
+        ));
+    }
+    Ok(data_dir)
+}
+
+/// Export the database and attachments into
+/// `<app data>/cortex-export-<timestamp>.zip` and return its path.
+#[tauri::command]
+pub async fn export_app_data(app: AppHandle, db: State<'_, Db>) -> AppResult<AppDataExport> {
+    let op = operations::start(&app, "app_export", "Exporting application data");
+    let result = build_export(&app, &db);
+    operations::finish(&app, &op, if result.is_ok() { "done" } else { "failed" });
+    result
+}
+
+fn build_export(app: &AppHandle, db: &Db) -> AppResult<AppDataExport> {
+    let data_dir = ensure_plaintext(app)?;
+    let out_path = data_dir.join(format!(
+        "cortex-export-{}.zip",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    // VACUUM INTO writes a consistent snapshot without closing the live
+    // connection.
+    let snapshot = data_dir.join("cortex-export.db.tmp");
+    let _ = fs::remove_file(&snapshot);
+    db.conn().execute(
+        "VACUUM INTO ?1",
+        [snapshot.to_string_lossy().into_owned()],
+    )?;
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: crate::db::now(),
+    };
+    let file = fs::File::create(&out_path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file("manifest.json", options)
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    zip.start_file("cortex.db", options)
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    zip.write_all(&fs::read(&snapshot)?)?;
+    let _ = fs::remove_file(&snapshot);
+
+    let attachments_dir = data_dir.join("attachments");
+    if attachments_dir.is_dir() {
+        for entry in fs::read_dir(&attachments_dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            zip.start_file(format!("attachments/{}", name), options)
+                .map_err(|e| AppError::Io(e.to_string()))?;
+            zip.write_all(&fs::read(&path)?)?;
+        }
+    }
+    zip.finish().map_err(|e| AppError::Io(e.to_string()))?;
+    let size = fs::metadata(&out_path)?.len();
+    Ok(AppDataExport {
+        path: out_path.to_string_lossy().to_string(),
+        size,
+    })
+}
+
+fn read_manifest(archive: &mut zip::ZipArchive<fs::File>) -> AppResult<Manifest> {
+    let mut raw = String::new();
+    archive
+        .by_name("manifest.json")
+        .map_err(|_| AppError::InvalidInput("not a cortex export: manifest.json missing".to_string()))?
+        .read_to_string(&mut raw)?;
+    let manifest: Manifest = serde_json::from_str(&raw)?;
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "archive format v{} is newer than this app understands (v{}) — update first",
+            manifest.format_version, FORMAT_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+fn extract_attachments(
+    archive: &mut zip::ZipArchive<fs::File>,
+    data_dir: &Path,
+    overwrite: bool,
+) -> AppResult<()> {
+    let attachments_dir = data_dir.join("attachments");
+    fs::create_dir_all(&attachments_dir)?;
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        let Some(name) = entry.name().strip_prefix("attachments/") else {
+            continue;
+        };
+        // Archive names are untrusted: keep extraction inside the
+        // attachments directory.
+        if name.is_empty() || name.contains('/') || name.contains("..") {
+            continue;
+        }
+        let target = attachments_dir.join(name);
+        if target.exists() && !overwrite {
+            continue;
+        }
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        fs::write(&target, contents)?;
+    }
+    Ok(())
+}
+
+/// Merge the imported database into the live one: rows whose primary key
+/// already exists locally are kept as-is, everything else is inserted.
+fn merge_database(db: &Db, imported: &std::path::PathBuf) -> AppResult<()> {
+    // Normalize the imported file through the current schema so both
+    // sides have identical column order before the blind SELECT *.
+    crate::db::open_connection(imported, None)?;
+    let conn = db.conn();
+    conn.execute(
+        "ATTACH DATABASE ?1 AS import",
+        [imported.to_string_lossy().into_owned()],
+    )?;
+    let result = (|| -> AppResult<()> {
+        for table in MERGE_TABLES {
+            conn.execute(
+                &format!("INSERT OR IGNORE INTO {0} SELECT * FROM import.{0}", table),
+                [],
+            )?;
+        }
+        Ok(())
+    })();
+    let _ = conn.execute("DETACH DATABASE import", []);
+    result
+}
+
+/// Import an archive produced by `export_app_data`. `mode` is `merge`
+/// (keep local rows on conflict, add everything new) or `replace` (swap
+/// the whole database in; the previous one is kept next to it as a
+/// timestamped backup).
+#[tauri::command]
+pub async fn import_app_data(
+    app: AppHandle,
+    db: State<'_, Db>,
+    path: String,
+    mode: String,
+) -> AppResult<Value> {
+    let op = operations::start(&app, "app_import", "Importing application data");
+    let result = run_import(&app, &db, &path, &mode);
+    operations::finish(&app, &op, if result.is_ok() { "done" } else { "failed" });
+    result
+}
+
+fn run_import(app: &AppHandle, db: &Db, path: &str, mode: &str) -> AppResult<Value> {
+    let data_dir = ensure_plaintext(app)?;
+    let file = fs::File::open(path)
+        .map_err(|e| AppError::InvalidInput(format!("cannot open {}: {}", path, e)))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| AppError::Io(e.to_string()))?;
+    let manifest = read_manifest(&mut archive)?;
+
+    let imported_db = data_dir.join("cortex-import.db.tmp");
+    {
+        let mut entry = archive
+            .by_name("cortex.db")
+            .map_err(|_| AppError::InvalidInput("not a cortex export: cortex.db missing".to_string()))?;
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        fs::write(&imported_db, contents)?;
+    }
+
+    match mode {
+        "merge" => {
+            merge_database(db, &imported_db)?;
+            let _ = fs::remove_file(&imported_db);
+            extract_attachments(&mut archive, &data_dir, false)?;
+        }
+        "replace" => {
+            let live = data_dir.join("cortex.db");
+            let backup = data_dir.join(format!(
+                "cortex.db.pre-import-{}",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            ));
+            // Release the file handle before swapping files.
+            *db.conn() = rusqlite::Connection::open_in_memory()?;
+            if live.exists() {
+                fs::rename(&live, &backup)?;
+            }
+            fs::rename(&imported_db, &live)?;
+            db.switch_to(data_dir.clone())?;
+            extract_attachments(&mut archive, &data_dir, true)?;
+        }
+        other => {
+            let _ = fs::remove_file(&imported_db);
+            return Err(AppError::InvalidInput(format!(
+                "unknown import mode '{}' (expected 'merge' or 'replace')",
+                other
+            )));
+        }
+    }
+    Ok(serde_json::json!({
+        "mode": mode,
+        "exported_at": manifest.exported_at,
+        "from_app_version": manifest.app_version,
+    }))
+}
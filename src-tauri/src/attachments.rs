@@ -0,0 +1,201 @@
+//! File attachments: copy a file into app data, extract whatever text we
+//! can from it, and surface that text to the next user message.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: String,
+    pub chat_id: String,
+    pub file_name: String,
+    pub stored_path: String,
+    pub kind: String,
+    pub extracted_text: Option<String>,
+    pub pending: bool,
+    pub created_at: String,
+}
+
+/// File extensions we treat as plain text / source code.
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "csv", "json", "yaml", "yml", "toml", "xml", "html", "css", "js", "jsx", "ts",
+    "tsx", "rs", "py", "go", "java", "c", "cpp", "h", "hpp", "sh", "rb", "sql", "log",
+];
+
+fn classify(path: &Path) -> String {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "pdf" => "pdf".to_string(),
+        "docx" => "docx".to_string(),
+        e if TEXT_EXTENSIONS.contains(&e) => "text".to_string(),
+        _ => "binary".to_string(),
+    }
+}
+
+fn extract_docx_text(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let docx = docx_rs::read_docx(&bytes).map_err(|e| e.to_string())?;
+    let mut text = String::new();
+    for child in docx.document.children {
+        if let docx_rs::DocumentChild::Paragraph(para) = child {
+            for pc in para.children {
+                if let docx_rs::ParagraphChild::Run(run) = pc {
+                    for rc in run.children {
+                        if let docx_rs::RunChild::Text(t) = rc {
+                            text.push_str(&t.text);
+                        }
+                    }
+                }
+            }
+            text.push('\n');
+        }
+    }
+    Ok(text)
+}
+
+/// Best-effort text extraction; binary files we can't read yield `None`
+/// rather than an error so the attachment is still recorded.
+fn extract_text(path: &Path, kind: &str) -> Option<String> {
+    let result = match kind {
+        "pdf" => pdf_extract::extract_text(path).map_err(|e| e.to_string()),
+        "docx" => extract_docx_text(path),
+        "text" => fs::read_to_string(path).map_err(|e| e.to_string()),
+        _ => return None,
+    };
+    match result {
+        Ok(text) if !text.trim().is_empty() => Some(text),
+        _ => None,
+    }
+}
+
+fn attachments_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("attachments");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Copy `path` into app data, extract text, and record the attachment as
+/// pending so it rides along with the next user message in the chat.
+pub fn store_attachment(
+    app: &AppHandle,
+    db: &Db,
+    chat_id: &str,
+    path: &str,
+) -> Result<Attachment, String> {
+    let source = PathBuf::from(path);
+    let file_name = source
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("invalid file path: {}", path))?
+        .to_string();
+
+    let id = Uuid::new_v4().to_string();
+    let stored_path = attachments_dir(app)?.join(format!("{}_{}", id, file_name));
+    fs::copy(&source, &stored_path).map_err(|e| e.to_string())?;
+
+    let kind = classify(&source);
+    let extracted_text = extract_text(&stored_path, &kind);
+
+    let attachment = Attachment {
+        id,
+        chat_id: chat_id.to_string(),
+        file_name,
+        stored_path: stored_path.to_string_lossy().into_owned(),
+        kind,
+        extracted_text,
+        pending: true,
+        created_at: db::now(),
+    };
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO attachments (id, chat_id, file_name, stored_path, kind, extracted_text, pending, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7)",
+        params![
+            attachment.id,
+            attachment.chat_id,
+            attachment.file_name,
+            attachment.stored_path,
+            attachment.kind,
+            attachment.extracted_text,
+            attachment.created_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(attachment)
+}
+
+#[tauri::command]
+pub fn attach_file(
+    app: AppHandle,
+    db: State<Db>,
+    chat_id: String,
+    path: String,
+) -> Result<Attachment, String> {
+    store_attachment(&app, &db, &chat_id, &path)
+}
+
+#[tauri::command]
+pub fn get_attachments(db: State<Db>, chat_id: String) -> Result<Vec<Attachment>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, chat_id, file_name, stored_path, kind, extracted_text, pending, created_at
+             FROM attachments WHERE chat_id = ?1 ORDER BY created_at ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![chat_id], row_to_attachment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Return pending attachments for a chat and mark them consumed.
+pub fn take_pending(db: &Db, chat_id: &str) -> Result<Vec<Attachment>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, chat_id, file_name, stored_path, kind, extracted_text, pending, created_at
+             FROM attachments WHERE chat_id = ?1 AND pending = 1 ORDER BY created_at ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![chat_id], row_to_attachment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE attachments SET pending = 0 WHERE chat_id = ?1 AND pending = 1",
+        params![chat_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn row_to_attachment(row: &rusqlite::Row) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        chat_id: row.get(1)?,
+        file_name: row.get(2)?,
+        stored_path: row.get(3)?,
+        kind: row.get(4)?,
+        extracted_text: row.get(5)?,
+        pending: row.get::<_, i64>(6)? != 0,
+        created_at: row.get(7)?,
+    })
+}
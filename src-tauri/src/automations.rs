@@ -0,0 +1,259 @@
+//! Scheduled and recurring prompts. An automation is a prompt + model +
+//! cron-like schedule; a background scheduler wakes once a minute, runs
+//! every due automation as a normal generation into its designated
+//! chat, and notifies the user when the output lands. The schedule
+//! syntax is the classic five fields (minute hour day-of-month month
+//! day-of-week) with `*`, numbers, comma lists and `*/n` steps.
+
+use chrono::{Datelike, Local, Timelike};
+use rusqlite::params;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Automation {
+    pub id: String,
+    pub name: String,
+    pub prompt: String,
+    pub model: String,
+    pub chat_id: String,
+    pub schedule: String,
+    pub enabled: bool,
+    pub last_run_at: Option<String>,
+    pub created_at: String,
+}
+
+/// Does one cron field match a value? Supports `*`, `*/n`, numbers and
+/// comma lists of either.
+fn field_matches(field: &str, value: u32) -> bool {
+    field.split(',').any(|part| {
+        if part == "*" {
+            true
+        } else if let Some(step) = part.strip_prefix("*/") {
+            step.parse::<u32>().map(|s| s > 0 && value % s == 0).unwrap_or(false)
+        } else {
+            part.parse::<u32>().map(|n| n == value).unwrap_or(false)
+        }
+    })
+}
+
+/// Does a five-field cron expression match the given local time?
+/// Malformed expressions never match.
+pub fn schedule_matches(schedule: &str, time: &chrono::DateTime<Local>) -> bool {
+    let fields: Vec<&str> = schedule.split_whitespace().collect();
+    if fields.len() != 5 {
+        return false;
+    }
+    field_matches(fields[0], time.minute())
+        && field_matches(fields[1], time.hour())
+        && field_matches(fields[2], time.day())
+        && field_matches(fields[3], time.month())
+        && field_matches(fields[4], time.weekday().num_days_from_sunday())
+}
+
+#[tauri::command]
+pub fn add_automation(
+    db: State<Db>,
+    name: String,
+    prompt: String,
+    model: String,
+    chat_id: String,
+    schedule: String,
+) -> Result<Automation, String> {
+    if schedule.split_whitespace().count() != 5 {
+        return Err("schedule must have five cron fields".to_string());
+    }
+    let automation = Automation {
+        id: Uuid::new_v4().to_string(),
+        name,
+        prompt,
+        model,
+        chat_id,
+        schedule,
+        enabled: true,
+        last_run_at: None,
+        created_at: db::now(),
+    };
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO automations (id, name, prompt, model, chat_id, schedule, enabled, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7)",
+        params![
+            automation.id,
+            automation.name,
+            automation.prompt,
+            automation.model,
+            automation.chat_id,
+            automation.schedule,
+            automation.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(automation)
+}
+
+#[tauri::command]
+pub fn remove_automation(db: State<Db>, automation_id: String) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute(
+        "DELETE FROM automations WHERE id = ?1",
+        params![automation_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_automation_enabled(
+    db: State<Db>,
+    automation_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE automations SET enabled = ?1 WHERE id = ?2",
+        params![enabled as i64, automation_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn get_automations_internal(db: &Db) -> Result<Vec<Automation>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, prompt, model, chat_id, schedule, enabled, last_run_at, created_at
+             FROM automations ORDER BY created_at ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let automations = stmt
+        .query_map([], |row| {
+            Ok(Automation {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                prompt: row.get(2)?,
+                model: row.get(3)?,
+                chat_id: row.get(4)?,
+                schedule: row.get(5)?,
+                enabled: row.get::<_, i64>(6)? != 0,
+                last_run_at: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(automations)
+}
+
+#[tauri::command]
+pub fn get_automations(db: State<Db>) -> Result<Vec<Automation>, String> {
+    get_automations_internal(&db)
+}
+
+async fn run_automation(app: &AppHandle, automation: &Automation) {
+    let db = app.state::<Db>();
+    {
+        let conn = db.conn();
+        let _ = conn.execute(
+            "UPDATE automations SET last_run_at = ?1 WHERE id = ?2",
+            params![db::now(), automation.id],
+        );
+    }
+    match crate::chat::run_generation(
+        app,
+        &db,
+        &automation.chat_id,
+        &automation.model,
+        &automation.prompt,
+        None,
+    )
+    .await
+    {
+        Ok(message) => crate::tray::notify_if_unfocused(
+            app,
+            &format!("Automation: {}", automation.name),
+            &crate::tray::preview(&message.content, 120),
+        ),
+        Err(e) => crate::tray::notify_if_unfocused(
+            app,
+            &format!("Automation failed: {}", automation.name),
+            &e.to_string(),
+        ),
+    }
+}
+
+#[tauri::command]
+pub async fn run_automation_now(app: AppHandle, automation_id: String) -> Result<(), String> {
+    let automation = {
+        let db = app.state::<Db>();
+        get_automations_internal(&db)?
+            .into_iter()
+            .find(|a| a.id == automation_id)
+            .ok_or_else(|| format!("no automation {}", automation_id))?
+    };
+    run_automation(&app, &automation).await;
+    Ok(())
+}
+
+/// Wake once a minute and run every enabled automation whose schedule
+/// matches the current local minute. `last_run_at` guards against
+/// double-firing within the same minute.
+pub fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            let now = Local::now();
+            let due: Vec<Automation> = {
+                let db = app.state::<Db>();
+                match get_automations_internal(&db) {
+                    Ok(automations) => automations
+                        .into_iter()
+                        .filter(|a| a.enabled && schedule_matches(&a.schedule, &now))
+                        .filter(|a| {
+                            a.last_run_at
+                                .as_deref()
+                                .and_then(|t| t.parse::<chrono::DateTime<chrono::Utc>>().ok())
+                                .map(|t| (chrono::Utc::now() - t).num_seconds() >= 60)
+                                .unwrap_or(true)
+                        })
+                        .collect(),
+                    Err(_) => Vec::new(),
+                }
+            };
+            for automation in due {
+                run_automation(&app, &automation).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::field_matches;
+
+    #[test]
+    fn wildcard_and_literal_fields() {
+        assert!(field_matches("*", 17));
+        assert!(field_matches("30", 30));
+        assert!(!field_matches("30", 31));
+    }
+
+    #[test]
+    fn steps_and_lists() {
+        assert!(field_matches("*/15", 45));
+        assert!(!field_matches("*/15", 20));
+        assert!(field_matches("1,15,30", 15));
+        assert!(!field_matches("1,15,30", 16));
+    }
+
+    #[test]
+    fn malformed_field_never_matches() {
+        assert!(!field_matches("tuesday", 2));
+        assert!(!field_matches("*/0", 0));
+    }
+}
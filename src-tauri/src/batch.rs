@@ -0,0 +1,468 @@
+//! Batch mode: run one prompt template over many inputs. Inputs come in
+//! as CSV (first row is headers) or JSONL (one object per line); each
+//! row is substituted into the template via `{{column}}` placeholders
+//! and queued against a model with configurable concurrency. Results
+//! persist per item, progress streams as `batch-progress` events, and a
+//! running job can be paused, resumed or cancelled.
+
+use futures_util::StreamExt;
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::ollama::OLLAMA_BASE_URL;
+use crate::operations;
+
+/// Pause/cancel flags for running jobs, keyed by job id.
+#[derive(Default)]
+pub struct BatchState(pub Mutex<HashMap<String, Arc<BatchControl>>>);
+
+#[derive(Default)]
+pub struct BatchControl {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchJob {
+    pub id: String,
+    pub model: String,
+    pub template: String,
+    pub status: String,
+    pub total: usize,
+    pub completed: usize,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItem {
+    pub id: String,
+    pub position: usize,
+    pub input: Value,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub job_id: String,
+    pub completed: usize,
+    pub total: usize,
+    pub status: String,
+}
+
+/// Minimal CSV reader: comma-separated, double quotes escape commas and
+/// newlines, `""` inside a quoted field is a literal quote. The first
+/// record names the columns.
+fn parse_csv(raw: &str) -> Result<Vec<Map<String, Value>>, String> {
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+        } else {
+            match c {
+                '"' => in_quotes = true,
+                ',' => record.push(std::mem::take(&mut field)),
+                '\r' => {}
+                '\n' => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                _ => field.push(c),
+            }
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    let mut records = records.into_iter();
+    let headers = records.next().ok_or("CSV input is empty")?;
+    let rows = records
+        .filter(|r| !(r.len() == 1 && r[0].is_empty()))
+        .map(|r| {
+            headers
+                .iter()
+                .zip(r)
+                .map(|(h, v)| (h.clone(), Value::String(v)))
+                .collect()
+        })
+        .collect();
+    Ok(rows)
+}
+
+fn parse_jsonl(raw: &str) -> Result<Vec<Map<String, Value>>, String> {
+    raw.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str::<Value>(line)
+                .map_err(|e| e.to_string())?
+                .as_object()
+                .cloned()
+                .ok_or_else(|| "JSONL line is not an object".to_string())
+        })
+        .collect()
+}
+
+/// Substitute `{{column}}` placeholders with row values.
+fn render_prompt(template: &str, row: &Map<String, Value>) -> String {
+    let mut prompt = template.to_string();
+    for (key, value) in row {
+        let text = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        prompt = prompt.replace(&format!("{{{{{}}}}}", key), &text);
+    }
+    prompt
+}
+
+async fn generate(model: &str, prompt: &str) -> Result<String, String> {
+    let client = reqwest::Client::new();
+    let response: Value = client
+        .post(format!("{}/api/generate", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({ "model": model, "prompt": prompt, "stream": false }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(response
+        .get("response")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string())
+}
+
+fn set_job_status(db: &Db, job_id: &str, status: &str) {
+    let conn = db.conn();
+    let _ = conn.execute(
+        "UPDATE batch_jobs SET status = ?1 WHERE id = ?2",
+        params![status, job_id],
+    );
+}
+
+fn emit_progress(app: &AppHandle, job_id: &str, completed: usize, total: usize, status: &str) {
+    let _ = app.emit(
+        "batch-progress",
+        &BatchProgress {
+            job_id: job_id.to_string(),
+            completed,
+            total,
+            status: status.to_string(),
+        },
+    );
+}
+
+async fn run_job(
+    app: AppHandle,
+    job_id: String,
+    model: String,
+    template: String,
+    rows: Vec<(String, Map<String, Value>)>,
+    concurrency: usize,
+    control: Arc<BatchControl>,
+) {
+    let total = rows.len();
+    let op = operations::start(&app, "batch", &format!("Batch job ({} items)", total));
+    futures_util::stream::iter(rows)
+        .map(|(item_id, row)| {
+            let app = app.clone();
+            let job_id = job_id.clone();
+            let op = op.clone();
+            let model = model.clone();
+            let template = template.clone();
+            let control = control.clone();
+            async move {
+                while control.paused.load(Ordering::Relaxed)
+                    && !control.cancelled.load(Ordering::Relaxed)
+                {
+                    tokio::time::sleep(Duration::from_millis(250)).await;
+                }
+                let db = app.state::<Db>();
+                if control.cancelled.load(Ordering::Relaxed) {
+                    let conn = db.conn();
+                    let _ = conn.execute(
+                        "UPDATE batch_items SET status = 'cancelled' WHERE id = ?1",
+                        params![item_id],
+                    );
+                    return;
+                }
+                let prompt = render_prompt(&template, &row);
+                let result = generate(&model, &prompt).await;
+                let conn = db.conn();
+                match result {
+                    Ok(output) => {
+                        let _ = conn.execute(
+                            "UPDATE batch_items SET status = 'done', output = ?1 WHERE id = ?2",
+                            params![output, item_id],
+                        );
+                    }
+                    Err(e) => {
+                        let _ = conn.execute(
+                            "UPDATE batch_items SET status = 'failed', error = ?1 WHERE id = ?2",
+                            params![e, item_id],
+                        );
+                    }
+                }
+                let _ = conn.execute(
+                    "UPDATE batch_jobs SET completed = completed + 1 WHERE id = ?1",
+                    params![job_id],
+                );
+                let completed: i64 = conn
+                    .query_row(
+                        "SELECT completed FROM batch_jobs WHERE id = ?1",
+                        params![job_id],
+                        |r| r.get(0),
+                    )
+                    .unwrap_or(0);
+                drop(conn);
+                emit_progress(&app, &job_id, completed as usize, total, "running");
+                operations::progress(&app, &op, completed as f64 / total.max(1) as f64);
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<()>>()
+        .await;
+    let db = app.state::<Db>();
+    let status = if control.cancelled.load(Ordering::Relaxed) {
+        "cancelled"
+    } else {
+        "done"
+    };
+    set_job_status(&db, &job_id, status);
+    emit_progress(&app, &job_id, total, total, status);
+    operations::finish(&app, &op, if status == "done" { "done" } else { "failed" });
+    app.state::<BatchState>().0.lock().unwrap().remove(&job_id);
+}
+
+/// Parse the inputs, persist the job and its items, and start the queue
+/// in the background. `input_format` is `csv` or `jsonl`.
+#[tauri::command]
+pub fn submit_batch(
+    app: AppHandle,
+    db: State<Db>,
+    state: State<BatchState>,
+    model: String,
+    template: String,
+    inputs: String,
+    input_format: String,
+    concurrency: usize,
+) -> Result<BatchJob, String> {
+    let rows = match input_format.as_str() {
+        "csv" => parse_csv(&inputs)?,
+        "jsonl" => parse_jsonl(&inputs)?,
+        other => return Err(format!("unknown input format: {}", other)),
+    };
+    if rows.is_empty() {
+        return Err("no input rows".to_string());
+    }
+    let job = BatchJob {
+        id: Uuid::new_v4().to_string(),
+        model,
+        template,
+        status: "running".to_string(),
+        total: rows.len(),
+        completed: 0,
+        created_at: db::now(),
+    };
+    let mut items = Vec::with_capacity(rows.len());
+    {
+        let conn = db.conn();
+        conn.execute(
+            "INSERT INTO batch_jobs (id, model, template, status, total, completed, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6)",
+            params![
+                job.id,
+                job.model,
+                job.template,
+                job.status,
+                job.total as i64,
+                job.created_at
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        for (position, row) in rows.into_iter().enumerate() {
+            let item_id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO batch_items (id, job_id, position, input, status)
+                 VALUES (?1, ?2, ?3, ?4, 'queued')",
+                params![
+                    item_id,
+                    job.id,
+                    position as i64,
+                    Value::Object(row.clone()).to_string()
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            items.push((item_id, row));
+        }
+    }
+    let control = Arc::new(BatchControl::default());
+    state
+        .0
+        .lock()
+        .unwrap()
+        .insert(job.id.clone(), control.clone());
+    tauri::async_runtime::spawn(run_job(
+        app.clone(),
+        job.id.clone(),
+        job.model.clone(),
+        job.template.clone(),
+        items,
+        concurrency,
+        control,
+    ));
+    Ok(job)
+}
+
+fn control_for(state: &BatchState, job_id: &str) -> Result<Arc<BatchControl>, String> {
+    state
+        .0
+        .lock()
+        .unwrap()
+        .get(job_id)
+        .cloned()
+        .ok_or_else(|| format!("no running batch job {}", job_id))
+}
+
+#[tauri::command]
+pub fn pause_batch(db: State<Db>, state: State<BatchState>, job_id: String) -> Result<(), String> {
+    control_for(&state, &job_id)?
+        .paused
+        .store(true, Ordering::Relaxed);
+    set_job_status(&db, &job_id, "paused");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resume_batch(db: State<Db>, state: State<BatchState>, job_id: String) -> Result<(), String> {
+    control_for(&state, &job_id)?
+        .paused
+        .store(false, Ordering::Relaxed);
+    set_job_status(&db, &job_id, "running");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn cancel_batch(state: State<BatchState>, job_id: String) -> Result<(), String> {
+    let control = control_for(&state, &job_id)?;
+    control.cancelled.store(true, Ordering::Relaxed);
+    control.paused.store(false, Ordering::Relaxed);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_batch_jobs(db: State<Db>) -> Result<Vec<BatchJob>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, model, template, status, total, completed, created_at
+             FROM batch_jobs ORDER BY created_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let jobs = stmt
+        .query_map([], |row| {
+            Ok(BatchJob {
+                id: row.get(0)?,
+                model: row.get(1)?,
+                template: row.get(2)?,
+                status: row.get(3)?,
+                total: row.get::<_, i64>(4)? as usize,
+                completed: row.get::<_, i64>(5)? as usize,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(jobs)
+}
+
+#[tauri::command]
+pub fn get_batch_items(db: State<Db>, job_id: String) -> Result<Vec<BatchItem>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, position, input, output, error, status
+             FROM batch_items WHERE job_id = ?1 ORDER BY position ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let items = stmt
+        .query_map(params![job_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    items
+        .into_iter()
+        .map(|(id, position, input, output, error, status)| {
+            Ok(BatchItem {
+                id,
+                position: position as usize,
+                input: serde_json::from_str(&input).map_err(|e| e.to_string())?,
+                output,
+                error,
+                status,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_quoted_fields_keep_commas_and_quotes() {
+        let rows = parse_csv("text,label\n\"a, b\",x\n\"say \"\"hi\"\"\",y\n").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["text"], "a, b");
+        assert_eq!(rows[1]["text"], "say \"hi\"");
+        assert_eq!(rows[1]["label"], "y");
+    }
+
+    #[test]
+    fn csv_without_trailing_newline() {
+        let rows = parse_csv("a,b\n1,2").unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["b"], "2");
+    }
+
+    #[test]
+    fn render_prompt_substitutes_row_values() {
+        let rows = parse_csv("text\nhello\n").unwrap();
+        assert_eq!(
+            render_prompt("Classify: {{text}}", &rows[0]),
+            "Classify: hello"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Optional response cache for identical prompts. Repeated requests with
+//! the same model, normalized message list and parameters (common in
+//! batch workflows and template testing) are answered from the
+//! `prompt_cache` table instead of hitting Ollama. Chats can opt out via
+//! `set_prompt_cache_bypass`, and tool-enabled requests are never cached
+//! because tool results vary between runs.
+
+use rusqlite::params;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::AppResult;
+use crate::journal;
+
+/// Stable key for a request: model, each message's role and
+/// whitespace-trimmed content, and the format/params. Trimming means a
+/// trailing newline in a template does not defeat the cache. SHA-256
+/// because keys persist in the database: the algorithm must not change
+/// between Rust releases, which `DefaultHasher` does not guarantee.
+pub fn cache_key(model: &str, messages: &Value, format: &Option<Value>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hasher.update(b"\x1e");
+    if let Some(messages) = messages.as_array() {
+        for message in messages {
+            hasher.update(
+                message
+                    .get("role")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .as_bytes(),
+            );
+            hasher.update(b"\x1f");
+            hasher.update(
+                message
+                    .get("content")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .trim()
+                    .as_bytes(),
+            );
+            hasher.update(b"\x1e");
+        }
+    }
+    if let Some(format) = format {
+        hasher.update(format.to_string().as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+pub fn lookup(db: &Db, key: &str) -> Option<String> {
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT response FROM prompt_cache WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+pub fn store(db: &Db, key: &str, model: &str, response: &str) {
+    let conn = db.conn();
+    let _ = conn.execute(
+        "INSERT OR REPLACE INTO prompt_cache (key, model, response, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![key, model, response, crate::db::now()],
+    );
+}
+
+pub fn is_bypassed(db: &Db, chat_id: &str) -> bool {
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT bypass_prompt_cache FROM chats WHERE id = ?1",
+        params![chat_id],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|v| v != 0)
+    .unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn clear_prompt_cache(db: State<Db>) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute("DELETE FROM prompt_cache", [])?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_prompt_cache_bypass(db: State<Db>, chat_id: String, bypass: bool) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET bypass_prompt_cache = ?1 WHERE id = ?2",
+        params![bypass as i64, chat_id],
+    )?;
+    journal::record(
+        &conn,
+        "chat",
+        &chat_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "bypass_prompt_cache": bypass }).to_string()),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cache_key;
+    use serde_json::json;
+
+    #[test]
+    fn whitespace_differences_share_a_key() {
+        let a = json!([{ "role": "user", "content": "Summarize this.\n" }]);
+        let b = json!([{ "role": "user", "content": "Summarize this." }]);
+        assert_eq!(cache_key("llama3", &a, &None), cache_key("llama3", &b, &None));
+    }
+
+    #[test]
+    fn model_and_content_change_the_key() {
+        let messages = json!([{ "role": "user", "content": "hi" }]);
+        assert_ne!(
+            cache_key("llama3", &messages, &None),
+            cache_key("mistral", &messages, &None)
+        );
+        assert_ne!(
+            cache_key("llama3", &messages, &None),
+            cache_key(
+                "llama3",
+                &json!([{ "role": "user", "content": "hello" }]),
+                &None
+            )
+        );
+    }
+
+    #[test]
+    fn format_is_part_of_the_key() {
+        let messages = json!([{ "role": "user", "content": "hi" }]);
+        assert_ne!(
+            cache_key("llama3", &messages, &None),
+            cache_key("llama3", &messages, &Some(json!("json")))
+        );
+    }
+}
@@ -0,0 +1,2095 @@
+//! Chat lifecycle commands and streaming generation against Ollama.
+
+use futures_util::StreamExt;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+use crate::attachments;
+use crate::cache;
+use crate::chatrefs;
+use crate::commands;
+use crate::compression;
+use crate::constraints::Constraint;
+use crate::context::{self, ChatContext, PruningPolicy};
+use crate::db::{self, Db};
+use crate::error::{AppError, AppResult};
+use crate::hooks;
+use crate::journal;
+use crate::knowledge;
+use crate::mcp;
+use crate::ndjson::NdjsonDecoder;
+use crate::ollama::{self, OLLAMA_BASE_URL};
+use crate::permissions;
+use crate::plugins;
+use crate::postprocess;
+use crate::privacy;
+use crate::settings;
+use crate::sources;
+use crate::structured;
+use crate::translate;
+use crate::tray;
+use crate::triggers;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chat {
+    pub id: String,
+    pub title: String,
+    pub model: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub id: String,
+    pub chat_id: String,
+    pub role: String,
+    pub content: String,
+    /// Which model produced this message; `None` for user messages and
+    /// rows from before this column existed.
+    pub model: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatToken {
+    pub chat_id: String,
+    pub message_id: String,
+    pub token: String,
+    pub done: bool,
+}
+
+/// Partial structured-output object emitted while a JSON response streams.
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuredPartial {
+    pub chat_id: String,
+    pub message_id: String,
+    pub value: Value,
+    pub done: bool,
+}
+
+#[tauri::command]
+pub fn create_chat(app: AppHandle, db: State<Db>, title: String, model: String) -> AppResult<Chat> {
+    let chat = create_chat_internal(&db, title, model)?;
+    hooks::fire(
+        &app,
+        "chat-created",
+        serde_json::to_value(&chat).unwrap_or_default(),
+    );
+    Ok(chat)
+}
+
+pub(crate) fn create_chat_internal(db: &Db, title: String, model: String) -> AppResult<Chat> {
+    let chat = Chat {
+        id: Uuid::new_v4().to_string(),
+        title,
+        model,
+        created_at: db::now(),
+        updated_at: db::now(),
+    };
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO chats (id, title, model, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![chat.id, chat.title, chat.model, chat.created_at, chat.updated_at],
+    )?;
+    journal::record(
+        &conn,
+        "chat",
+        &chat.id,
+        journal::Op::Create,
+        serde_json::to_string(&chat).ok(),
+    );
+    Ok(chat)
+}
+
+pub(crate) fn get_chats_internal(db: &Db) -> AppResult<Vec<Chat>> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, model, created_at, updated_at FROM chats
+             WHERE deleted_at IS NULL ORDER BY updated_at DESC",
+        )?;
+    let chats = stmt
+        .query_map([], |row| {
+            Ok(Chat {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                model: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(chats)
+}
+
+#[tauri::command]
+pub fn get_chats(db: State<Db>) -> AppResult<Vec<Chat>> {
+    get_chats_internal(&db)
+}
+
+#[tauri::command]
+pub fn get_messages(db: State<Db>, chat_id: String) -> AppResult<Vec<Message>> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, chat_id, role, content, model, created_at, compressed, content_zstd
+             FROM messages
+             WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+        )?;
+    let messages = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(Message {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                role: row.get(2)?,
+                content: compression::stored(row.get(3)?, row.get::<_, i64>(6)? != 0, row.get(7)?),
+                model: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(messages)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatsPage {
+    pub chats: Vec<Chat>,
+    /// Total chats matching the filter, for the scrollbar.
+    pub total: usize,
+}
+
+/// Paginated chat list, most recently updated first. `filter` narrows
+/// to titles containing it (case-insensitive); empty matches all.
+#[tauri::command]
+pub fn get_chats_page(
+    db: State<Db>,
+    offset: usize,
+    limit: usize,
+    filter: String,
+) -> AppResult<ChatsPage> {
+    let pattern = format!("%{}%", filter);
+    let conn = db.conn();
+    let total = conn.query_row(
+        "SELECT COUNT(*) FROM chats WHERE deleted_at IS NULL AND title LIKE ?1",
+        params![pattern],
+        |row| row.get::<_, i64>(0),
+    )? as usize;
+    let mut stmt = conn.prepare(
+        "SELECT id, title, model, created_at, updated_at FROM chats
+         WHERE deleted_at IS NULL AND title LIKE ?1
+         ORDER BY updated_at DESC LIMIT ?2 OFFSET ?3",
+    )?;
+    let chats = stmt
+        .query_map(params![pattern, limit as i64, offset as i64], |row| {
+            Ok(Chat {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                model: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ChatsPage { chats, total })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessagesPage {
+    /// The page in conversation order (oldest first).
+    pub messages: Vec<Message>,
+    /// Whether older messages exist before this page.
+    pub has_more: bool,
+}
+
+/// Lazy-load a chat backwards: the newest `limit` messages, or with
+/// `before_id` the `limit` messages preceding that one. Keyset
+/// pagination on `(created_at, id)`, so inserts never shift pages.
+#[tauri::command]
+pub fn get_chat_messages_page(
+    db: State<Db>,
+    chat_id: String,
+    before_id: Option<String>,
+    limit: usize,
+) -> AppResult<MessagesPage> {
+    let conn = db.conn();
+    let anchor: Option<String> = match &before_id {
+        Some(id) => Some(conn.query_row(
+            "SELECT created_at FROM messages WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?),
+        None => None,
+    };
+    // Fetch one extra row to learn whether an older page exists.
+    let mut stmt = conn.prepare(
+        "SELECT id, chat_id, role, content, model, created_at, compressed, content_zstd
+         FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL
+           AND (?2 IS NULL OR created_at < ?2 OR (created_at = ?2 AND id < ?3))
+         ORDER BY created_at DESC, id DESC LIMIT ?4",
+    )?;
+    let mut messages: Vec<Message> = stmt
+        .query_map(
+            params![chat_id, anchor, before_id, (limit + 1) as i64],
+            |row| {
+                Ok(Message {
+                    id: row.get(0)?,
+                    chat_id: row.get(1)?,
+                    role: row.get(2)?,
+                    content: compression::stored(
+                        row.get(3)?,
+                        row.get::<_, i64>(6)? != 0,
+                        row.get(7)?,
+                    ),
+                    model: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    let has_more = messages.len() > limit;
+    messages.truncate(limit);
+    messages.reverse();
+    Ok(MessagesPage { messages, has_more })
+}
+
+#[tauri::command]
+pub fn rename_chat(db: State<Db>, chat_id: String, title: String) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET title = ?1, updated_at = ?2 WHERE id = ?3",
+        params![title, db::now(), chat_id],
+    )?;
+    journal::record(
+        &conn,
+        "chat",
+        &chat_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "title": title }).to_string()),
+    );
+    Ok(())
+}
+
+/// Switch the chat's model mid-conversation. Subsequent generations use
+/// the new model; each assistant message records which model wrote it.
+#[tauri::command]
+pub fn update_chat_model(db: State<Db>, chat_id: String, model: String) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET model = ?1, updated_at = ?2 WHERE id = ?3",
+        params![model, db::now(), chat_id],
+    )?;
+    journal::record(
+        &conn,
+        "chat",
+        &chat_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "model": model }).to_string()),
+    );
+    Ok(())
+}
+
+/// Move a chat to the trash. Nothing is removed from disk until
+/// `purge_trash` (or the daily purge job) runs.
+#[tauri::command]
+pub fn delete_chat(db: State<Db>, chat_id: String) -> AppResult<()> {
+    let deleted_at = db::now();
+    let conn = db.conn();
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE chats SET deleted_at = ?1 WHERE id = ?2",
+        params![deleted_at, chat_id],
+    )?;
+    journal::record(
+        &tx,
+        "chat",
+        &chat_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "deleted_at": deleted_at }).to_string()),
+    );
+    tx.commit()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_trashed_chats(db: State<Db>) -> AppResult<Vec<Chat>> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, model, created_at, updated_at FROM chats
+             WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        )?;
+    let chats = stmt
+        .query_map([], |row| {
+            Ok(Chat {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                model: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(chats)
+}
+
+#[tauri::command]
+pub fn restore_chat(db: State<Db>, chat_id: String) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET deleted_at = NULL WHERE id = ?1",
+        params![chat_id],
+    )?;
+    journal::record(
+        &conn,
+        "chat",
+        &chat_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "deleted_at": Value::Null }).to_string()),
+    );
+    Ok(())
+}
+
+/// Copy a chat and its messages into a new chat. When `up_to_message_id`
+/// is set, only messages up to and including that one come along.
+fn copy_chat(db: &Db, chat_id: &str, up_to_message_id: Option<&str>) -> AppResult<Chat> {
+    let conn = db.conn();
+    let source = conn
+        .query_row(
+            "SELECT title, model FROM chats WHERE id = ?1 AND deleted_at IS NULL",
+            params![chat_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )?;
+    let cutoff: Option<String> = match up_to_message_id {
+        Some(message_id) => Some(
+            conn.query_row(
+                "SELECT created_at FROM messages WHERE id = ?1 AND chat_id = ?2",
+                params![message_id, chat_id],
+                |row| row.get(0),
+            )?,
+        ),
+        None => None,
+    };
+    let chat = Chat {
+        id: Uuid::new_v4().to_string(),
+        title: format!("{} (copy)", source.0),
+        model: source.1,
+        created_at: db::now(),
+        updated_at: db::now(),
+    };
+    conn.execute(
+        "INSERT INTO chats (id, title, model, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![chat.id, chat.title, chat.model, chat.created_at, chat.updated_at],
+    )?;
+    journal::record(
+        &conn,
+        "chat",
+        &chat.id,
+        journal::Op::Create,
+        serde_json::to_string(&chat).ok(),
+    );
+    let mut stmt = conn
+        .prepare(
+            "SELECT role, content, model, pinned, created_at, compressed, content_zstd
+             FROM messages
+             WHERE chat_id = ?1 AND deleted_at IS NULL
+               AND (?2 IS NULL OR created_at <= ?2)
+             ORDER BY created_at ASC",
+        )?;
+    let rows = stmt
+        .query_map(params![chat_id, cutoff], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                compression::stored(row.get(1)?, row.get::<_, i64>(5)? != 0, row.get(6)?),
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (role, content, model, pinned, created_at) in rows {
+        let message = Message {
+            id: Uuid::new_v4().to_string(),
+            chat_id: chat.id.clone(),
+            role,
+            content,
+            model,
+            created_at,
+        };
+        conn.execute(
+            "INSERT INTO messages (id, chat_id, role, content, model, pinned, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                message.id,
+                message.chat_id,
+                message.role,
+                message.content,
+                message.model,
+                pinned,
+                message.created_at
+            ],
+        )?;
+        journal::record(
+            &conn,
+            "message",
+            &message.id,
+            journal::Op::Create,
+            serde_json::to_string(&message).ok(),
+        );
+    }
+    Ok(chat)
+}
+
+/// Full copy of a chat, e.g. to try a different direction while keeping
+/// the original intact.
+#[tauri::command]
+pub fn duplicate_chat(db: State<Db>, chat_id: String) -> AppResult<Chat> {
+    copy_chat(&db, &chat_id, None)
+}
+
+/// Branch a chat from a known-good point: copies the conversation up to
+/// and including `message_id` into a new chat.
+#[tauri::command]
+pub fn fork_chat(db: State<Db>, chat_id: String, message_id: String) -> AppResult<Chat> {
+    copy_chat(&db, &chat_id, Some(&message_id))
+}
+
+/// Trash a single message without touching the rest of the chat.
+#[tauri::command]
+pub fn delete_message(db: State<Db>, message_id: String) -> AppResult<()> {
+    let chat_id: String = db.conn().query_row(
+        "SELECT chat_id FROM messages WHERE id = ?1",
+        params![message_id],
+        |row| row.get(0),
+    )?;
+    ensure_unlocked(&db, &chat_id)?;
+    let deleted_at = db::now();
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE messages SET deleted_at = ?1 WHERE id = ?2",
+        params![deleted_at, message_id],
+    )?;
+    journal::record(
+        &conn,
+        "message",
+        &message_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "deleted_at": deleted_at }).to_string()),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn restore_message(db: State<Db>, message_id: String) -> AppResult<()> {
+    let chat_id: String = db.conn().query_row(
+        "SELECT chat_id FROM messages WHERE id = ?1",
+        params![message_id],
+        |row| row.get(0),
+    )?;
+    ensure_unlocked(&db, &chat_id)?;
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE messages SET deleted_at = NULL WHERE id = ?1",
+        params![message_id],
+    )?;
+    journal::record(
+        &conn,
+        "message",
+        &message_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "deleted_at": Value::Null }).to_string()),
+    );
+    Ok(())
+}
+
+/// A finalized chat's lock record: when it was locked, the content hash
+/// taken at that moment, and whether the live messages still match it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatLock {
+    pub chat_id: String,
+    pub locked_at: String,
+    pub lock_hash: String,
+    pub intact: bool,
+}
+
+/// SHA-256 over every live message's role, content and timestamp in
+/// order. Recomputing it later reveals any post-lock edits.
+fn content_hash(db: &Db, chat_id: &str) -> AppResult<String> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT role, content, created_at, compressed, content_zstd FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at",
+    )?;
+    let rows = stmt.query_map(params![chat_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            compression::stored(row.get(1)?, row.get::<_, i64>(3)? != 0, row.get(4)?),
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut hasher = Sha256::new();
+    for row in rows {
+        let (role, content, created_at) = row?;
+        hasher.update(role.as_bytes());
+        hasher.update(b"\x1f");
+        hasher.update(content.as_bytes());
+        hasher.update(b"\x1f");
+        hasher.update(created_at.as_bytes());
+        hasher.update(b"\x1e");
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Reject writes against a finalized chat.
+pub(crate) fn ensure_unlocked(db: &Db, chat_id: &str) -> AppResult<()> {
+    let locked_at: Option<String> = db
+        .conn()
+        .query_row(
+            "SELECT locked_at FROM chats WHERE id = ?1",
+            params![chat_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(None);
+    match locked_at {
+        Some(at) => Err(AppError::InvalidInput(format!(
+            "chat is locked (finalized at {})",
+            at
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Finalize a chat: record a lock timestamp and content hash, after
+/// which message writes against it are rejected and any tampering shows
+/// up as a hash mismatch.
+#[tauri::command]
+pub fn lock_chat(db: State<Db>, chat_id: String) -> AppResult<ChatLock> {
+    ensure_unlocked(&db, &chat_id)?;
+    let locked_at = db::now();
+    let lock_hash = content_hash(&db, &chat_id)?;
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET locked_at = ?1, lock_hash = ?2 WHERE id = ?3",
+        params![locked_at, lock_hash, chat_id],
+    )?;
+    journal::record(
+        &conn,
+        "chat",
+        &chat_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "locked_at": locked_at, "lock_hash": lock_hash }).to_string()),
+    );
+    Ok(ChatLock {
+        chat_id,
+        locked_at,
+        lock_hash,
+        intact: true,
+    })
+}
+
+/// Remove a chat's lock. Deliberate friction: the frontend must pass an
+/// explicit confirmation, since unlocking gives up tamper evidence.
+#[tauri::command]
+pub fn unlock_chat(db: State<Db>, chat_id: String, confirm: bool) -> AppResult<()> {
+    if !confirm {
+        return Err(AppError::InvalidInput(
+            "unlocking a finalized chat requires explicit confirmation".to_string(),
+        ));
+    }
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET locked_at = NULL, lock_hash = NULL WHERE id = ?1",
+        params![chat_id],
+    )?;
+    journal::record(
+        &conn,
+        "chat",
+        &chat_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "locked_at": Value::Null, "lock_hash": Value::Null }).to_string()),
+    );
+    Ok(())
+}
+
+/// The lock record for a chat, with the hash re-verified against the
+/// current messages; `None` when the chat is not locked.
+#[tauri::command]
+pub fn get_chat_lock(db: State<Db>, chat_id: String) -> AppResult<Option<ChatLock>> {
+    chat_lock(&db, &chat_id)
+}
+
+pub(crate) fn chat_lock(db: &Db, chat_id: &str) -> AppResult<Option<ChatLock>> {
+    let lock: Option<(String, String)> = db
+        .conn()
+        .query_row(
+            "SELECT locked_at, lock_hash FROM chats WHERE id = ?1
+             AND locked_at IS NOT NULL",
+            params![chat_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+    let Some((locked_at, lock_hash)) = lock else {
+        return Ok(None);
+    };
+    let intact = content_hash(db, chat_id)? == lock_hash;
+    Ok(Some(ChatLock {
+        chat_id: chat_id.to_string(),
+        locked_at,
+        lock_hash,
+        intact,
+    }))
+}
+
+fn purge_trash_internal(db: &Db, older_than_days: i64) -> AppResult<usize> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(older_than_days)).to_rfc3339();
+    let conn = db.conn();
+    let chats = conn
+        .execute(
+            "DELETE FROM chats WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+            params![cutoff],
+        )?;
+    let messages = conn
+        .execute(
+            "DELETE FROM messages WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+            params![cutoff],
+        )?;
+    Ok(chats + messages)
+}
+
+/// Permanently remove trashed rows older than the given number of days.
+/// Returns how many rows were purged.
+#[tauri::command]
+pub fn purge_trash(db: State<Db>, older_than_days: i64) -> AppResult<usize> {
+    purge_trash_internal(&db, older_than_days.max(0))
+}
+
+/// How long trashed rows survive before the automatic purge removes them.
+const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Purge expired trash once a day in the background.
+pub fn start_trash_purge(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            {
+                let db = app.state::<Db>();
+                let _ = purge_trash_internal(&db, TRASH_RETENTION_DAYS);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(24 * 60 * 60)).await;
+        }
+    });
+}
+
+/// A message matching an in-chat search, with byte offsets of every
+/// occurrence so the frontend can highlight without re-scanning.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    pub message_id: String,
+    pub offsets: Vec<(usize, usize)>,
+}
+
+/// Case-insensitive substring positions of `query` in `content`, as
+/// byte ranges into the original string.
+fn match_offsets(content: &str, query: &str) -> Vec<(usize, usize)> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let haystack = content.to_lowercase();
+    let needle = query.to_lowercase();
+    // Lowercasing can change byte lengths for non-ASCII text; only trust
+    // the offsets when they still line up with the original string.
+    if haystack.len() != content.len() {
+        return content
+            .match_indices(query)
+            .map(|(i, m)| (i, i + m.len()))
+            .collect();
+    }
+    let mut offsets = Vec::new();
+    let mut start = 0;
+    while let Some(pos) = haystack[start..].find(&needle) {
+        let begin = start + pos;
+        offsets.push((begin, begin + needle.len()));
+        start = begin + needle.len();
+    }
+    offsets
+}
+
+/// Search one chat's messages in the backend, for conversations too big
+/// to fully load in the webview.
+#[tauri::command]
+pub fn search_in_chat(db: State<Db>, chat_id: String, query: String) -> AppResult<Vec<SearchMatch>> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare("SELECT id, content, compressed, content_zstd FROM messages WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC")?;
+    let rows = stmt
+        .query_map(params![chat_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                compression::stored(row.get(1)?, row.get::<_, i64>(2)? != 0, row.get(3)?),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, content)| {
+            let offsets = match_offsets(&content, &query);
+            if offsets.is_empty() {
+                None
+            } else {
+                Some(SearchMatch {
+                    message_id: id,
+                    offsets,
+                })
+            }
+        })
+        .collect())
+}
+
+pub(crate) fn insert_message(
+    db: &Db,
+    chat_id: &str,
+    role: &str,
+    content: &str,
+    model: Option<&str>,
+) -> AppResult<Message> {
+    ensure_unlocked(db, chat_id)?;
+    let message = Message {
+        id: Uuid::new_v4().to_string(),
+        chat_id: chat_id.to_string(),
+        role: role.to_string(),
+        content: content.to_string(),
+        model: model.map(str::to_string),
+        created_at: db::now(),
+    };
+    let conn = db.conn();
+    // Large content (big code blocks) is stored zstd-compressed; every
+    // reader resolves it back through `compression::stored`.
+    if message.content.len() >= compression::THRESHOLD_BYTES {
+        conn.execute(
+            "INSERT INTO messages (id, chat_id, role, content, model, created_at, content_zstd, compressed)
+             VALUES (?1, ?2, ?3, '', ?4, ?5, ?6, 1)",
+            params![
+                message.id,
+                message.chat_id,
+                message.role,
+                message.model,
+                message.created_at,
+                compression::compress(&message.content)
+            ],
+        )?;
+    } else {
+        conn.execute(
+            "INSERT INTO messages (id, chat_id, role, content, model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                message.id,
+                message.chat_id,
+                message.role,
+                message.content,
+                message.model,
+                message.created_at
+            ],
+        )?;
+    }
+    conn.execute(
+        "UPDATE chats SET updated_at = ?1 WHERE id = ?2",
+        params![message.created_at, chat_id],
+    )?;
+    journal::record(
+        &conn,
+        "message",
+        &message.id,
+        journal::Op::Create,
+        serde_json::to_string(&message).ok(),
+    );
+    Ok(message)
+}
+
+/// Build the request context for a chat: persisted history (with pinned
+/// flags) plus any pending attachment text folded into the new user
+/// message, pruned under the chat's configured policy.
+pub(crate) async fn build_context(
+    db: &Db,
+    chat_id: &str,
+    model: &str,
+    user_content: &str,
+    consume_attachments: bool,
+) -> AppResult<ChatContext> {
+    let (policy, history) = {
+        let conn = db.conn();
+        let (policy, cleared_at): (String, Option<String>) = conn
+            .query_row(
+                "SELECT pruning_policy, context_cleared_at FROM chats WHERE id = ?1",
+                params![chat_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+        // `/clear-context` and `set_context_boundary` stamp
+        // context_cleared_at: messages at or before the boundary stay
+        // in the transcript but drop out of the model's context,
+        // except pinned ones, which survive a clear like they survive
+        // pruning.
+        let mut stmt = conn
+            .prepare(
+                "SELECT role, content, pinned, compressed, content_zstd FROM messages
+                 WHERE chat_id = ?1 AND deleted_at IS NULL
+                   AND (pinned = 1 OR created_at > COALESCE(?2, ''))
+                 ORDER BY created_at ASC",
+            )?;
+        let rows = stmt
+            .query_map(params![chat_id, cleared_at], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    compression::stored(row.get(1)?, row.get::<_, i64>(3)? != 0, row.get(4)?),
+                    row.get::<_, i64>(2)? != 0,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        (PruningPolicy::parse(&policy), rows)
+    };
+
+    let mut context = ChatContext::with_policy(model, policy);
+    context.set_window(ollama::detect_context_window(db, model).await);
+    for (role, content, pinned) in history {
+        context.add_message(&role, &content, pinned)?;
+    }
+
+    let mut content = String::new();
+    if consume_attachments {
+        for att in attachments::take_pending(db, chat_id)? {
+            if let Some(text) = att.extracted_text {
+                let block = context.format_attachment(&att.file_name, &text);
+                context.sources.push(sources::RetrievedSource {
+                    kind: "attachment".to_string(),
+                    origin: att.file_name.clone(),
+                    chunk: block.clone(),
+                    score: None,
+                });
+                content.push_str(&block);
+                content.push_str("\n\n");
+            }
+        }
+    }
+    content.push_str(user_content);
+    context.add_message("user", &content, false)?;
+
+    if policy == PruningPolicy::Relevance {
+        for message in &mut context.messages {
+            message.embedding = Some(knowledge::embed(&message.content).await?);
+        }
+        // Re-run pruning now that similarity scores are available.
+        context.prune()?;
+    }
+    Ok(context)
+}
+
+#[tauri::command]
+pub fn set_pruning_policy(db: State<Db>, chat_id: String, policy: String) -> AppResult<()> {
+    let policy = PruningPolicy::parse(&policy);
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET pruning_policy = ?1 WHERE id = ?2",
+        params![policy.as_str(), chat_id],
+    )?;
+    Ok(())
+}
+
+/// Mark a manual context cut point at `message_id`: `build_context`
+/// only loads messages after the boundary (plus pinned ones before it),
+/// so a long chat can be "reset" in place without starting a new one.
+/// `None` removes the boundary and restores the full history. The
+/// boundary shares storage with `/clear-context`, which is the same cut
+/// placed at the current moment.
+#[tauri::command]
+pub fn set_context_boundary(
+    db: State<Db>,
+    chat_id: String,
+    message_id: Option<String>,
+) -> AppResult<()> {
+    let conn = db.conn();
+    let boundary = match &message_id {
+        Some(id) => Some(conn.query_row(
+            "SELECT created_at FROM messages WHERE id = ?1 AND chat_id = ?2",
+            params![id, chat_id],
+            |row| row.get::<_, String>(0),
+        )?),
+        None => None,
+    };
+    conn.execute(
+        "UPDATE chats SET context_cleared_at = ?1 WHERE id = ?2",
+        params![boundary, chat_id],
+    )?;
+    Ok(())
+}
+
+/// Set or clear a chat's keep-alive policy: an Ollama duration like
+/// "10m", "0" to unload right after each response, or `None` for the
+/// server default. Applied to every generation in the chat and when the
+/// chat is opened (see `ollama::warm_chat_model`).
+#[tauri::command]
+pub fn set_chat_keep_alive(
+    db: State<Db>,
+    chat_id: String,
+    keep_alive: Option<String>,
+) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET keep_alive = ?1 WHERE id = ?2",
+        params![keep_alive, chat_id],
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn pin_message(db: State<Db>, message_id: String, pinned: bool) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE messages SET pinned = ?1 WHERE id = ?2",
+        params![pinned as i64, message_id],
+    )?;
+    journal::record(
+        &conn,
+        "message",
+        &message_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "pinned": pinned }).to_string()),
+    );
+    Ok(())
+}
+
+/// Record thumbs up/down feedback on a message, or clear it with `None`.
+/// Feedback is kept alongside the message so fine-tuning exports can
+/// filter on it.
+#[tauri::command]
+pub fn set_message_feedback(
+    db: State<Db>,
+    message_id: String,
+    feedback: Option<String>,
+) -> AppResult<()> {
+    if let Some(value) = feedback.as_deref() {
+        if value != "up" && value != "down" {
+            return Err(AppError::InvalidInput(format!("unknown feedback value: {}", value)));
+        }
+    }
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE messages SET feedback = ?1 WHERE id = ?2",
+        params![feedback, message_id],
+    )?;
+    journal::record(
+        &conn,
+        "message",
+        &message_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "feedback": feedback }).to_string()),
+    );
+    Ok(())
+}
+
+/// Attach a free-text note to a message; an empty note clears it.
+#[tauri::command]
+pub fn add_message_note(db: State<Db>, message_id: String, note: String) -> AppResult<()> {
+    let note = if note.trim().is_empty() { None } else { Some(note) };
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE messages SET note = ?1 WHERE id = ?2",
+        params![note, message_id],
+    )?;
+    journal::record(
+        &conn,
+        "message",
+        &message_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "note": note }).to_string()),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_message_bookmarked(
+    db: State<Db>,
+    message_id: String,
+    bookmarked: bool,
+) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE messages SET bookmarked = ?1 WHERE id = ?2",
+        params![bookmarked as i64, message_id],
+    )?;
+    journal::record(
+        &conn,
+        "message",
+        &message_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "bookmarked": bookmarked }).to_string()),
+    );
+    Ok(())
+}
+
+/// All bookmarked messages across chats, newest first.
+#[tauri::command]
+pub fn list_bookmarked_messages(db: State<Db>) -> AppResult<Vec<Message>> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, chat_id, role, content, model, created_at, compressed, content_zstd
+             FROM messages
+             WHERE bookmarked = 1 AND deleted_at IS NULL ORDER BY created_at DESC",
+        )?;
+    let messages = stmt
+        .query_map([], |row| {
+            Ok(Message {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                role: row.get(2)?,
+                content: compression::stored(row.get(3)?, row.get::<_, i64>(6)? != 0, row.get(7)?),
+                model: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(messages)
+}
+
+/// Stop flags for generations currently streaming, keyed by chat id.
+#[derive(Default)]
+pub struct ActiveGenerations(pub Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+impl ActiveGenerations {
+    fn register(&self, chat_id: &str) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        self.0
+            .lock()
+            .unwrap()
+            .insert(chat_id.to_string(), flag.clone());
+        flag
+    }
+
+    fn unregister(&self, chat_id: &str) {
+        self.0.lock().unwrap().remove(chat_id);
+    }
+}
+
+/// Two-lane generation queue: interactive requests always start
+/// immediately, while speculative background work runs only when the
+/// app is idle and is cancelled the moment interactive work begins.
+#[derive(Default)]
+pub struct GenerationQueue {
+    interactive: AtomicUsize,
+    background: Mutex<Vec<Arc<AtomicBool>>>,
+}
+
+/// Marks an interactive generation for as long as it lives.
+pub(crate) struct InteractiveGuard<'a>(&'a GenerationQueue);
+
+impl Drop for InteractiveGuard<'_> {
+    fn drop(&mut self) {
+        self.0.interactive.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl GenerationQueue {
+    fn begin_interactive(&self) -> InteractiveGuard<'_> {
+        self.interactive.fetch_add(1, Ordering::SeqCst);
+        for flag in self.background.lock().unwrap().drain(..) {
+            flag.store(true, Ordering::SeqCst);
+        }
+        InteractiveGuard(self)
+    }
+
+    fn idle(&self) -> bool {
+        self.interactive.load(Ordering::SeqCst) == 0
+    }
+
+    fn register_background(&self) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        self.background.lock().unwrap().push(flag.clone());
+        flag
+    }
+}
+
+/// Settings key for the generation concurrency cap.
+pub(crate) const MAX_CONCURRENT_KEY: &str = "max_concurrent_generations";
+/// Default cap: a second stream hides tool and IO stalls without making
+/// a single GPU thrash between more models than it can hold.
+const DEFAULT_MAX_CONCURRENT: usize = 2;
+
+fn max_concurrent(db: &Db) -> usize {
+    settings::get(db, MAX_CONCURRENT_KEY)
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n >= 1)
+        .unwrap_or(DEFAULT_MAX_CONCURRENT)
+}
+
+struct QueuedGeneration {
+    chat_id: String,
+    notify: Arc<tokio::sync::Notify>,
+    granted: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    running: usize,
+    waiting: Vec<QueuedGeneration>,
+}
+
+/// Caps how many generations stream at once, so several busy chats
+/// cannot make a single GPU crawl. Excess `chat` calls wait in FIFO
+/// order, each receiving `queue-position-{chat_id}` events as the line
+/// moves; waiters can be promoted to the front or cancelled.
+#[derive(Default)]
+pub struct GenerationScheduler {
+    state: Mutex<SchedulerState>,
+}
+
+/// Holds one concurrency slot; dropping it admits the next waiter.
+pub(crate) struct GenerationSlot<'a> {
+    scheduler: &'a GenerationScheduler,
+    app: AppHandle,
+    limit: usize,
+}
+
+impl Drop for GenerationSlot<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release(&self.app, self.limit);
+    }
+}
+
+impl GenerationScheduler {
+    async fn acquire<'a>(
+        &'a self,
+        app: &AppHandle,
+        db: &Db,
+        chat_id: &str,
+    ) -> AppResult<GenerationSlot<'a>> {
+        // The cap is read per call, so a settings change applies to the
+        // next generation without a restart.
+        let limit = max_concurrent(db);
+        let (notify, granted, cancelled) = {
+            let mut state = self.state.lock().unwrap();
+            if state.running < limit && state.waiting.is_empty() {
+                state.running += 1;
+                return Ok(GenerationSlot {
+                    scheduler: self,
+                    app: app.clone(),
+                    limit,
+                });
+            }
+            let notify = Arc::new(tokio::sync::Notify::new());
+            let granted = Arc::new(AtomicBool::new(false));
+            let cancelled = Arc::new(AtomicBool::new(false));
+            state.waiting.push(QueuedGeneration {
+                chat_id: chat_id.to_string(),
+                notify: notify.clone(),
+                granted: granted.clone(),
+                cancelled: cancelled.clone(),
+            });
+            (notify, granted, cancelled)
+        };
+        self.emit_positions(app);
+        loop {
+            notify.notified().await;
+            if cancelled.load(Ordering::SeqCst) {
+                return Err(AppError::Cancelled("dequeued before starting".to_string()));
+            }
+            if granted.load(Ordering::SeqCst) {
+                return Ok(GenerationSlot {
+                    scheduler: self,
+                    app: app.clone(),
+                    limit,
+                });
+            }
+        }
+    }
+
+    fn release(&self, app: &AppHandle, limit: usize) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.running -= 1;
+            while state.running < limit && !state.waiting.is_empty() {
+                let next = state.waiting.remove(0);
+                if next.cancelled.load(Ordering::SeqCst) {
+                    continue;
+                }
+                state.running += 1;
+                next.granted.store(true, Ordering::SeqCst);
+                next.notify.notify_one();
+            }
+        }
+        self.emit_positions(app);
+    }
+
+    /// Move a waiting chat to the front of the queue.
+    pub(crate) fn promote(&self, app: &AppHandle, chat_id: &str) -> bool {
+        let found = {
+            let mut state = self.state.lock().unwrap();
+            match state.waiting.iter().position(|w| w.chat_id == chat_id) {
+                Some(idx) => {
+                    let waiter = state.waiting.remove(idx);
+                    state.waiting.insert(0, waiter);
+                    true
+                }
+                None => false,
+            }
+        };
+        if found {
+            self.emit_positions(app);
+        }
+        found
+    }
+
+    /// Drop a waiting chat from the queue; its `chat` call returns
+    /// `Cancelled` without ever generating.
+    pub(crate) fn cancel(&self, app: &AppHandle, chat_id: &str) -> bool {
+        let found = {
+            let mut state = self.state.lock().unwrap();
+            match state.waiting.iter().position(|w| w.chat_id == chat_id) {
+                Some(idx) => {
+                    let waiter = state.waiting.remove(idx);
+                    waiter.cancelled.store(true, Ordering::SeqCst);
+                    waiter.notify.notify_one();
+                    true
+                }
+                None => false,
+            }
+        };
+        if found {
+            self.emit_positions(app);
+        }
+        found
+    }
+
+    /// One `queue-position-{chat_id}` event per waiter, 1-based.
+    fn emit_positions(&self, app: &AppHandle) {
+        let positions: Vec<(String, usize)> = {
+            let state = self.state.lock().unwrap();
+            state
+                .waiting
+                .iter()
+                .enumerate()
+                .map(|(i, w)| (w.chat_id.clone(), i + 1))
+                .collect()
+        };
+        for (chat_id, position) in positions {
+            let _ = app.emit(&format!("queue-position-{}", chat_id), position);
+        }
+    }
+}
+
+/// Jump a queued generation to the front of the waiting line.
+#[tauri::command]
+pub fn promote_queued_generation(app: AppHandle, chat_id: String) -> AppResult<()> {
+    if app.state::<GenerationScheduler>().promote(&app, &chat_id) {
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!(
+            "chat {} is not waiting in the queue",
+            chat_id
+        )))
+    }
+}
+
+/// Cancel a generation that is still waiting for a concurrency slot.
+#[tauri::command]
+pub fn cancel_queued_generation(app: AppHandle, chat_id: String) -> AppResult<()> {
+    if app.state::<GenerationScheduler>().cancel(&app, &chat_id) {
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!(
+            "chat {} is not waiting in the queue",
+            chat_id
+        )))
+    }
+}
+
+/// Pre-generate the answer to a likely next question and store it in the
+/// prompt cache under the same key an interactive run would compute, so
+/// sending that question is a cache hit. Nothing streams and no messages
+/// persist; pending attachments stay queued for the real request. The
+/// job yields immediately if interactive work is running and aborts
+/// mid-stream when some starts.
+pub(crate) async fn prefetch_answer(
+    app: &AppHandle,
+    db: &Db,
+    chat_id: &str,
+    model: &str,
+    question: &str,
+) -> AppResult<()> {
+    let queue = app.state::<GenerationQueue>();
+    if !queue.idle() || cache::is_bypassed(db, chat_id) {
+        return Ok(());
+    }
+    // Tool-enabled requests never cache, so there is nothing to prefetch.
+    if !mcp::ollama_tool_specs(app).await.is_empty()
+        || !plugins::ollama_tool_specs(app).is_empty()
+    {
+        return Ok(());
+    }
+    let cancel = queue.register_background();
+    let context = build_context(db, chat_id, model, question, false).await?;
+    let mut payload = chat_payload(&context, model, &None);
+    privacy::apply_outbound_filter(app, db, chat_id, &mut payload)?;
+    let key = cache::cache_key(model, &payload["messages"], &None);
+    if cache::lookup(db, &key).is_some() {
+        return Ok(());
+    }
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{}/api/chat", OLLAMA_BASE_URL))
+        .json(&payload)
+        .send()
+        .await?;
+    let mut answer = String::new();
+    let mut stream = resp.bytes_stream();
+    let mut decoder = NdjsonDecoder::new();
+    while let Some(chunk) = stream.next().await {
+        if cancel.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        for value in decoder.push(&chunk?) {
+            if let Some(token) = value.pointer("/message/content").and_then(Value::as_str) {
+                answer.push_str(token);
+            }
+        }
+    }
+    if let Some(value) = decoder.finish() {
+        if let Some(token) = value.pointer("/message/content").and_then(Value::as_str) {
+            answer.push_str(token);
+        }
+    }
+    if !cancel.load(Ordering::SeqCst) && !answer.is_empty() {
+        cache::store(db, &key, model, &answer);
+    }
+    Ok(())
+}
+
+/// How long tokens are batched before one `chat-token` event is emitted.
+/// One event per Ollama chunk floods the IPC bridge on fast hardware;
+/// ~25 events a second is indistinguishable to a reader.
+const COALESCE_FRAME: Duration = Duration::from_millis(40);
+
+/// How often the partial assistant text is checkpointed to the database
+/// while streaming (see `stream_response`).
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Default per-chunk inactivity timeout for a streaming generation,
+/// overridable via the `stream_timeout_secs` setting. A stall warning
+/// goes out at the halfway mark.
+const DEFAULT_STALL_TIMEOUT_SECS: u64 = 120;
+
+fn stall_timeout(db: &Db) -> Duration {
+    let seconds = settings::get(db, "stream_timeout_secs")
+        .and_then(|v| v.parse().ok())
+        .filter(|&s| s > 0)
+        .unwrap_or(DEFAULT_STALL_TIMEOUT_SECS);
+    Duration::from_secs(seconds)
+}
+
+/// Batches streamed tokens into frame-sized `chat-token` events. A zero
+/// frame (low-latency mode) flushes every token immediately.
+struct TokenCoalescer {
+    buffer: String,
+    last_flush: Instant,
+    frame: Duration,
+}
+
+impl TokenCoalescer {
+    fn new(frame: Duration) -> Self {
+        TokenCoalescer {
+            buffer: String::new(),
+            last_flush: Instant::now(),
+            frame,
+        }
+    }
+
+    /// Add a token; returns the batch to emit now, if any. The final
+    /// (`done`) token always flushes whatever is buffered so nothing is
+    /// left behind.
+    fn push(&mut self, token: &str, done: bool) -> Option<String> {
+        self.buffer.push_str(token);
+        if done || (!self.buffer.is_empty() && self.last_flush.elapsed() >= self.frame) {
+            self.last_flush = Instant::now();
+            Some(std::mem::take(&mut self.buffer))
+        } else {
+            None
+        }
+    }
+}
+
+/// What one streamed /api/chat round produced: the text so far plus any
+/// tool calls the model requested (tool-capable models emit them on the
+/// final message instead of content).
+pub(crate) struct StreamOutcome {
+    pub text: String,
+    pub tool_calls: Vec<Value>,
+    /// The stop flag was raised mid-stream; `text` is a partial answer.
+    pub cancelled: bool,
+}
+
+/// Stream one /api/chat request, emitting `chat-token` (and, in
+/// structured mode, `structured-partial`) events under `message_id`.
+/// Returns the generated text and any requested tool calls; stops early
+/// when the chat's stop flag is raised, returning what arrived so far.
+async fn stream_response(
+    app: &AppHandle,
+    payload: &Value,
+    chat_id: &str,
+    message_id: &str,
+    structured_mode: bool,
+    initial: &str,
+    low_latency: bool,
+) -> AppResult<StreamOutcome> {
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{}/api/chat", OLLAMA_BASE_URL))
+        .json(payload)
+        .send()
+        .await?;
+
+    let stop_flag = app.state::<ActiveGenerations>().register(chat_id);
+    let mut full_response = initial.to_string();
+    let mut tool_calls: Vec<Value> = Vec::new();
+    let mut cancelled = false;
+    // Periodically checkpoint the partial text into the messages table
+    // (marked interrupted) so a crash or cancel mid-stream loses at most
+    // a couple of seconds of output; the finalize path replaces the
+    // checkpoint row with the real message.
+    let model = payload
+        .get("model")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let checkpoint = |text: &str| {
+        let db = app.state::<Db>();
+        let conn = db.conn();
+        let _ = conn.execute(
+            "INSERT INTO messages (id, chat_id, role, content, model, created_at, interrupted)
+             VALUES (?1, ?2, 'assistant', ?3, ?4, ?5, 1)
+             ON CONFLICT(id) DO UPDATE SET content = excluded.content",
+            params![message_id, chat_id, text, model, db::now()],
+        );
+    };
+    let mut last_checkpoint = Instant::now();
+    let mut last_partial: Option<Value> = None;
+    let mut stream = resp.bytes_stream();
+    let mut decoder = NdjsonDecoder::new();
+    let mut coalescer = TokenCoalescer::new(if low_latency {
+        Duration::ZERO
+    } else {
+        COALESCE_FRAME
+    });
+    let handle_value = |value: Value,
+                        full_response: &mut String,
+                        tool_calls: &mut Vec<Value>,
+                        last_partial: &mut Option<Value>,
+                        coalescer: &mut TokenCoalescer|
+     -> AppResult<()> {
+        let token = value
+            .pointer("/message/content")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let done = value.get("done").and_then(Value::as_bool).unwrap_or(false);
+        full_response.push_str(&token);
+        if let Some(calls) = value.pointer("/message/tool_calls").and_then(Value::as_array) {
+            tool_calls.extend(calls.iter().cloned());
+        }
+        if let Some(batch) = coalescer.push(&token, done) {
+            app.emit(
+                "chat-token",
+                &ChatToken {
+                    chat_id: chat_id.to_string(),
+                    message_id: message_id.to_string(),
+                    token: batch,
+                    done,
+                },
+            )?;
+        }
+        if structured_mode {
+            if let Some(partial) = structured::parse_partial(full_response) {
+                // Only emit when the repaired object actually advanced.
+                if last_partial.as_ref() != Some(&partial) {
+                    app.emit(
+                        "structured-partial",
+                        &StructuredPartial {
+                            chat_id: chat_id.to_string(),
+                            message_id: message_id.to_string(),
+                            value: partial.clone(),
+                            done,
+                        },
+                    )?;
+                    *last_partial = Some(partial);
+                }
+            }
+        }
+        Ok(())
+    };
+    let timeout = stall_timeout(&app.state::<Db>());
+    let result: AppResult<()> = async {
+        loop {
+            // A model OOM or driver hang leaves the connection open but
+            // silent; bound every chunk wait. Half the timeout gone
+            // without bytes raises a stall warning, the full timeout
+            // aborts with the partial output checkpointed.
+            let next = match tokio::time::timeout(timeout / 2, stream.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    let _ = app.emit(
+                        &format!("chat-stalled-{}", chat_id),
+                        serde_json::json!({
+                            "message_id": message_id,
+                            "silent_for_secs": timeout.as_secs() / 2,
+                            "timeout_secs": timeout.as_secs(),
+                        }),
+                    );
+                    match tokio::time::timeout(timeout - timeout / 2, stream.next()).await {
+                        Ok(next) => next,
+                        Err(_) => {
+                            checkpoint(&full_response);
+                            return Err(AppError::Timeout {
+                                seconds: timeout.as_secs(),
+                                message: "Ollama stopped sending data mid-stream; partial \
+                                          output was kept"
+                                    .to_string(),
+                            });
+                        }
+                    }
+                }
+            };
+            let Some(chunk) = next else {
+                break;
+            };
+            if stop_flag.load(Ordering::Relaxed) {
+                cancelled = true;
+                checkpoint(&full_response);
+                return Ok(());
+            }
+            let chunk = chunk?;
+            for value in decoder.push(&chunk) {
+                handle_value(
+                    value,
+                    &mut full_response,
+                    &mut tool_calls,
+                    &mut last_partial,
+                    &mut coalescer,
+                )?;
+            }
+            if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                checkpoint(&full_response);
+                last_checkpoint = Instant::now();
+            }
+        }
+        if let Some(value) = decoder.finish() {
+            handle_value(
+                value,
+                &mut full_response,
+                &mut tool_calls,
+                &mut last_partial,
+                &mut coalescer,
+            )?;
+        }
+        Ok(())
+    }
+    .await;
+    app.state::<ActiveGenerations>().unregister(chat_id);
+    result?;
+    Ok(StreamOutcome {
+        text: full_response,
+        tool_calls,
+        cancelled,
+    })
+}
+
+pub(crate) fn chat_payload(context: &ChatContext, model: &str, format: &Option<Value>) -> Value {
+    let mut payload = serde_json::json!({
+        "model": model,
+        "messages": context
+            .messages
+            .iter()
+            .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
+            .collect::<Vec<_>>(),
+        "stream": true,
+    });
+    if let Some(format) = format {
+        payload["format"] = format.clone();
+    }
+    payload
+}
+
+/// Send a user message and stream the assistant response, emitting
+/// `chat-token` events as tokens arrive. Both messages are persisted.
+/// This is the shared generation path used by the `chat` command and by
+/// the workflow pipeline.
+pub async fn run_generation(
+    app: &AppHandle,
+    db: &Db,
+    chat_id: &str,
+    model: &str,
+    content: &str,
+    format: Option<Value>,
+) -> AppResult<Message> {
+    run_generation_with(app, db, chat_id, model, content, format, None, false).await
+}
+
+/// `run_generation` with all knobs exposed: an output `constraint`
+/// (validated before the message is accepted, with bounded corrective
+/// retries) and `low_latency`, which emits one event per Ollama chunk
+/// instead of batching per frame.
+pub async fn run_generation_with(
+    app: &AppHandle,
+    db: &Db,
+    chat_id: &str,
+    model: &str,
+    content: &str,
+    format: Option<Value>,
+    constraint: Option<Constraint>,
+    low_latency: bool,
+) -> AppResult<Message> {
+    let scheduler = app.state::<GenerationScheduler>();
+    let _slot = scheduler.acquire(app, db, chat_id).await?;
+    let queue = app.state::<GenerationQueue>();
+    let _interactive = queue.begin_interactive();
+    // `@chat:` references resolve to injected context spans. They ride
+    // the outgoing message only; the stored user message stays verbatim
+    // and each span is recorded against it for exports.
+    let references = chatrefs::resolve_all(db, content, model).await?;
+    let model_content = if references.is_empty() {
+        content.to_string()
+    } else {
+        let spans: Vec<&str> = references.iter().map(|(_, _, span)| span.as_str()).collect();
+        format!("{}\n\n{}", spans.join("\n\n"), content)
+    };
+    let mut context = build_context(db, chat_id, model, &model_content, true).await?;
+    for (referenced, _, span) in &references {
+        context.sources.push(sources::RetrievedSource {
+            kind: "chat".to_string(),
+            origin: referenced.clone(),
+            chunk: span.clone(),
+            score: None,
+        });
+    }
+    let user_message = insert_message(db, chat_id, "user", content, None)?;
+    context::record_history(db, chat_id, &user_message.id, &context.stats());
+    for (referenced, strategy, span) in &references {
+        chatrefs::record(db, chat_id, &user_message.id, referenced, strategy, span);
+    }
+    knowledge::embed_message_background(app, &user_message);
+    {
+        // Keep the chat's model column in step with what is actually
+        // being used, so the frontend and DB cannot drift apart.
+        let conn = db.conn();
+        conn.execute(
+            "UPDATE chats SET model = ?1 WHERE id = ?2 AND model != ?1",
+            params![model, chat_id],
+        )?;
+    }
+
+    // A constraint with a native Ollama equivalent (JSON) rides the
+    // `format` field; everything else is instruction plus validation.
+    let format = constraint.as_ref().and_then(Constraint::format).or(format);
+    let mut payload = chat_payload(&context, model, &format);
+    if let Some(keep_alive) = ollama::chat_keep_alive(db, chat_id) {
+        payload["keep_alive"] = Value::String(keep_alive);
+    }
+    if let Some(constraint) = &constraint {
+        let messages = payload["messages"]
+            .as_array_mut()
+            .ok_or_else(|| AppError::Internal("malformed chat payload".to_string()))?;
+        messages.insert(
+            0,
+            serde_json::json!({ "role": "system", "content": constraint.instruction() }),
+        );
+    }
+    privacy::apply_outbound_filter(app, db, chat_id, &mut payload)?;
+    let mut tool_specs = mcp::ollama_tool_specs(app).await;
+    tool_specs.extend(plugins::ollama_tool_specs(app));
+    if !tool_specs.is_empty() {
+        payload["tools"] = Value::Array(tool_specs);
+    }
+    let message_id = Uuid::new_v4().to_string();
+
+    // Answer identical requests from the prompt cache. Tool-enabled
+    // requests never cache: tool results vary between runs.
+    let cache_key = (payload.get("tools").is_none() && !cache::is_bypassed(db, chat_id))
+        .then(|| cache::cache_key(model, &payload["messages"], &format));
+    if let Some(key) = &cache_key {
+        if let Some(cached) = cache::lookup(db, key) {
+            app.emit(
+                "chat-token",
+                &ChatToken {
+                    chat_id: chat_id.to_string(),
+                    message_id: message_id.clone(),
+                    token: cached.clone(),
+                    done: true,
+                },
+            )?;
+            let message = insert_message(db, chat_id, "assistant", &cached, Some(model))?;
+            sources::record_all(db, chat_id, &message.id, &context.sources);
+            knowledge::embed_message_background(app, &message);
+            tray::emit_or_notify(app, "generation-finished", &message);
+            return Ok(message);
+        }
+    }
+
+    const MAX_TOOL_ROUNDS: usize = 5;
+    const MAX_CONSTRAINT_RETRIES: usize = 2;
+    let mut full_response = String::new();
+    let mut cancelled = false;
+    'generation: for attempt in 0..=MAX_CONSTRAINT_RETRIES {
+        // Tool-calling loop: when the model requests MCP tools, run them,
+        // append the results as `tool` messages and go another round. All
+        // rounds stream into the same message bubble. Bounded so a model
+        // that keeps calling tools cannot spin forever.
+        for _ in 0..=MAX_TOOL_ROUNDS {
+            let outcome = stream_response(
+                app,
+                &payload,
+                chat_id,
+                &message_id,
+                format.is_some(),
+                &full_response,
+                low_latency,
+            )
+            .await?;
+            full_response = outcome.text;
+            if outcome.cancelled {
+                // A user cancel ends the whole generation; the partial
+                // text persists below, marked interrupted.
+                cancelled = true;
+                break 'generation;
+            }
+            if outcome.tool_calls.is_empty() {
+                break;
+            }
+            let messages = payload["messages"]
+                .as_array_mut()
+                .ok_or_else(|| AppError::Internal("malformed chat payload".to_string()))?;
+            messages.push(serde_json::json!({
+                "role": "assistant",
+                "content": "",
+                "tool_calls": outcome.tool_calls,
+            }));
+            for call in &outcome.tool_calls {
+                let name = call
+                    .pointer("/function/name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let arguments = call
+                    .pointer("/function/arguments")
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                // Every model-triggered tool call passes the permission
+                // gate first and lands in the audit log either way.
+                let allowed =
+                    permissions::ensure_tool_allowed(app, db, chat_id, name, &arguments).await;
+                let result = match allowed {
+                    Err(denied) => {
+                        permissions::audit(
+                            db,
+                            Some(chat_id),
+                            name,
+                            &arguments,
+                            "denied",
+                            Some(&denied),
+                        );
+                        format!("tool error: {}", denied)
+                    }
+                    Ok(()) => {
+                        // Plugin tools get first refusal on the
+                        // namespaced name; everything else routes to MCP.
+                        let dispatched =
+                            match plugins::dispatch_tool_call(app, name, arguments.clone()).await {
+                                Some(result) => result,
+                                None => mcp::dispatch_tool_call(app, name, arguments.clone()).await,
+                            };
+                        match dispatched {
+                            Ok(text) => {
+                                permissions::audit(
+                                    db,
+                                    Some(chat_id),
+                                    name,
+                                    &arguments,
+                                    "ok",
+                                    None,
+                                );
+                                text
+                            }
+                            Err(e) => {
+                                permissions::audit(
+                                    db,
+                                    Some(chat_id),
+                                    name,
+                                    &arguments,
+                                    "error",
+                                    Some(&e),
+                                );
+                                format!("tool error: {}", e)
+                            }
+                        }
+                    }
+                };
+                let messages = payload["messages"]
+                    .as_array_mut()
+                    .ok_or_else(|| AppError::Internal("malformed chat payload".to_string()))?;
+                messages.push(serde_json::json!({ "role": "tool", "content": result }));
+            }
+        }
+
+        let Some(constraint) = &constraint else { break };
+        match constraint.validate(&full_response) {
+            Ok(()) => break,
+            Err(problem) if attempt < MAX_CONSTRAINT_RETRIES => {
+                // Feed the rejected answer back with a corrective nudge
+                // and regenerate from scratch.
+                let messages = payload["messages"]
+                    .as_array_mut()
+                    .ok_or_else(|| AppError::Internal("malformed chat payload".to_string()))?;
+                messages.push(
+                    serde_json::json!({ "role": "assistant", "content": full_response }),
+                );
+                messages.push(serde_json::json!({
+                    "role": "system",
+                    "content": format!(
+                        "Your previous answer was rejected: {}. Answer again, satisfying the constraint exactly.",
+                        problem
+                    ),
+                }));
+                full_response.clear();
+            }
+            Err(problem) => {
+                return Err(AppError::InvalidInput(format!(
+                    "response still violates the constraint after {} retries: {}",
+                    MAX_CONSTRAINT_RETRIES, problem
+                )));
+            }
+        }
+    }
+
+    if let Some(key) = &cache_key {
+        if !cancelled {
+            cache::store(db, key, model, &full_response);
+        }
+    }
+    // Replace the streaming checkpoint row (if any) with the real
+    // message, keeping the interrupted marker for cancelled runs.
+    db.conn().execute(
+        "DELETE FROM messages WHERE id = ?1 AND interrupted = 1",
+        params![message_id],
+    )?;
+    // Configured post-processors run on the final text; when they
+    // change anything the raw model output is kept in raw_content.
+    let (processed, postprocessed) = postprocess::apply(db, &full_response);
+    let message = insert_message(db, chat_id, "assistant", &processed, Some(model))?;
+    sources::record_all(db, chat_id, &message.id, &context.sources);
+    if postprocessed {
+        db.conn().execute(
+            "UPDATE messages SET raw_content = ?1 WHERE id = ?2",
+            params![full_response, message.id],
+        )?;
+    }
+    if cancelled {
+        db.conn().execute(
+            "UPDATE messages SET interrupted = 1 WHERE id = ?1",
+            params![message.id],
+        )?;
+    }
+    knowledge::embed_message_background(app, &message);
+    triggers::fire_assistant_message(app, &message);
+    translate::auto_translate_background(app, content, &message);
+    hooks::fire(
+        app,
+        "chat-complete",
+        serde_json::to_value(&message).unwrap_or_default(),
+    );
+    tray::emit_or_notify(app, "generation-finished", &message);
+    let title: String = db
+        .conn()
+        .query_row(
+            "SELECT title FROM chats WHERE id = ?1",
+            params![chat_id],
+            |row| row.get(0),
+        )
+        .unwrap_or_else(|_| "Chat".to_string());
+    tray::notify_if_unfocused(app, &title, &tray::preview(&full_response, 120));
+    Ok(message)
+}
+
+/// Raise the stop flag for a chat's in-flight generation. The stream
+/// loop notices on the next chunk and persists what has arrived so far.
+#[tauri::command]
+pub fn stop_generation(
+    generations: State<ActiveGenerations>,
+    chat_id: String,
+) -> AppResult<()> {
+    match generations.0.lock().unwrap().get(&chat_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        None => Err(AppError::NotFound(format!("no generation running for chat {}", chat_id))),
+    }
+}
+
+/// Resume a truncated assistant response (cancelled or cut off at
+/// max_tokens): the context up to and including the partial response is
+/// resent with a continuation instruction, and new tokens are appended
+/// to the same message row instead of creating a new one.
+#[tauri::command]
+pub async fn continue_generation(
+    app: AppHandle,
+    db: State<'_, Db>,
+    chat_id: String,
+    message_id: String,
+) -> AppResult<Message> {
+    let (model, partial) = {
+        let conn = db.conn();
+        let model: String = conn
+            .query_row(
+                "SELECT model FROM chats WHERE id = ?1",
+                params![chat_id],
+                |row| row.get(0),
+            )?;
+        let partial: String = conn
+            .query_row(
+                "SELECT content, compressed, content_zstd FROM messages
+                 WHERE id = ?1 AND chat_id = ?2 AND role = 'assistant'",
+                params![message_id, chat_id],
+                |row| {
+                    Ok(compression::stored(
+                        row.get(0)?,
+                        row.get::<_, i64>(1)? != 0,
+                        row.get(2)?,
+                    ))
+                },
+            )?;
+        (model, partial)
+    };
+    ensure_unlocked(&db, &chat_id)?;
+
+    // A continuation streams a full generation, so it takes a
+    // concurrency slot like any other `chat` call.
+    let scheduler = app.state::<GenerationScheduler>();
+    let _slot = scheduler.acquire(&app, &db, &chat_id).await?;
+
+    let mut context = build_context(&db, &chat_id, &model, "", false).await?;
+    // build_context appended an empty user turn for the "next" message;
+    // replace it with the continuation instruction.
+    context.messages.pop();
+    context.add_message(
+        "user",
+        "Continue your previous answer exactly where it stopped. \
+         Do not repeat anything you already wrote.",
+        false,
+    )?;
+
+    let payload = chat_payload(&context, &model, &None);
+    // Seed the stream with the stored partial so periodic checkpoints
+    // (and the returned text) always hold the full combined answer.
+    let combined = stream_response(&app, &payload, &chat_id, &message_id, false, &partial, false)
+        .await?
+        .text;
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE messages SET content = ?1, content_zstd = NULL, compressed = 0, interrupted = 0
+         WHERE id = ?2",
+        params![combined, message_id],
+    )?;
+    journal::record(
+        &conn,
+        "message",
+        &message_id,
+        journal::Op::Update,
+        Some(serde_json::json!({ "content": combined }).to_string()),
+    );
+    conn.query_row(
+        "SELECT id, chat_id, role, content, model, created_at, compressed, content_zstd
+         FROM messages WHERE id = ?1",
+        params![message_id],
+        |row| {
+            Ok(Message {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                role: row.get(2)?,
+                content: compression::stored(row.get(3)?, row.get::<_, i64>(6)? != 0, row.get(7)?),
+                model: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        },
+    )
+    .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn chat(
+    app: AppHandle,
+    db: State<'_, Db>,
+    chat_id: String,
+    model: String,
+    content: String,
+    format: Option<Value>,
+    constraint: Option<Constraint>,
+    low_latency: Option<bool>,
+) -> AppResult<Message> {
+    // Slash commands short-circuit generation entirely.
+    if let Some(message) = commands::dispatch(&app, &db, &chat_id, &model, &content).await? {
+        return Ok(message);
+    }
+    run_generation_with(
+        &app,
+        &db,
+        &chat_id,
+        &model,
+        &content,
+        format,
+        constraint,
+        low_latency.unwrap_or(false),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{match_offsets, TokenCoalescer};
+    use std::time::Duration;
+
+    #[test]
+    fn coalescer_batches_until_done_within_frame() {
+        let mut coalescer = TokenCoalescer::new(Duration::from_secs(3600));
+        assert_eq!(coalescer.push("Hel", false), None);
+        assert_eq!(coalescer.push("lo", false), None);
+        assert_eq!(coalescer.push("!", true), Some("Hello!".to_string()));
+    }
+
+    #[test]
+    fn zero_frame_flushes_every_token() {
+        let mut coalescer = TokenCoalescer::new(Duration::ZERO);
+        assert_eq!(coalescer.push("a", false), Some("a".to_string()));
+        assert_eq!(coalescer.push("b", false), Some("b".to_string()));
+    }
+
+    #[test]
+    fn done_flushes_even_when_nothing_is_buffered() {
+        let mut coalescer = TokenCoalescer::new(Duration::from_secs(3600));
+        assert_eq!(coalescer.push("", true), Some(String::new()));
+    }
+
+    #[test]
+    fn finds_all_case_insensitive_matches() {
+        let offsets = match_offsets("Rust is great. I love rust.", "rust");
+        assert_eq!(offsets, vec![(0, 4), (22, 26)]);
+    }
+
+    #[test]
+    fn empty_query_matches_nothing() {
+        assert!(match_offsets("anything", "").is_empty());
+    }
+}
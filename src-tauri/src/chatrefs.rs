@@ -0,0 +1,262 @@
+//! "@chat" references: a message containing `@chat:<id>` pulls that
+//! conversation in as context for the current generation. The
+//! `chat_reference_strategy` setting picks how: `summary` injects a
+//! model-written summary of the referenced chat, `relevant` injects its
+//! most similar messages by embedding. Every injected span is recorded
+//! in the context_refs table against the user message that triggered
+//! it, so exports can show exactly what external context was used.
+
+use regex::Regex;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use uuid::Uuid;
+
+use crate::compression;
+use crate::db::{self, Db};
+use crate::error::{AppError, AppResult};
+use crate::knowledge;
+use crate::settings;
+use crate::web;
+
+const STRATEGY_KEY: &str = "chat_reference_strategy";
+/// Messages injected under the `relevant` strategy.
+const RELEVANT_TOP_K: usize = 5;
+
+/// One resolved reference: the text that was injected plus where it
+/// came from. Older exports without refs deserialize to an empty list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextRef {
+    pub id: String,
+    pub chat_id: String,
+    pub message_id: String,
+    pub referenced_chat_id: String,
+    pub strategy: String,
+    pub span: String,
+    pub created_at: String,
+}
+
+/// Chat ids referenced as `@chat:<id>` in a message, in order of first
+/// appearance.
+pub fn parse_references(text: &str) -> Vec<String> {
+    let re = Regex::new(r"@chat:([A-Za-z0-9-]+)").expect("valid reference pattern");
+    let mut ids = Vec::new();
+    for capture in re.captures_iter(text) {
+        let id = capture[1].to_string();
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+fn configured_strategy(db: &Db) -> String {
+    settings::get(db, STRATEGY_KEY).unwrap_or_else(|| "summary".to_string())
+}
+
+async fn summarize_chat(db: &Db, referenced: &str, model: &str) -> AppResult<(String, String)> {
+    let (title, transcript) = {
+        let conn = db.conn();
+        let title: String = conn.query_row(
+            "SELECT title FROM chats WHERE id = ?1 AND deleted_at IS NULL",
+            params![referenced],
+            |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(
+            "SELECT role, content, compressed, content_zstd FROM messages
+             WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+        )?;
+        let lines = stmt
+            .query_map(params![referenced], |row| {
+                Ok(format!(
+                    "{}: {}",
+                    row.get::<_, String>(0)?,
+                    compression::stored(row.get(1)?, row.get::<_, i64>(2)? != 0, row.get(3)?)
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        (title, lines.join("\n\n"))
+    };
+    let summary = web::generate(
+        model,
+        &format!(
+            "Summarize this conversation so it can serve as background context \
+             for another discussion. Keep concrete facts, decisions and open \
+             questions.\n\n{}",
+            transcript
+        ),
+    )
+    .await?;
+    Ok((title, summary))
+}
+
+async fn relevant_messages(db: &Db, referenced: &str, query: &str) -> AppResult<(String, String)> {
+    let query_embedding = knowledge::embed(query).await?;
+    let (title, mut scored) = {
+        let conn = db.conn();
+        let title: String = conn.query_row(
+            "SELECT title FROM chats WHERE id = ?1 AND deleted_at IS NULL",
+            params![referenced],
+            |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(
+            "SELECT m.role, m.content, e.embedding, m.compressed, m.content_zstd
+             FROM message_embeddings e JOIN messages m ON m.id = e.message_id
+             WHERE e.chat_id = ?1 AND m.deleted_at IS NULL",
+        )?;
+        let rows = stmt
+            .query_map(params![referenced], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    compression::stored(row.get(1)?, row.get::<_, i64>(3)? != 0, row.get(4)?),
+                    row.get::<_, Vec<u8>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let scored: Vec<(f32, String)> = rows
+            .into_iter()
+            .map(|(role, content, blob)| {
+                (
+                    knowledge::cosine_similarity(
+                        &query_embedding,
+                        &knowledge::blob_to_embedding(&blob),
+                    ),
+                    format!("{}: {}", role, content),
+                )
+            })
+            .collect();
+        (title, scored)
+    };
+    if scored.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "chat {} has no embedded messages to retrieve from",
+            referenced
+        )));
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(RELEVANT_TOP_K);
+    Ok((title, scored.into_iter().map(|(_, line)| line).collect::<Vec<_>>().join("\n\n")))
+}
+
+/// Resolve one reference under `strategy` (`summary` or `relevant`).
+/// `query` guides the `relevant` strategy; `model` writes summaries.
+pub(crate) async fn resolve(
+    db: &Db,
+    referenced: &str,
+    strategy: &str,
+    query: &str,
+    model: &str,
+) -> AppResult<(String, String)> {
+    match strategy {
+        "summary" => summarize_chat(db, referenced, model).await,
+        "relevant" => relevant_messages(db, referenced, query).await,
+        other => Err(AppError::InvalidInput(format!(
+            "unknown reference strategy: {}",
+            other
+        ))),
+    }
+}
+
+/// Resolve every `@chat:` reference in a user message under the
+/// configured strategy. Returns the spans to inject, formatted and
+/// ready to prepend to the outgoing message content.
+pub(crate) async fn resolve_all(
+    db: &Db,
+    content: &str,
+    model: &str,
+) -> AppResult<Vec<(String, String, String)>> {
+    let strategy = configured_strategy(db);
+    let mut resolved = Vec::new();
+    for referenced in parse_references(content) {
+        let (title, span) = resolve(db, &referenced, &strategy, content, model).await?;
+        let formatted = format!(
+            "[Context from chat \"{}\" ({})]\n{}",
+            title, strategy, span
+        );
+        resolved.push((referenced, strategy.clone(), formatted));
+    }
+    Ok(resolved)
+}
+
+/// Record an injected span against the user message that triggered it.
+pub(crate) fn record(
+    db: &Db,
+    chat_id: &str,
+    message_id: &str,
+    referenced: &str,
+    strategy: &str,
+    span: &str,
+) {
+    let conn = db.conn();
+    let _ = conn.execute(
+        "INSERT INTO context_refs (id, chat_id, message_id, referenced_chat_id, strategy, span, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            Uuid::new_v4().to_string(),
+            chat_id,
+            message_id,
+            referenced,
+            strategy,
+            span,
+            db::now()
+        ],
+    );
+}
+
+/// The recorded refs for a chat, oldest first (used by exports).
+pub(crate) fn refs_for_chat(db: &Db, chat_id: &str) -> AppResult<Vec<ContextRef>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, chat_id, message_id, referenced_chat_id, strategy, span, created_at
+         FROM context_refs WHERE chat_id = ?1 ORDER BY created_at ASC",
+    )?;
+    let refs = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(ContextRef {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                message_id: row.get(2)?,
+                referenced_chat_id: row.get(3)?,
+                strategy: row.get(4)?,
+                span: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(refs)
+}
+
+/// Preview what a reference would inject, without running a generation.
+#[tauri::command]
+pub async fn resolve_chat_reference(
+    db: State<'_, Db>,
+    chat_id: String,
+    strategy: String,
+    query: Option<String>,
+    model: String,
+) -> AppResult<String> {
+    let (title, span) = resolve(
+        &db,
+        &chat_id,
+        &strategy,
+        query.as_deref().unwrap_or_default(),
+        &model,
+    )
+    .await?;
+    Ok(format!(
+        "[Context from chat \"{}\" ({})]\n{}",
+        title, strategy, span
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_references;
+
+    #[test]
+    fn references_parse_in_order_and_dedupe() {
+        let text = "compare @chat:abc-123 with @chat:def and @chat:abc-123 again";
+        assert_eq!(parse_references(text), vec!["abc-123", "def"]);
+        assert!(parse_references("an email@chat.example.com is not a ref").is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+//! Post-generation citation verification for answers produced from
+//! retrieved sources. Inline `[n]` markers in the assistant output are
+//! matched back against the numbered source list; markers that point at
+//! no source are flagged so hallucinated references are visible, and
+//! sources the answer never cites are reported too.
+
+use regex::Regex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CitationReport {
+    pub chat_id: String,
+    pub message_id: String,
+    pub total_sources: usize,
+    /// Source numbers the answer cites, in first-use order.
+    pub cited: Vec<usize>,
+    /// Markers with no corresponding source — likely hallucinated.
+    pub invalid: Vec<usize>,
+    /// Sources that were provided but never cited.
+    pub uncited: Vec<usize>,
+}
+
+/// All `[n]` markers in the text, in order, duplicates removed.
+pub fn extract_markers(text: &str) -> Vec<usize> {
+    let re = Regex::new(r"\[(\d+)\]").expect("marker regex is valid");
+    let mut markers = Vec::new();
+    for capture in re.captures_iter(text) {
+        if let Ok(n) = capture[1].parse::<usize>() {
+            if !markers.contains(&n) {
+                markers.push(n);
+            }
+        }
+    }
+    markers
+}
+
+/// Check every marker in `text` against a source list numbered
+/// `1..=source_count`.
+pub fn verify(text: &str, source_count: usize) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
+    let markers = extract_markers(text);
+    let (cited, invalid): (Vec<usize>, Vec<usize>) = markers
+        .into_iter()
+        .partition(|&n| n >= 1 && n <= source_count);
+    let uncited = (1..=source_count).filter(|n| !cited.contains(n)).collect();
+    (cited, invalid, uncited)
+}
+
+/// Verify and emit the report as `citation-report-{chat_id}`, suffixed
+/// so concurrent reviews in different chats don't cross streams.
+pub(crate) fn report(
+    app: &AppHandle,
+    chat_id: &str,
+    message_id: &str,
+    text: &str,
+    source_count: usize,
+) -> CitationReport {
+    let (cited, invalid, uncited) = verify(text, source_count);
+    let report = CitationReport {
+        chat_id: chat_id.to_string(),
+        message_id: message_id.to_string(),
+        total_sources: source_count,
+        cited,
+        invalid,
+        uncited,
+    };
+    if !report.invalid.is_empty() {
+        tracing::warn!(
+            "message {} cites nonexistent sources: {:?}",
+            message_id,
+            report.invalid
+        );
+    }
+    let _ = app.emit(&format!("citation-report-{}", chat_id), &report);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_markers, verify};
+
+    #[test]
+    fn markers_are_extracted_in_order_without_duplicates() {
+        assert_eq!(
+            extract_markers("As shown in [2], and again [1][2]; see [10]."),
+            vec![2, 1, 10]
+        );
+        assert!(extract_markers("no citations here").is_empty());
+    }
+
+    #[test]
+    fn verify_partitions_valid_invalid_and_uncited() {
+        let (cited, invalid, uncited) = verify("Per [1] and [4], also [0].", 3);
+        assert_eq!(cited, vec![1]);
+        assert_eq!(invalid, vec![4, 0]);
+        assert_eq!(uncited, vec![2, 3]);
+    }
+}
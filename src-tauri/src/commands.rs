@@ -0,0 +1,283 @@
+//! Backend slash-command registry. Messages starting with a registered
+//! command (`/search`, `/summarize`, `/model`, `/pin`,
+//! `/clear-context`, plus any granted plugin tool as
+//! `/plugin__tool`) are intercepted before generation and handled in
+//! Rust, so the behavior is identical whichever frontend sends them.
+//! Unregistered leading slashes — file paths, for instance — fall
+//! through to normal chat. `list_slash_commands` exposes the registry
+//! for autocomplete.
+
+use rusqlite::params;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::chat;
+use crate::compression;
+use crate::db::{self, Db};
+use crate::error::{AppError, AppResult};
+use crate::plugins::{self, PluginState};
+use crate::web;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlashCommand {
+    pub name: String,
+    pub description: String,
+    pub usage: String,
+    /// `builtin` or `plugin:<name>`.
+    pub source: String,
+}
+
+fn builtin(name: &str, description: &str, usage: &str) -> SlashCommand {
+    SlashCommand {
+        name: name.to_string(),
+        description: description.to_string(),
+        usage: usage.to_string(),
+        source: "builtin".to_string(),
+    }
+}
+
+fn builtins() -> Vec<SlashCommand> {
+    vec![
+        builtin("search", "Search this chat's messages", "/search <query>"),
+        builtin("summarize", "Summarize the conversation so far", "/summarize"),
+        builtin("model", "Switch this chat's model", "/model <name>"),
+        builtin("pin", "Pin the latest assistant message", "/pin"),
+        builtin(
+            "clear-context",
+            "Drop earlier messages from the model's context (pinned ones stay)",
+            "/clear-context",
+        ),
+    ]
+}
+
+/// Split `/name args` into name and trimmed arguments. Returns `None`
+/// unless the input starts with a slash followed by a plausible command
+/// name, so paths like `/home/...` are never mistaken for commands.
+pub fn parse(input: &str) -> Option<(&str, &str)> {
+    let rest = input.trim_start().strip_prefix('/')?;
+    let name_end = rest
+        .find(char::is_whitespace)
+        .unwrap_or(rest.len());
+    let name = &rest[..name_end];
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    Some((name, rest[name_end..].trim()))
+}
+
+/// Everything the registry currently answers to, builtins first.
+#[tauri::command]
+pub fn list_slash_commands(app: AppHandle) -> Vec<SlashCommand> {
+    let mut commands = builtins();
+    let state = app.state::<PluginState>();
+    let loaded = state.0.lock().unwrap();
+    for plugin in loaded.values() {
+        for tool in &plugin.tools {
+            commands.push(SlashCommand {
+                name: format!("{}__{}", plugin.name, tool.name),
+                description: tool.description.clone(),
+                usage: format!("/{}__{} <arguments>", plugin.name, tool.name),
+                source: format!("plugin:{}", plugin.name),
+            });
+        }
+    }
+    commands
+}
+
+fn run_search(db: &Db, chat_id: &str, query: &str) -> AppResult<String> {
+    if query.is_empty() {
+        return Err(AppError::InvalidInput("usage: /search <query>".to_string()));
+    }
+    let conn = db.conn();
+    // Matching happens in Rust rather than with LIKE: compressed rows
+    // keep an empty `content` column, so SQL can't see their text.
+    let mut stmt = conn.prepare(
+        "SELECT content, compressed, content_zstd FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL
+         ORDER BY created_at ASC",
+    )?;
+    let matches: Vec<String> = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(compression::stored(
+                row.get(0)?,
+                row.get::<_, i64>(1)? != 0,
+                row.get(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|content| content.contains(query))
+        .collect();
+    if matches.is_empty() {
+        return Ok(format!("No messages match \"{}\".", query));
+    }
+    let mut out = format!("{} message(s) match \"{}\":\n", matches.len(), query);
+    for content in matches.iter().take(10) {
+        out.push_str(&format!("\n> {}\n", snippet(content, query)));
+    }
+    Ok(out)
+}
+
+/// A short excerpt around the first occurrence of `query`.
+pub fn snippet(content: &str, query: &str) -> String {
+    const RADIUS: usize = 80;
+    let pos = content.find(query).unwrap_or(0);
+    let start = content[..pos]
+        .char_indices()
+        .map(|(i, _)| i)
+        .rfind(|&i| pos - i >= RADIUS)
+        .unwrap_or(0);
+    let end = content[pos..]
+        .char_indices()
+        .map(|(i, _)| pos + i)
+        .find(|&i| i - pos >= RADIUS + query.len())
+        .unwrap_or(content.len());
+    let mut excerpt = content[start..end].replace('\n', " ");
+    if start > 0 {
+        excerpt = format!("…{}", excerpt);
+    }
+    if end < content.len() {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+async fn run_summarize(db: &Db, chat_id: &str, model: &str) -> AppResult<String> {
+    let transcript = {
+        let conn = db.conn();
+        let mut stmt = conn.prepare(
+            "SELECT role, content, compressed, content_zstd FROM messages
+             WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+        )?;
+        let lines = stmt
+            .query_map(params![chat_id], |row| {
+                Ok(format!(
+                    "{}: {}",
+                    row.get::<_, String>(0)?,
+                    compression::stored(row.get(1)?, row.get::<_, i64>(2)? != 0, row.get(3)?)
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        lines.join("\n\n")
+    };
+    if transcript.is_empty() {
+        return Ok("Nothing to summarize yet.".to_string());
+    }
+    web::generate(
+        model,
+        &format!(
+            "Summarize this conversation concisely: main topics, conclusions \
+             and open questions.\n\n{}",
+            transcript
+        ),
+    )
+    .await
+}
+
+fn run_model_switch(db: &Db, chat_id: &str, model: &str) -> AppResult<String> {
+    if model.is_empty() {
+        return Err(AppError::InvalidInput("usage: /model <name>".to_string()));
+    }
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET model = ?1, updated_at = ?2 WHERE id = ?3",
+        params![model, db::now(), chat_id],
+    )?;
+    Ok(format!("Model switched to {}.", model))
+}
+
+fn run_pin(db: &Db, chat_id: &str) -> AppResult<String> {
+    let conn = db.conn();
+    let updated = conn.execute(
+        "UPDATE messages SET pinned = 1 WHERE id = (
+             SELECT id FROM messages
+             WHERE chat_id = ?1 AND role = 'assistant' AND deleted_at IS NULL
+             ORDER BY created_at DESC LIMIT 1
+         )",
+        params![chat_id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::NotFound(
+            "no assistant message to pin".to_string(),
+        ));
+    }
+    Ok("Pinned the latest assistant message.".to_string())
+}
+
+fn run_clear_context(db: &Db, chat_id: &str) -> AppResult<String> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chats SET context_cleared_at = ?1 WHERE id = ?2",
+        params![db::now(), chat_id],
+    )?;
+    Ok("Context cleared. Earlier messages stay in the transcript but \
+        won't be sent to the model; pinned messages are kept."
+        .to_string())
+}
+
+async fn run_plugin(app: &AppHandle, name: &str, args: &str) -> Option<AppResult<String>> {
+    // Arguments are JSON when they parse as JSON, otherwise wrapped as
+    // `{ "input": ... }` so simple text-in/text-out tools stay usable.
+    let arguments = serde_json::from_str(args)
+        .unwrap_or_else(|_| serde_json::json!({ "input": args }));
+    let result = plugins::dispatch_tool_call(app, name, arguments).await?;
+    Some(result.map_err(AppError::Internal))
+}
+
+/// Intercept a slash command. `Ok(None)` means the input is not a
+/// registered command and should go through normal generation; a
+/// handled command records the input and its result in the transcript
+/// and returns the result message, mirroring `chat`.
+pub(crate) async fn dispatch(
+    app: &AppHandle,
+    db: &Db,
+    chat_id: &str,
+    model: &str,
+    input: &str,
+) -> AppResult<Option<chat::Message>> {
+    let Some((name, args)) = parse(input) else {
+        return Ok(None);
+    };
+    let output = match name {
+        "search" => run_search(db, chat_id, args)?,
+        "summarize" => run_summarize(db, chat_id, model).await?,
+        "model" => run_model_switch(db, chat_id, args)?,
+        "pin" => run_pin(db, chat_id)?,
+        "clear-context" => run_clear_context(db, chat_id)?,
+        _ => match run_plugin(app, name, args).await {
+            Some(result) => result?,
+            None => return Ok(None),
+        },
+    };
+    chat::insert_message(db, chat_id, "user", input.trim(), None)?;
+    let message = chat::insert_message(db, chat_id, "assistant", &output, None)?;
+    Ok(Some(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, snippet};
+
+    #[test]
+    fn parses_commands_but_not_paths() {
+        assert_eq!(parse("/model llama3"), Some(("model", "llama3")));
+        assert_eq!(parse("  /pin  "), Some(("pin", "")));
+        assert_eq!(parse("/clear-context"), Some(("clear-context", "")));
+        assert_eq!(parse("/home/user/notes.txt is the file"), None);
+        assert_eq!(parse("not a command"), None);
+        assert_eq!(parse("/"), None);
+    }
+
+    #[test]
+    fn snippets_center_on_the_match() {
+        let long = format!("{}needle{}", "a".repeat(200), "b".repeat(200));
+        let excerpt = snippet(&long, "needle");
+        assert!(excerpt.contains("needle"));
+        assert!(excerpt.starts_with('…') && excerpt.ends_with('…'));
+        assert_eq!(snippet("short needle here", "needle"), "short needle here");
+    }
+}
@@ -0,0 +1,108 @@
+//! Transparent zstd compression for large message content. Chats full
+//! of code blocks balloon the database; content above the threshold is
+//! stored compressed in `messages.content_zstd` (with `compressed = 1`
+//! and an empty `content` column) and decompressed on read through
+//! `stored`. `compact_database` recompresses historical rows in place
+//! and VACUUMs to actually reclaim the space.
+
+use rusqlite::params;
+use serde::Serialize;
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::AppResult;
+
+/// Content at or above this many bytes is stored compressed. Short
+/// messages aren't worth the round-trip: zstd headers eat the savings.
+pub(crate) const THRESHOLD_BYTES: usize = 4096;
+
+/// zstd level 3: the default, fast enough to be invisible on insert.
+const LEVEL: i32 = 3;
+
+pub fn compress(text: &str) -> Vec<u8> {
+    zstd::encode_all(text.as_bytes(), LEVEL).unwrap_or_else(|_| text.as_bytes().to_vec())
+}
+
+pub fn decompress(blob: &[u8]) -> String {
+    zstd::decode_all(blob)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_default()
+}
+
+/// Resolve a message's content from its three storage columns: the
+/// plain `content` text, the `compressed` flag and the `content_zstd`
+/// blob. Every read path goes through this.
+pub(crate) fn stored(content: String, compressed: bool, blob: Option<Vec<u8>>) -> String {
+    if compressed {
+        blob.as_deref().map(decompress).unwrap_or(content)
+    } else {
+        content
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactReport {
+    /// Historical rows compressed by this pass.
+    pub compressed_rows: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// One-time (or occasional) maintenance: compress every historical row
+/// above the threshold that is still stored plain, then VACUUM so the
+/// freed pages are returned to the filesystem.
+#[tauri::command]
+pub fn compact_database(db: State<Db>) -> AppResult<CompactReport> {
+    let conn = db.conn();
+    let rows: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, content FROM messages
+             WHERE compressed = 0 AND LENGTH(content) >= ?1",
+        )?;
+        let rows = stmt
+            .query_map(params![THRESHOLD_BYTES], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows
+    };
+    let bytes_before: u64 = rows.iter().map(|(_, content)| content.len() as u64).sum();
+    let mut bytes_after = 0u64;
+    let tx = conn.unchecked_transaction()?;
+    for (id, content) in &rows {
+        let blob = compress(content);
+        bytes_after += blob.len() as u64;
+        tx.execute(
+            "UPDATE messages SET content = '', content_zstd = ?1, compressed = 1 WHERE id = ?2",
+            params![blob, id],
+        )?;
+    }
+    tx.commit()?;
+    conn.execute("VACUUM", [])?;
+    Ok(CompactReport {
+        compressed_rows: rows.len(),
+        bytes_before,
+        bytes_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, decompress, stored};
+
+    #[test]
+    fn round_trips_and_actually_shrinks() {
+        let text = "fn main() {}\n".repeat(500);
+        let blob = compress(&text);
+        assert!(blob.len() < text.len());
+        assert_eq!(decompress(&blob), text);
+    }
+
+    #[test]
+    fn stored_prefers_the_blob_only_when_flagged() {
+        let blob = compress("big");
+        assert_eq!(stored(String::new(), true, Some(blob)), "big");
+        assert_eq!(stored("plain".to_string(), false, None), "plain");
+    }
+}
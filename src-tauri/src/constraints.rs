@@ -0,0 +1,135 @@
+//! Output constraints for generation: presets for common shapes (valid
+//! JSON, yes/no, a choice list, a regex) that map onto Ollama's `format`
+//! field where possible and are validated server-side before the final
+//! message is accepted. Violations trigger a bounded retry with a
+//! corrective nudge (see `chat::run_generation_with`).
+
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Constraint {
+    /// The response must be a valid JSON document.
+    Json,
+    /// The response must be exactly `yes` or `no`.
+    YesNo,
+    /// The response must be one of the given options.
+    Choice { options: Vec<String> },
+    /// The whole response must match the pattern.
+    Regex { pattern: String },
+}
+
+impl Constraint {
+    /// The Ollama `format` field to request, when the constraint has a
+    /// native equivalent. Everything else is enforced by instruction
+    /// plus validation.
+    pub fn format(&self) -> Option<Value> {
+        match self {
+            Constraint::Json => Some(Value::String("json".to_string())),
+            Constraint::YesNo | Constraint::Choice { .. } | Constraint::Regex { .. } => None,
+        }
+    }
+
+    /// System instruction describing the constraint to the model.
+    pub fn instruction(&self) -> String {
+        match self {
+            Constraint::Json => "Respond with a single valid JSON document and nothing else."
+                .to_string(),
+            Constraint::YesNo => {
+                "Respond with exactly one word: yes or no. No punctuation, no explanation."
+                    .to_string()
+            }
+            Constraint::Choice { options } => format!(
+                "Respond with exactly one of the following options and nothing else: {}.",
+                options.join(", ")
+            ),
+            Constraint::Regex { pattern } => format!(
+                "Your entire response must match this regular expression: {}",
+                pattern
+            ),
+        }
+    }
+
+    /// Check the assembled response against the constraint. Returns a
+    /// description of the violation, which is fed back to the model as
+    /// the corrective nudge.
+    pub fn validate(&self, response: &str) -> Result<(), String> {
+        let trimmed = response.trim();
+        match self {
+            Constraint::Json => match serde_json::from_str::<Value>(trimmed) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(format!("the response is not valid JSON ({})", e)),
+            },
+            Constraint::YesNo => {
+                if trimmed.eq_ignore_ascii_case("yes") || trimmed.eq_ignore_ascii_case("no") {
+                    Ok(())
+                } else {
+                    Err("the response must be exactly 'yes' or 'no'".to_string())
+                }
+            }
+            Constraint::Choice { options } => {
+                if options.iter().any(|o| trimmed.eq_ignore_ascii_case(o)) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "the response must be exactly one of: {}",
+                        options.join(", ")
+                    ))
+                }
+            }
+            Constraint::Regex { pattern } => {
+                // Anchor so the whole response must match, not a substring.
+                let anchored = format!("^(?s:{})$", pattern);
+                let re = regex::Regex::new(&anchored)
+                    .map_err(|e| format!("invalid constraint pattern: {}", e))?;
+                if re.is_match(trimmed) {
+                    Ok(())
+                } else {
+                    Err(format!("the response does not match the pattern {}", pattern))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Constraint;
+
+    #[test]
+    fn yes_no_accepts_either_case_and_rejects_prose() {
+        let c = Constraint::YesNo;
+        assert!(c.validate("Yes").is_ok());
+        assert!(c.validate(" no \n").is_ok());
+        assert!(c.validate("yes, definitely").is_err());
+    }
+
+    #[test]
+    fn choice_matches_case_insensitively() {
+        let c = Constraint::Choice {
+            options: vec!["red".to_string(), "green".to_string()],
+        };
+        assert!(c.validate("Green").is_ok());
+        assert!(c.validate("blue").is_err());
+    }
+
+    #[test]
+    fn regex_must_match_the_whole_response() {
+        let c = Constraint::Regex {
+            pattern: r"\d{4}-\d{2}-\d{2}".to_string(),
+        };
+        assert!(c.validate("2024-05-01").is_ok());
+        assert!(c.validate("on 2024-05-01 probably").is_err());
+    }
+
+    #[test]
+    fn json_constraint_requests_native_format() {
+        assert_eq!(
+            Constraint::Json.format(),
+            Some(serde_json::Value::String("json".to_string()))
+        );
+        assert!(Constraint::Json.validate("{\"ok\": true}").is_ok());
+        assert!(Constraint::Json.validate("not json").is_err());
+    }
+}
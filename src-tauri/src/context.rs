@@ -0,0 +1,379 @@
+//! Context window management for a chat. `ChatContext` keeps the running
+//! message list trimmed to the model's context window using a cheap
+//! chars/4 token estimate, under a configurable pruning policy.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::error::AppResult;
+use crate::knowledge;
+use crate::ollama::ModelConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextMessage {
+    pub role: String,
+    pub content: String,
+    pub pinned: bool,
+    pub tokens: usize,
+    /// Set when the chat uses relevance-based pruning.
+    #[serde(skip)]
+    pub embedding: Option<Vec<f32>>,
+}
+
+/// How to choose which non-pinned message goes when the window overflows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PruningPolicy {
+    /// Drop the oldest non-pinned message first.
+    #[default]
+    OldestFirst,
+    /// Keep the first exchange and the most recent messages; prune from
+    /// the middle of the conversation.
+    MiddleOut,
+    /// Drop the non-pinned message least similar to the latest message,
+    /// judged by embeddings (falls back to oldest-first without them).
+    Relevance,
+}
+
+impl PruningPolicy {
+    pub fn parse(s: &str) -> PruningPolicy {
+        match s {
+            "middle_out" => PruningPolicy::MiddleOut,
+            "relevance" => PruningPolicy::Relevance,
+            _ => PruningPolicy::OldestFirst,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PruningPolicy::OldestFirst => "oldest_first",
+            PruningPolicy::MiddleOut => "middle_out",
+            PruningPolicy::Relevance => "relevance",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatContext {
+    pub model: String,
+    pub max_tokens: usize,
+    /// Hard cap on the share of the window pinned messages may occupy.
+    pub pinned_budget: usize,
+    pub policy: PruningPolicy,
+    pub messages: Vec<ContextMessage>,
+    /// Messages dropped by budget enforcement over this context's life.
+    pub pruned_messages: usize,
+    /// Chunks injected while assembling this context, recorded against
+    /// the assistant message once it exists. Not part of the payload.
+    #[serde(skip)]
+    pub sources: Vec<crate::sources::RetrievedSource>,
+}
+
+/// A point-in-time snapshot of a built context, persisted per
+/// generation so token usage and pruning can be charted over a chat's
+/// life.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextStats {
+    pub total_tokens: usize,
+    pub pinned_tokens: usize,
+    pub max_tokens: usize,
+    pub message_count: usize,
+    pub pruned_messages: usize,
+    pub policy: String,
+}
+
+/// Rough token estimate: ~4 characters per token for English text.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+impl ChatContext {
+    pub fn new(model: &str) -> Self {
+        Self::with_policy(model, PruningPolicy::default())
+    }
+
+    pub fn with_policy(model: &str, policy: PruningPolicy) -> Self {
+        let config = ModelConfig::get_default_config(model);
+        ChatContext {
+            model: model.to_string(),
+            max_tokens: config.context_window,
+            pinned_budget: config.context_window / 2,
+            policy,
+            messages: Vec::new(),
+            pruned_messages: 0,
+            sources: Vec::new(),
+        }
+    }
+
+    /// Adopt the real context window for the model (detected from
+    /// `/api/show`), keeping the pinned budget at half the window.
+    pub fn set_window(&mut self, max_tokens: usize) {
+        self.max_tokens = max_tokens;
+        self.pinned_budget = max_tokens / 2;
+    }
+
+    pub fn total_tokens(&self) -> usize {
+        self.messages.iter().map(|m| m.tokens).sum()
+    }
+
+    pub fn pinned_tokens(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|m| m.pinned)
+            .map(|m| m.tokens)
+            .sum()
+    }
+
+    /// Append a message, then prune under the active policy until the
+    /// estimated total fits in the context window. Errors when pinned
+    /// content alone can no longer fit.
+    pub fn add_message(&mut self, role: &str, content: &str, pinned: bool) -> Result<(), String> {
+        self.messages.push(ContextMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            pinned,
+            tokens: estimate_tokens(content),
+            embedding: None,
+        });
+        self.enforce_budget()
+    }
+
+    /// Re-run budget enforcement, e.g. after embeddings were filled in
+    /// for relevance-based pruning.
+    pub fn prune(&mut self) -> Result<(), String> {
+        self.enforce_budget()
+    }
+
+    fn enforce_budget(&mut self) -> Result<(), String> {
+        if self.pinned_tokens() > self.pinned_budget {
+            return Err(format!(
+                "pinned messages use {} tokens, over the pinned budget of {} — unpin something",
+                self.pinned_tokens(),
+                self.pinned_budget
+            ));
+        }
+        while self.total_tokens() > self.max_tokens {
+            match self.prune_candidate() {
+                Some(idx) => {
+                    self.messages.remove(idx);
+                    self.pruned_messages += 1;
+                }
+                None => {
+                    return Err(format!(
+                        "context window ({} tokens) is exhausted by pinned messages",
+                        self.max_tokens
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Index of the next message to drop, or `None` if only pinned
+    /// messages (and the message just added) remain.
+    fn prune_candidate(&self) -> Option<usize> {
+        let len = self.messages.len();
+        let prunable =
+            |(i, m): &(usize, &ContextMessage)| !m.pinned && *i != len.saturating_sub(1);
+        match self.policy {
+            PruningPolicy::OldestFirst => self
+                .messages
+                .iter()
+                .enumerate()
+                .find(prunable)
+                .map(|(i, _)| i),
+            PruningPolicy::MiddleOut => {
+                // Protect the first exchange (indices 0 and 1); if nothing
+                // in the middle is prunable, fall back to the front.
+                self.messages
+                    .iter()
+                    .enumerate()
+                    .skip(2)
+                    .find(prunable)
+                    .map(|(i, _)| i)
+                    .or_else(|| {
+                        self.messages
+                            .iter()
+                            .enumerate()
+                            .find(prunable)
+                            .map(|(i, _)| i)
+                    })
+            }
+            PruningPolicy::Relevance => {
+                let fallback = self
+                    .messages
+                    .iter()
+                    .enumerate()
+                    .find(prunable)
+                    .map(|(i, _)| i);
+                let Some(anchor) = self.messages.last().and_then(|m| m.embedding.as_ref()) else {
+                    return fallback;
+                };
+                self.messages
+                    .iter()
+                    .enumerate()
+                    .filter(prunable)
+                    .filter(|(_, m)| m.embedding.is_some())
+                    .min_by(|(_, a), (_, b)| {
+                        let sa = knowledge::cosine_similarity(
+                            a.embedding.as_deref().unwrap_or(&[]),
+                            anchor,
+                        );
+                        let sb = knowledge::cosine_similarity(
+                            b.embedding.as_deref().unwrap_or(&[]),
+                            anchor,
+                        );
+                        sa.total_cmp(&sb)
+                    })
+                    .map(|(i, _)| i)
+                    .or(fallback)
+            }
+        }
+    }
+
+    pub fn stats(&self) -> ContextStats {
+        ContextStats {
+            total_tokens: self.total_tokens(),
+            pinned_tokens: self.pinned_tokens(),
+            max_tokens: self.max_tokens,
+            message_count: self.messages.len(),
+            pruned_messages: self.pruned_messages,
+            policy: self.policy.as_str().to_string(),
+        }
+    }
+
+    /// Render an attachment's extracted text as a context block, truncated
+    /// so a single attachment can take at most a quarter of the window.
+    pub fn format_attachment(&self, file_name: &str, text: &str) -> String {
+        let budget = self.max_tokens / 4;
+        let mut body = text.to_string();
+        if estimate_tokens(&body) > budget {
+            let max_chars = budget * 4;
+            let cut = body
+                .char_indices()
+                .nth(max_chars)
+                .map(|(i, _)| i)
+                .unwrap_or(body.len());
+            body.truncate(cut);
+            body.push_str("\n[... attachment truncated ...]");
+        }
+        format!("[Attached file: {}]\n{}", file_name, body)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextHistoryEntry {
+    pub message_id: String,
+    pub stats: ContextStats,
+    pub created_at: String,
+}
+
+/// Persist the stats snapshot for one generation, keyed by the user
+/// message that triggered it. History is advisory, so failures are
+/// swallowed like journal writes.
+pub(crate) fn record_history(db: &Db, chat_id: &str, message_id: &str, stats: &ContextStats) {
+    let conn = db.conn();
+    let _ = conn.execute(
+        "INSERT INTO context_history
+         (id, chat_id, message_id, total_tokens, pinned_tokens, max_tokens,
+          message_count, pruned_messages, policy, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            Uuid::new_v4().to_string(),
+            chat_id,
+            message_id,
+            stats.total_tokens as i64,
+            stats.pinned_tokens as i64,
+            stats.max_tokens as i64,
+            stats.message_count as i64,
+            stats.pruned_messages as i64,
+            stats.policy,
+            db::now()
+        ],
+    );
+}
+
+/// Every stats snapshot recorded for a chat, oldest first, for charting
+/// token usage growth and pruning events over the conversation.
+#[tauri::command]
+pub fn get_context_history(db: State<Db>, chat_id: String) -> AppResult<Vec<ContextHistoryEntry>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT message_id, total_tokens, pinned_tokens, max_tokens,
+                message_count, pruned_messages, policy, created_at
+         FROM context_history WHERE chat_id = ?1 ORDER BY created_at ASC",
+    )?;
+    let entries = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(ContextHistoryEntry {
+                message_id: row.get(0)?,
+                stats: ContextStats {
+                    total_tokens: row.get::<_, i64>(1)? as usize,
+                    pinned_tokens: row.get::<_, i64>(2)? as usize,
+                    max_tokens: row.get::<_, i64>(3)? as usize,
+                    message_count: row.get::<_, i64>(4)? as usize,
+                    pruned_messages: row.get::<_, i64>(5)? as usize,
+                    policy: row.get(6)?,
+                },
+                created_at: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_context(policy: PruningPolicy) -> ChatContext {
+        let mut ctx = ChatContext::with_policy("test-model", policy);
+        ctx.max_tokens = 100;
+        ctx.pinned_budget = 50;
+        ctx
+    }
+
+    #[test]
+    fn oldest_first_prunes_from_the_front() {
+        let mut ctx = small_context(PruningPolicy::OldestFirst);
+        for i in 0..5 {
+            ctx.add_message("user", &format!("message {} {}", i, "x".repeat(120)), false)
+                .unwrap();
+        }
+        assert!(ctx.total_tokens() <= ctx.max_tokens);
+        assert!(ctx.messages.last().unwrap().content.contains("message 4"));
+        assert!(!ctx.messages.iter().any(|m| m.content.contains("message 0")));
+    }
+
+    #[test]
+    fn middle_out_keeps_first_exchange() {
+        let mut ctx = small_context(PruningPolicy::MiddleOut);
+        ctx.add_message("user", "first question", false).unwrap();
+        ctx.add_message("assistant", "first answer", false).unwrap();
+        for i in 0..5 {
+            ctx.add_message("user", &format!("later {} {}", i, "x".repeat(120)), false)
+                .unwrap();
+        }
+        assert!(ctx.messages[0].content.contains("first question"));
+        assert!(ctx.messages[1].content.contains("first answer"));
+    }
+
+    #[test]
+    fn errors_instead_of_looping_when_pinned_overflows() {
+        let mut ctx = small_context(PruningPolicy::OldestFirst);
+        ctx.pinned_budget = 1000;
+        ctx.add_message("user", &"x".repeat(300), true).unwrap();
+        let err = ctx.add_message("user", &"y".repeat(300), true).unwrap_err();
+        assert!(err.contains("pinned"));
+    }
+
+    #[test]
+    fn pinned_budget_is_enforced() {
+        let mut ctx = small_context(PruningPolicy::OldestFirst);
+        let err = ctx.add_message("user", &"x".repeat(300), true).unwrap_err();
+        assert!(err.contains("pinned budget"));
+    }
+}
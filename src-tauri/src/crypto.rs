@@ -0,0 +1,142 @@
+//! Optional database encryption. The database is SQLCipher-encrypted
+//! with a key derived from the user's passphrase via argon2; a small
+//! plaintext sidecar file holds the KDF salts and a verifier so a wrong
+//! passphrase is rejected before touching the database. Startup with an
+//! encrypted database waits for `unlock_database` from the passphrase
+//! prompt instead of opening the DB in setup.
+
+use argon2::Argon2;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::{self, Db};
+
+const META_FILE: &str = "encryption.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptionMeta {
+    /// Salt for deriving the SQLCipher key.
+    kdf_salt: String,
+    /// Salt for the verifier derivation (distinct from the key's).
+    verify_salt: String,
+    /// Hex of the verifier derivation; compared on unlock.
+    verifier: String,
+}
+
+fn meta_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(META_FILE)
+}
+
+pub fn is_encrypted(app_data_dir: &Path) -> bool {
+    meta_path(app_data_dir).exists()
+}
+
+fn derive(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut out = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut out)
+        .map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+fn load_meta(app_data_dir: &Path) -> Result<EncryptionMeta, String> {
+    let raw = fs::read_to_string(meta_path(app_data_dir)).map_err(|e| e.to_string())?;
+    serde_json::from_str(&raw).map_err(|e| e.to_string())
+}
+
+/// Fresh salts, verifier and derived key for a passphrase. Nothing is
+/// written until the accompanying migration/rekey succeeded.
+fn new_meta(passphrase: &str) -> Result<(EncryptionMeta, String), String> {
+    let mut kdf_salt = [0u8; 16];
+    let mut verify_salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut kdf_salt);
+    rand::thread_rng().fill_bytes(&mut verify_salt);
+    let key = derive(passphrase, &kdf_salt)?;
+    let verifier = derive(passphrase, &verify_salt)?;
+    let meta = EncryptionMeta {
+        kdf_salt: hex::encode(kdf_salt),
+        verify_salt: hex::encode(verify_salt),
+        verifier: hex::encode(verifier),
+    };
+    Ok((meta, hex::encode(key)))
+}
+
+fn store_meta(app_data_dir: &Path, meta: &EncryptionMeta) -> Result<(), String> {
+    fs::write(
+        meta_path(app_data_dir),
+        serde_json::to_string_pretty(meta).map_err(|e| e.to_string())?,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Derive the database key after checking the passphrase against the
+/// stored verifier.
+fn key_for(app_data_dir: &Path, passphrase: &str) -> Result<String, String> {
+    let meta = load_meta(app_data_dir)?;
+    let verify_salt = hex::decode(&meta.verify_salt).map_err(|e| e.to_string())?;
+    let verifier = derive(passphrase, &verify_salt)?;
+    if hex::encode(verifier) != meta.verifier {
+        return Err("incorrect passphrase".to_string());
+    }
+    let kdf_salt = hex::decode(&meta.kdf_salt).map_err(|e| e.to_string())?;
+    Ok(hex::encode(derive(passphrase, &kdf_salt)?))
+}
+
+#[tauri::command]
+pub fn is_database_encrypted(app: AppHandle) -> Result<bool, String> {
+    let dir = crate::profiles::data_dir(&app)?;
+    Ok(is_encrypted(&dir))
+}
+
+/// Open the encrypted database with the given passphrase and finish
+/// startup. Called from the passphrase prompt before any other command.
+#[tauri::command]
+pub fn unlock_database(app: AppHandle, passphrase: String) -> Result<(), String> {
+    if app.try_state::<Db>().is_some() {
+        return Err("database is already unlocked".to_string());
+    }
+    let dir = crate::profiles::data_dir(&app)?;
+    let key = key_for(&dir, &passphrase)?;
+    let db = db::init_encrypted(dir, key)?;
+    app.manage(db);
+    crate::finish_startup(&app)
+}
+
+/// Encrypt a currently plaintext database in place: the live connection
+/// exports into a SQLCipher-encrypted copy which then replaces the
+/// plaintext file (kept as cortex.db.plaintext-backup until the user
+/// deletes it).
+#[tauri::command]
+pub fn enable_encryption(app: AppHandle, db: State<Db>, passphrase: String) -> Result<(), String> {
+    let dir = crate::profiles::data_dir(&app)?;
+    if is_encrypted(&dir) {
+        return Err("database is already encrypted".to_string());
+    }
+    if passphrase.len() < 8 {
+        return Err("passphrase must be at least 8 characters".to_string());
+    }
+    let (meta, key) = new_meta(&passphrase)?;
+    db.migrate_to_encrypted(&key)?;
+    store_meta(&dir, &meta)
+}
+
+/// Re-key the encrypted database under a new passphrase.
+#[tauri::command]
+pub fn change_passphrase(
+    app: AppHandle,
+    db: State<Db>,
+    current: String,
+    new: String,
+) -> Result<(), String> {
+    let dir = crate::profiles::data_dir(&app)?;
+    key_for(&dir, &current)?;
+    if new.len() < 8 {
+        return Err("passphrase must be at least 8 characters".to_string());
+    }
+    let (meta, new_key) = new_meta(&new)?;
+    db.rekey(&new_key)?;
+    store_meta(&dir, &meta)
+}
@@ -0,0 +1,520 @@
+//! SQLite persistence layer. A single connection guarded by a mutex is
+//! shared across all commands via Tauri managed state. `Db::conn`
+//! recovers from a poisoned mutex (a panic while holding the lock no
+//! longer bricks every later command), and a background watchdog
+//! detects a wedged database layer, reinitializes the connection and
+//! reports it to the UI as a recoverable event.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppResult;
+
+pub struct Db {
+    conn: Mutex<Connection>,
+    /// Behind a mutex so `switch_profile` can repoint the managed state
+    /// at another profile's database file.
+    path: Mutex<PathBuf>,
+    /// Hex-encoded SQLCipher key when the database is encrypted.
+    key: Mutex<Option<String>>,
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS chats (
+    id          TEXT PRIMARY KEY,
+    title       TEXT NOT NULL,
+    model       TEXT NOT NULL,
+    created_at  TEXT NOT NULL,
+    updated_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS messages (
+    id          TEXT PRIMARY KEY,
+    chat_id     TEXT NOT NULL REFERENCES chats(id) ON DELETE CASCADE,
+    role        TEXT NOT NULL,
+    content     TEXT NOT NULL,
+    created_at  TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_messages_chat ON messages(chat_id);
+CREATE INDEX IF NOT EXISTS idx_messages_chat_created ON messages(chat_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_chats_updated ON chats(updated_at);
+
+CREATE TABLE IF NOT EXISTS attachments (
+    id              TEXT PRIMARY KEY,
+    chat_id         TEXT NOT NULL REFERENCES chats(id) ON DELETE CASCADE,
+    file_name       TEXT NOT NULL,
+    stored_path     TEXT NOT NULL,
+    kind            TEXT NOT NULL,
+    extracted_text  TEXT,
+    pending         INTEGER NOT NULL DEFAULT 1,
+    created_at      TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_attachments_chat ON attachments(chat_id);
+
+CREATE TABLE IF NOT EXISTS watched_folders (
+    id          TEXT PRIMARY KEY,
+    path        TEXT NOT NULL UNIQUE,
+    created_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS kb_documents (
+    id          TEXT PRIMARY KEY,
+    folder_id   TEXT NOT NULL,
+    path        TEXT NOT NULL,
+    updated_at  TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_kb_documents_path ON kb_documents(path);
+
+CREATE TABLE IF NOT EXISTS kb_chunks (
+    id           TEXT PRIMARY KEY,
+    document_id  TEXT NOT NULL REFERENCES kb_documents(id) ON DELETE CASCADE,
+    content      TEXT NOT NULL,
+    embedding    BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_kb_chunks_document ON kb_chunks(document_id);
+
+CREATE TABLE IF NOT EXISTS mcp_servers (
+    id          TEXT PRIMARY KEY,
+    name        TEXT NOT NULL,
+    transport   TEXT NOT NULL,
+    target      TEXT NOT NULL,
+    args        TEXT NOT NULL,
+    enabled     INTEGER NOT NULL DEFAULT 1,
+    created_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS model_pulls (
+    model       TEXT PRIMARY KEY,
+    status      TEXT NOT NULL,
+    total       INTEGER,
+    completed   INTEGER,
+    updated_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS model_configs (
+    model           TEXT PRIMARY KEY,
+    context_window  INTEGER NOT NULL,
+    updated_at      TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS change_journal (
+    seq          INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity       TEXT NOT NULL,
+    entity_id    TEXT NOT NULL,
+    op           TEXT NOT NULL,
+    payload      TEXT,
+    recorded_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS personas (
+    id             TEXT PRIMARY KEY,
+    name           TEXT NOT NULL,
+    description    TEXT NOT NULL,
+    system_prompt  TEXT NOT NULL,
+    model          TEXT NOT NULL,
+    options        TEXT NOT NULL,
+    template_ids   TEXT NOT NULL,
+    created_at     TEXT NOT NULL,
+    updated_at     TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS chat_triggers (
+    id          TEXT PRIMARY KEY,
+    chat_id     TEXT NOT NULL REFERENCES chats(id) ON DELETE CASCADE,
+    kind        TEXT NOT NULL,
+    target      TEXT NOT NULL,
+    enabled     INTEGER NOT NULL DEFAULT 1,
+    created_at  TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_chat_triggers_chat ON chat_triggers(chat_id);
+
+CREATE TABLE IF NOT EXISTS playground_sweeps (
+    id          TEXT PRIMARY KEY,
+    prompt      TEXT NOT NULL,
+    results     TEXT NOT NULL,
+    created_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS automations (
+    id           TEXT PRIMARY KEY,
+    name         TEXT NOT NULL,
+    prompt       TEXT NOT NULL,
+    model        TEXT NOT NULL,
+    chat_id      TEXT NOT NULL REFERENCES chats(id) ON DELETE CASCADE,
+    schedule     TEXT NOT NULL,
+    enabled      INTEGER NOT NULL DEFAULT 1,
+    last_run_at  TEXT,
+    created_at   TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS batch_jobs (
+    id          TEXT PRIMARY KEY,
+    model       TEXT NOT NULL,
+    template    TEXT NOT NULL,
+    status      TEXT NOT NULL,
+    total       INTEGER NOT NULL,
+    completed   INTEGER NOT NULL DEFAULT 0,
+    created_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS batch_items (
+    id        TEXT PRIMARY KEY,
+    job_id    TEXT NOT NULL REFERENCES batch_jobs(id) ON DELETE CASCADE,
+    position  INTEGER NOT NULL,
+    input     TEXT NOT NULL,
+    output    TEXT,
+    error     TEXT,
+    status    TEXT NOT NULL DEFAULT 'queued'
+);
+CREATE INDEX IF NOT EXISTS idx_batch_items_job ON batch_items(job_id);
+
+CREATE TABLE IF NOT EXISTS sync_config (
+    id                 INTEGER PRIMARY KEY CHECK (id = 1),
+    folder             TEXT NOT NULL,
+    device_id          TEXT NOT NULL,
+    last_exported_seq  INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS sync_peers (
+    device_id       TEXT PRIMARY KEY,
+    applied_lines   INTEGER NOT NULL DEFAULT 0,
+    last_synced_at  TEXT
+);
+
+CREATE TABLE IF NOT EXISTS message_embeddings (
+    message_id  TEXT PRIMARY KEY,
+    chat_id     TEXT NOT NULL,
+    embedding   BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_message_embeddings_chat ON message_embeddings(chat_id);
+
+CREATE TABLE IF NOT EXISTS prompt_cache (
+    key         TEXT PRIMARY KEY,
+    model       TEXT NOT NULL,
+    response    TEXT NOT NULL,
+    created_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS hooks (
+    id                TEXT PRIMARY KEY,
+    name              TEXT NOT NULL,
+    event             TEXT NOT NULL,
+    kind              TEXT NOT NULL,
+    target            TEXT NOT NULL,
+    payload_template  TEXT,
+    enabled           INTEGER NOT NULL DEFAULT 1,
+    created_at        TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS settings (
+    key         TEXT PRIMARY KEY,
+    value       TEXT NOT NULL,
+    updated_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS draft_outcomes (
+    id           TEXT PRIMARY KEY,
+    chat_id      TEXT NOT NULL,
+    message_id   TEXT NOT NULL,
+    choice       TEXT NOT NULL,
+    draft_model  TEXT NOT NULL,
+    final_model  TEXT NOT NULL,
+    created_at   TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tool_grants (
+    id          TEXT PRIMARY KEY,
+    tool        TEXT NOT NULL,
+    scope       TEXT NOT NULL,
+    chat_id     TEXT,
+    decision    TEXT NOT NULL,
+    created_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tool_audit_log (
+    id          TEXT PRIMARY KEY,
+    chat_id     TEXT,
+    tool        TEXT NOT NULL,
+    arguments   TEXT NOT NULL,
+    outcome     TEXT NOT NULL,
+    detail      TEXT,
+    created_at  TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_tool_audit_created ON tool_audit_log(created_at);
+
+CREATE TABLE IF NOT EXISTS context_refs (
+    id                  TEXT PRIMARY KEY,
+    chat_id             TEXT NOT NULL,
+    message_id          TEXT NOT NULL,
+    referenced_chat_id  TEXT NOT NULL,
+    strategy            TEXT NOT NULL,
+    span                TEXT NOT NULL,
+    created_at          TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_context_refs_chat ON context_refs(chat_id);
+
+CREATE TABLE IF NOT EXISTS context_history (
+    id               TEXT PRIMARY KEY,
+    chat_id          TEXT NOT NULL,
+    message_id       TEXT NOT NULL,
+    total_tokens     INTEGER NOT NULL,
+    pinned_tokens    INTEGER NOT NULL,
+    max_tokens       INTEGER NOT NULL,
+    message_count    INTEGER NOT NULL,
+    pruned_messages  INTEGER NOT NULL,
+    policy           TEXT NOT NULL,
+    created_at       TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_context_history_chat ON context_history(chat_id);
+
+CREATE TABLE IF NOT EXISTS message_sources (
+    id          TEXT PRIMARY KEY,
+    message_id  TEXT NOT NULL,
+    chat_id     TEXT NOT NULL,
+    kind        TEXT NOT NULL,
+    origin      TEXT NOT NULL,
+    chunk       TEXT NOT NULL,
+    score       REAL,
+    created_at  TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_message_sources_message ON message_sources(message_id);
+
+CREATE TABLE IF NOT EXISTS snapshots (
+    id          TEXT PRIMARY KEY,
+    chat_id     TEXT NOT NULL,
+    name        TEXT NOT NULL,
+    messages    TEXT NOT NULL,
+    created_at  TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_snapshots_chat ON snapshots(chat_id);
+
+CREATE TABLE IF NOT EXISTS templates (
+    id           TEXT PRIMARY KEY,
+    name         TEXT NOT NULL,
+    description  TEXT NOT NULL,
+    builtin      INTEGER NOT NULL DEFAULT 0,
+    definition   TEXT NOT NULL,
+    created_at   TEXT NOT NULL,
+    updated_at   TEXT NOT NULL
+);
+";
+
+pub(crate) fn open_connection(path: &PathBuf, key: Option<&str>) -> AppResult<Connection> {
+    let conn = Connection::open(path)?;
+    if let Some(key) = key {
+        // Must be the very first statement against an encrypted database.
+        conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key))?;
+    }
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    // WAL lets readers proceed while a writer commits, and NORMAL
+    // synchronous is durable under WAL without an fsync per commit.
+    // The busy timeout covers brief writer locks (checkpoints, the
+    // watchdog's probe) instead of surfacing SQLITE_BUSY to commands.
+    conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.busy_timeout(Duration::from_millis(5000))?;
+    conn.execute_batch(SCHEMA)?;
+    migrate(&conn);
+    Ok(conn)
+}
+
+/// Open (or create) the application database under the app data directory
+/// and run the idempotent schema.
+pub fn init(app_data_dir: PathBuf) -> AppResult<Db> {
+    fs::create_dir_all(&app_data_dir)?;
+    let path = app_data_dir.join("cortex.db");
+    let conn = open_connection(&path, None)?;
+    Ok(Db {
+        conn: Mutex::new(conn),
+        path: Mutex::new(path),
+        key: Mutex::new(None),
+    })
+}
+
+/// Open an encrypted database with a key derived from the user's
+/// passphrase (see `crypto`).
+pub fn init_encrypted(app_data_dir: PathBuf, key: String) -> AppResult<Db> {
+    fs::create_dir_all(&app_data_dir)?;
+    let path = app_data_dir.join("cortex.db");
+    let conn = open_connection(&path, Some(&key))?;
+    Ok(Db {
+        conn: Mutex::new(conn),
+        path: Mutex::new(path),
+        key: Mutex::new(Some(key)),
+    })
+}
+
+/// Column additions for databases created by older versions. `ALTER TABLE
+/// ADD COLUMN` fails when the column already exists, so errors are ignored.
+fn migrate(conn: &Connection) {
+    let alters = [
+        "ALTER TABLE chats ADD COLUMN pruning_policy TEXT NOT NULL DEFAULT 'oldest_first'",
+        "ALTER TABLE messages ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0",
+        "ALTER TABLE messages ADD COLUMN feedback TEXT",
+        "ALTER TABLE messages ADD COLUMN note TEXT",
+        "ALTER TABLE messages ADD COLUMN bookmarked INTEGER NOT NULL DEFAULT 0",
+        "ALTER TABLE messages ADD COLUMN model TEXT",
+        "ALTER TABLE chats ADD COLUMN deleted_at TEXT",
+        "ALTER TABLE messages ADD COLUMN deleted_at TEXT",
+        "ALTER TABLE chats ADD COLUMN bypass_prompt_cache INTEGER NOT NULL DEFAULT 0",
+        "ALTER TABLE chats ADD COLUMN locked_at TEXT",
+        "ALTER TABLE chats ADD COLUMN lock_hash TEXT",
+        "ALTER TABLE messages ADD COLUMN interrupted INTEGER NOT NULL DEFAULT 0",
+        "ALTER TABLE chats ADD COLUMN keep_alive TEXT",
+        "ALTER TABLE messages ADD COLUMN detected_lang TEXT",
+        "ALTER TABLE messages ADD COLUMN translated_content TEXT",
+        "ALTER TABLE chats ADD COLUMN context_cleared_at TEXT",
+        "ALTER TABLE messages ADD COLUMN raw_content TEXT",
+        "ALTER TABLE messages ADD COLUMN content_zstd BLOB",
+        "ALTER TABLE messages ADD COLUMN compressed INTEGER NOT NULL DEFAULT 0",
+    ];
+    for alter in alters {
+        let _ = conn.execute(alter, []);
+    }
+}
+
+impl Db {
+    /// Acquire the connection, recovering from a poisoned mutex: the
+    /// connection itself stays valid when a command panics mid-query.
+    pub fn conn(&self) -> MutexGuard<'_, Connection> {
+        match self.conn.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn try_conn(&self) -> Option<MutexGuard<'_, Connection>> {
+        match self.conn.try_lock() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    /// Swap in a freshly opened connection, e.g. after the watchdog saw
+    /// health-check queries failing.
+    pub fn reinitialize(&self) -> AppResult<()> {
+        let path = self.path.lock().unwrap().clone();
+        let key = self.key.lock().unwrap().clone();
+        let fresh = open_connection(&path, key.as_deref())?;
+        *self.conn() = fresh;
+        Ok(())
+    }
+
+    /// Repoint this state at another profile's database. The new
+    /// profile's database must be plaintext — switching to an encrypted
+    /// profile goes through `unlock_database` after a restart.
+    pub fn switch_to(&self, data_dir: PathBuf) -> AppResult<()> {
+        fs::create_dir_all(&data_dir)?;
+        let path = data_dir.join("cortex.db");
+        let conn = open_connection(&path, None)?;
+        *self.conn() = conn;
+        *self.path.lock().unwrap() = path;
+        *self.key.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// Export the plaintext database into a SQLCipher-encrypted copy and
+    /// swap it in. The plaintext file is kept as a `.plaintext-backup`
+    /// next to the database until the user removes it.
+    pub fn migrate_to_encrypted(&self, key: &str) -> AppResult<()> {
+        let path = self.path.lock().unwrap().clone();
+        let encrypted_path = path.with_extension("db.encrypted");
+        {
+            let conn = self.conn();
+            conn.execute_batch(&format!(
+                "ATTACH DATABASE '{}' AS encrypted KEY \"x'{}'\";
+                 SELECT sqlcipher_export('encrypted');
+                 DETACH DATABASE encrypted;",
+                encrypted_path.to_string_lossy(),
+                key
+            ))?;
+        }
+        // Release the file handle before swapping files.
+        *self.conn() = Connection::open_in_memory()?;
+        let backup = path.with_extension("db.plaintext-backup");
+        fs::rename(&path, &backup)?;
+        fs::rename(&encrypted_path, &path)?;
+        *self.key.lock().unwrap() = Some(key.to_string());
+        self.reinitialize()
+    }
+
+    /// Re-key the encrypted database under a new derived key.
+    pub fn rekey(&self, new_key: &str) -> AppResult<()> {
+        {
+            let conn = self.conn();
+            conn.execute_batch(&format!("PRAGMA rekey = \"x'{}'\";", new_key))?;
+        }
+        *self.key.lock().unwrap() = Some(new_key.to_string());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DbHealthEvent {
+    pub status: String,
+    pub detail: String,
+}
+
+fn emit_health(app: &AppHandle, status: &str, detail: String) {
+    let _ = app.emit(
+        "db-health",
+        &DbHealthEvent {
+            status: status.to_string(),
+            detail,
+        },
+    );
+}
+
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(10);
+const WATCHDOG_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Periodically health-check the database layer. A lock that cannot be
+/// acquired for several seconds means some command is wedged holding it;
+/// a failing trivial query means the connection itself has gone bad and
+/// is swapped for a fresh one. Either way the UI gets a recoverable
+/// `db-health` event instead of every command silently failing.
+pub fn start_watchdog(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(WATCHDOG_INTERVAL);
+        let db = app.state::<Db>();
+        let deadline = Instant::now() + WATCHDOG_ACQUIRE_TIMEOUT;
+        let guard = loop {
+            match db.try_conn() {
+                Some(guard) => break Some(guard),
+                None if Instant::now() >= deadline => break None,
+                None => std::thread::sleep(Duration::from_millis(100)),
+            }
+        };
+        match guard {
+            None => emit_health(
+                &app,
+                "wedged",
+                "database lock not released within 5s".to_string(),
+            ),
+            Some(guard) => {
+                let healthy = guard.query_row("SELECT 1", [], |_| Ok(())).is_ok();
+                drop(guard);
+                if !healthy {
+                    match db.reinitialize() {
+                        Ok(()) => emit_health(
+                            &app,
+                            "recovered",
+                            "connection failed health check and was reopened".to_string(),
+                        ),
+                        Err(e) => emit_health(&app, "error", e.to_string()),
+                    }
+                }
+            }
+        }
+    });
+}
+
+pub fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
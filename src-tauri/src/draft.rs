@@ -0,0 +1,205 @@
+//! Cascaded draft mode for slow hardware: a small fast model streams an
+//! immediate draft on the `draft-token` channel while the selected
+//! large model generates the real answer in parallel on the usual
+//! `chat-token` channel. The draft is never persisted unless the user
+//! keeps it (`keep_draft`, which also stops the large run); either way
+//! the outcome — which answer the user kept — is recorded in the
+//! draft_outcomes table.
+
+use futures_util::StreamExt;
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+use crate::chat::{self, ActiveGenerations, Message};
+use crate::db::{self, Db};
+use crate::error::{AppError, AppResult};
+use crate::ndjson::NdjsonDecoder;
+use crate::ollama::OLLAMA_BASE_URL;
+
+struct DraftRun {
+    draft_model: String,
+    final_model: String,
+    text: String,
+    done: bool,
+    kept: bool,
+}
+
+/// In-flight and just-finished drafts, keyed by chat id.
+#[derive(Default)]
+pub struct DraftState(Mutex<HashMap<String, DraftRun>>);
+
+#[derive(Debug, Clone, Serialize)]
+struct DraftToken {
+    chat_id: String,
+    token: String,
+    done: bool,
+}
+
+/// Stream the draft silently into `DraftState`, emitting `draft-token`
+/// events. Failures are swallowed: the draft is best-effort and the
+/// real generation is already running.
+async fn stream_draft(app: AppHandle, chat_id: String, payload: Value) {
+    let client = reqwest::Client::new();
+    let resp = match client
+        .post(format!("{}/api/chat", OLLAMA_BASE_URL))
+        .json(&payload)
+        .send()
+        .await
+    {
+        Ok(resp) => resp,
+        Err(e) => {
+            tracing::debug!("draft stream failed to start: {}", e);
+            return;
+        }
+    };
+    let mut stream = resp.bytes_stream();
+    let mut decoder = NdjsonDecoder::new();
+    while let Some(chunk) = stream.next().await {
+        let Ok(chunk) = chunk else { return };
+        for value in decoder.push(&chunk) {
+            let token = value
+                .pointer("/message/content")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let done = value.get("done").and_then(Value::as_bool).unwrap_or(false);
+            {
+                let state = app.state::<DraftState>();
+                let mut runs = state.0.lock().unwrap();
+                let Some(run) = runs.get_mut(&chat_id) else {
+                    // The run was resolved (final answer landed first);
+                    // stop streaming a draft nobody can keep.
+                    return;
+                };
+                run.text.push_str(token);
+                run.done = done;
+            }
+            let _ = app.emit(
+                "draft-token",
+                &DraftToken {
+                    chat_id: chat_id.clone(),
+                    token: token.to_string(),
+                    done,
+                },
+            );
+        }
+    }
+}
+
+fn record_outcome(
+    db: &Db,
+    chat_id: &str,
+    message_id: &str,
+    choice: &str,
+    draft_model: &str,
+    final_model: &str,
+) {
+    let conn = db.conn();
+    let _ = conn.execute(
+        "INSERT INTO draft_outcomes (id, chat_id, message_id, choice, draft_model, final_model, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            Uuid::new_v4().to_string(),
+            chat_id,
+            message_id,
+            choice,
+            draft_model,
+            final_model,
+            db::now()
+        ],
+    );
+}
+
+/// Cascaded generation: stream a draft from `draft_model` while `model`
+/// produces the final answer. Returns the final message; if the user
+/// kept the draft mid-run (`keep_draft`), the large run comes back
+/// interrupted and the draft message already holds the answer.
+#[tauri::command]
+pub async fn chat_with_draft(
+    app: AppHandle,
+    db: State<'_, Db>,
+    chat_id: String,
+    model: String,
+    draft_model: String,
+    content: String,
+) -> AppResult<Message> {
+    if draft_model == model {
+        return Err(AppError::InvalidInput(
+            "draft model must differ from the final model".to_string(),
+        ));
+    }
+    {
+        let state = app.state::<DraftState>();
+        state.0.lock().unwrap().insert(
+            chat_id.clone(),
+            DraftRun {
+                draft_model: draft_model.clone(),
+                final_model: model.clone(),
+                text: String::new(),
+                done: false,
+                kept: false,
+            },
+        );
+    }
+    // The draft sees the same history but must not consume pending
+    // attachments out from under the real run.
+    let context = chat::build_context(&db, &chat_id, &draft_model, &content, false).await?;
+    let payload = chat::chat_payload(&context, &draft_model, &None);
+    tauri::async_runtime::spawn(stream_draft(app.clone(), chat_id.clone(), payload));
+
+    let result = chat::run_generation(&app, &db, &chat_id, &model, &content, None).await;
+
+    let kept_draft = {
+        let state = app.state::<DraftState>();
+        let run = state.0.lock().unwrap().remove(&chat_id);
+        run.map(|r| r.kept).unwrap_or(false)
+    };
+    let message = result?;
+    if !kept_draft {
+        record_outcome(&db, &chat_id, &message.id, "final", &draft_model, &model);
+    }
+    Ok(message)
+}
+
+/// Keep the draft: stop the in-flight large generation, persist the
+/// draft text as the assistant message and record the choice.
+#[tauri::command]
+pub fn keep_draft(app: AppHandle, db: State<Db>, chat_id: String) -> AppResult<Message> {
+    let (text, draft_model, final_model) = {
+        let state = app.state::<DraftState>();
+        let mut runs = state.0.lock().unwrap();
+        let run = runs.get_mut(&chat_id).ok_or_else(|| {
+            AppError::NotFound(format!("no draft in flight for chat {}", chat_id))
+        })?;
+        if run.text.is_empty() {
+            return Err(AppError::InvalidInput(
+                "the draft has not produced any text yet".to_string(),
+            ));
+        }
+        run.kept = true;
+        (
+            run.text.clone(),
+            run.draft_model.clone(),
+            run.final_model.clone(),
+        )
+    };
+    // Stop the large run; its partial checkpoint gets replaced below by
+    // the draft as the real answer.
+    if let Some(flag) = app
+        .state::<ActiveGenerations>()
+        .0
+        .lock()
+        .unwrap()
+        .get(&chat_id)
+    {
+        flag.store(true, Ordering::Relaxed);
+    }
+    let message = chat::insert_message(&db, &chat_id, "assistant", &text, Some(&draft_model))?;
+    record_outcome(&db, &chat_id, &message.id, "draft", &draft_model, &final_model);
+    Ok(message)
+}
@@ -0,0 +1,113 @@
+//! Crate-wide command error type. Serializes with a stable `code` the
+//! frontend can switch on instead of string-matching messages:
+//! `{ "code": "ollama_http", "detail": { "status": 500, ... } }`.
+//! Modules still on `Result<_, String>` interoperate in both directions
+//! through the `From` impls at the bottom.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "code", content = "detail", rename_all = "snake_case")]
+pub enum AppError {
+    /// SQLite layer failure.
+    Database(String),
+    /// Ollama could not be reached at all (not running, refused).
+    OllamaUnavailable(String),
+    /// Ollama answered with an error status.
+    OllamaHttp { status: u16, message: String },
+    /// The referenced chat/message/model/etc. does not exist.
+    NotFound(String),
+    Serialization(String),
+    /// The operation was stopped by the user.
+    Cancelled(String),
+    /// A stream went silent past the inactivity timeout.
+    Timeout { seconds: u64, message: String },
+    /// The caller passed something unusable.
+    InvalidInput(String),
+    Io(String),
+    /// Anything without a more specific code.
+    Internal(String),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Database(detail) => write!(f, "database error: {}", detail),
+            AppError::OllamaUnavailable(detail) => write!(f, "Ollama unavailable: {}", detail),
+            AppError::OllamaHttp { status, message } => {
+                write!(f, "Ollama returned {}: {}", status, message)
+            }
+            AppError::NotFound(detail) => write!(f, "not found: {}", detail),
+            AppError::Serialization(detail) => write!(f, "serialization error: {}", detail),
+            AppError::Cancelled(detail) => write!(f, "cancelled: {}", detail),
+            AppError::Timeout { seconds, message } => {
+                write!(f, "timed out after {}s: {}", seconds, message)
+            }
+            AppError::InvalidInput(detail) => write!(f, "invalid input: {}", detail),
+            AppError::Io(detail) => write!(f, "io error: {}", detail),
+            AppError::Internal(detail) => write!(f, "{}", detail),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound("no matching row".to_string())
+            }
+            other => AppError::Database(other.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        if let Some(status) = e.status() {
+            AppError::OllamaHttp {
+                status: status.as_u16(),
+                message: e.to_string(),
+            }
+        } else if e.is_connect() || e.is_timeout() {
+            AppError::OllamaUnavailable(e.to_string())
+        } else {
+            AppError::Internal(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Serialization(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+/// Bridge from modules still returning `String` errors.
+impl From<String> for AppError {
+    fn from(e: String) -> Self {
+        AppError::Internal(e)
+    }
+}
+
+/// Bridge the other way, for `String`-based callers of converted code.
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.to_string()
+    }
+}
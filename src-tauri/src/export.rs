@@ -0,0 +1,383 @@
+//! Chat transcript export with message selection and redaction. A
+//! transcript can be narrowed to an explicit message selection or an
+//! id range, and redaction rules (built-in presets for emails and API
+//! keys, plus custom patterns for things like names) run server-side
+//! before anything is serialized, so secrets from earlier in the
+//! conversation never reach the exported document. The same format
+//! imports back via `import_chat`, which merges into an existing chat
+//! with the same original id instead of duplicating it.
+
+use regex::Regex;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::chat;
+use crate::compression;
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Patterns for the named redaction presets. Names and other free-form
+/// identifiers have no general pattern; callers pass those as custom
+/// `patterns`.
+fn preset_pattern(name: &str) -> Option<&'static str> {
+    match name {
+        "email" => Some(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+        "api_key" => Some(r"\b(?:sk|pk|ghp|gho|glpat|xox[abp])[-_][A-Za-z0-9_-]{16,}\b"),
+        _ => None,
+    }
+}
+
+/// Compile presets plus custom patterns into one rule set.
+fn compile_rules(presets: &[String], patterns: &[String]) -> AppResult<Vec<Regex>> {
+    let mut rules = Vec::new();
+    for preset in presets {
+        let pattern = preset_pattern(preset).ok_or_else(|| {
+            AppError::InvalidInput(format!("unknown redaction preset: {}", preset))
+        })?;
+        rules.push(Regex::new(pattern).expect("preset patterns are valid"));
+    }
+    for pattern in patterns {
+        rules.push(
+            Regex::new(pattern)
+                .map_err(|e| AppError::InvalidInput(format!("bad redaction pattern: {}", e)))?,
+        );
+    }
+    Ok(rules)
+}
+
+fn redact(text: &str, rules: &[Regex]) -> String {
+    let mut result = text.to_string();
+    for rule in rules {
+        result = rule.replace_all(&result, REDACTED).into_owned();
+    }
+    result
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatExport {
+    pub chat: chat::Chat,
+    pub messages: Vec<chat::Message>,
+    /// Lock record when the chat is finalized (see `lock_chat`).
+    pub lock: Option<chat::ChatLock>,
+    /// External context injected via `@chat:` references, so the export
+    /// shows what the answers were grounded in. Absent in old exports.
+    #[serde(default)]
+    pub context_refs: Vec<crate::chatrefs::ContextRef>,
+    pub redacted: bool,
+    pub exported_at: String,
+}
+
+/// Export a chat transcript. `selection` limits it to those message ids;
+/// otherwise `from_message_id`/`to_message_id` bound an inclusive range
+/// in conversation order. Redaction rules apply to message content
+/// before serialization.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn export_chat(
+    db: State<Db>,
+    chat_id: String,
+    selection: Option<Vec<String>>,
+    from_message_id: Option<String>,
+    to_message_id: Option<String>,
+    presets: Vec<String>,
+    patterns: Vec<String>,
+) -> AppResult<ChatExport> {
+    let rules = compile_rules(&presets, &patterns)?;
+    let conn = db.conn();
+    let chat = conn.query_row(
+        "SELECT id, title, model, created_at, updated_at FROM chats
+         WHERE id = ?1 AND deleted_at IS NULL",
+        params![chat_id],
+        |row| {
+            Ok(chat::Chat {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                model: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        },
+    )?;
+    let mut stmt = conn.prepare(
+        "SELECT id, chat_id, role, content, model, created_at, compressed, content_zstd
+         FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+    )?;
+    let all: Vec<chat::Message> = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(chat::Message {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                role: row.get(2)?,
+                content: compression::stored(row.get(3)?, row.get::<_, i64>(6)? != 0, row.get(7)?),
+                model: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(stmt);
+    drop(conn);
+
+    let mut in_range = from_message_id.is_none();
+    let mut messages = Vec::new();
+    for mut message in all {
+        if Some(&message.id) == from_message_id.as_ref() {
+            in_range = true;
+        }
+        let selected = match &selection {
+            Some(ids) => ids.contains(&message.id),
+            None => in_range,
+        };
+        let stop_after = Some(&message.id) == to_message_id.as_ref();
+        if selected {
+            message.content = redact(&message.content, &rules);
+            messages.push(message);
+        }
+        if stop_after {
+            break;
+        }
+    }
+
+    let lock = chat::chat_lock(&db, &chat_id)?;
+    // Only refs attached to exported messages ride along.
+    let context_refs = crate::chatrefs::refs_for_chat(&db, &chat_id)?
+        .into_iter()
+        .filter(|r| messages.iter().any(|m| m.id == r.message_id))
+        .collect();
+    Ok(ChatExport {
+        chat,
+        messages,
+        lock,
+        context_refs,
+        redacted: !rules.is_empty(),
+        exported_at: crate::db::now(),
+    })
+}
+
+/// Message-level comparison of two message lists, keyed by message id:
+/// ids only on one side, and ids present on both whose content differs.
+pub fn diff_messages(
+    a: &[chat::Message],
+    b: &[chat::Message],
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let ids_a: std::collections::HashMap<&str, &chat::Message> =
+        a.iter().map(|m| (m.id.as_str(), m)).collect();
+    let ids_b: std::collections::HashMap<&str, &chat::Message> =
+        b.iter().map(|m| (m.id.as_str(), m)).collect();
+    let only_a = a
+        .iter()
+        .filter(|m| !ids_b.contains_key(m.id.as_str()))
+        .map(|m| m.id.clone())
+        .collect();
+    let only_b = b
+        .iter()
+        .filter(|m| !ids_a.contains_key(m.id.as_str()))
+        .map(|m| m.id.clone())
+        .collect();
+    let diverged = a
+        .iter()
+        .filter(|m| {
+            ids_b
+                .get(m.id.as_str())
+                .is_some_and(|other| other.content != m.content)
+        })
+        .map(|m| m.id.clone())
+        .collect();
+    (only_a, only_b, diverged)
+}
+
+fn chat_messages(db: &Db, chat_id: &str) -> AppResult<Vec<chat::Message>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, chat_id, role, content, model, created_at, compressed, content_zstd
+         FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+    )?;
+    let messages = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(chat::Message {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                role: row.get(2)?,
+                content: compression::stored(row.get(3)?, row.get::<_, i64>(6)? != 0, row.get(7)?),
+                model: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(messages)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatDiff {
+    pub chat_a: String,
+    pub chat_b: String,
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    /// Ids present in both chats with different content.
+    pub diverged: Vec<String>,
+    pub identical: bool,
+}
+
+/// Structured comparison of two local chats (typically an original and
+/// a fork, or a chat and its re-import).
+#[tauri::command]
+pub fn diff_chats(db: State<Db>, chat_a: String, chat_b: String) -> AppResult<ChatDiff> {
+    let messages_a = chat_messages(&db, &chat_a)?;
+    let messages_b = chat_messages(&db, &chat_b)?;
+    let (only_in_a, only_in_b, diverged) = diff_messages(&messages_a, &messages_b);
+    let identical = only_in_a.is_empty() && only_in_b.is_empty() && diverged.is_empty();
+    Ok(ChatDiff {
+        chat_a,
+        chat_b,
+        only_in_a,
+        only_in_b,
+        diverged,
+        identical,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportOutcome {
+    pub chat_id: String,
+    /// False when the chat already existed and was merged into.
+    pub created: bool,
+    pub added: usize,
+    pub skipped: usize,
+}
+
+/// Import a `ChatExport`. A chat with the same original id merges
+/// instead of duplicating: messages already present locally are
+/// skipped, missing ones are appended (original ids and timestamps
+/// kept, so repeated imports stay idempotent). Unknown chats are
+/// recreated as-is.
+#[tauri::command]
+pub fn import_chat(db: State<Db>, export: ChatExport) -> AppResult<ImportOutcome> {
+    let exists = {
+        let conn = db.conn();
+        conn.query_row(
+            "SELECT COUNT(*) FROM chats WHERE id = ?1",
+            params![export.chat.id],
+            |row| row.get::<_, i64>(0),
+        )? > 0
+    };
+    let (added, skipped) = if exists {
+        chat::ensure_unlocked(&db, &export.chat.id)?;
+        let local = chat_messages(&db, &export.chat.id)?;
+        let (_, missing, _) = diff_messages(&local, &export.messages);
+        // One transaction, so a failed import never leaves a
+        // half-merged chat behind.
+        let conn = db.conn();
+        let tx = conn.unchecked_transaction()?;
+        let mut added = 0;
+        for message in export
+            .messages
+            .iter()
+            .filter(|m| missing.contains(&m.id))
+        {
+            added += tx.execute(
+                "INSERT OR IGNORE INTO messages (id, chat_id, role, content, model, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    message.id,
+                    export.chat.id,
+                    message.role,
+                    message.content,
+                    message.model,
+                    message.created_at
+                ],
+            )?;
+        }
+        tx.commit()?;
+        (added, export.messages.len() - added)
+    } else {
+        let conn = db.conn();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO chats (id, title, model, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                export.chat.id,
+                export.chat.title,
+                export.chat.model,
+                export.chat.created_at,
+                export.chat.updated_at
+            ],
+        )?;
+        for message in &export.messages {
+            tx.execute(
+                "INSERT INTO messages (id, chat_id, role, content, model, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    message.id,
+                    export.chat.id,
+                    message.role,
+                    message.content,
+                    message.model,
+                    message.created_at
+                ],
+            )?;
+        }
+        tx.commit()?;
+        (export.messages.len(), 0)
+    };
+    Ok(ImportOutcome {
+        chat_id: export.chat.id,
+        created: !exists,
+        added,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile_rules, diff_messages, redact};
+    use crate::chat::Message;
+
+    fn message(id: &str, content: &str) -> Message {
+        Message {
+            id: id.to_string(),
+            chat_id: "c".to_string(),
+            role: "user".to_string(),
+            content: content.to_string(),
+            model: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_finds_missing_and_diverged_messages() {
+        let a = vec![message("1", "hi"), message("2", "old"), message("3", "x")];
+        let b = vec![message("1", "hi"), message("2", "new"), message("4", "y")];
+        let (only_a, only_b, diverged) = diff_messages(&a, &b);
+        assert_eq!(only_a, vec!["3"]);
+        assert_eq!(only_b, vec!["4"]);
+        assert_eq!(diverged, vec!["2"]);
+    }
+
+    #[test]
+    fn email_preset_redacts_addresses() {
+        let rules = compile_rules(&["email".to_string()], &[]).unwrap();
+        assert_eq!(
+            redact("mail me at sam@example.com please", &rules),
+            "mail me at [REDACTED] please"
+        );
+    }
+
+    #[test]
+    fn api_key_preset_redacts_tokens_but_not_prose() {
+        let rules = compile_rules(&["api_key".to_string()], &[]).unwrap();
+        let text = "use sk-abcdefghijklmnop1234 as the key";
+        assert_eq!(redact(text, &rules), "use [REDACTED] as the key");
+        assert_eq!(redact("skip this", &rules), "skip this");
+    }
+
+    #[test]
+    fn custom_patterns_and_unknown_presets() {
+        let rules = compile_rules(&[], &[r"\bAlice\b".to_string()]).unwrap();
+        assert_eq!(redact("Alice met Alicia", &rules), "[REDACTED] met Alicia");
+        assert!(compile_rules(&["phone".to_string()], &[]).is_err());
+    }
+}
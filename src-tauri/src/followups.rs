@@ -0,0 +1,87 @@
+//! Follow-up question suggestions after an assistant answer, with an
+//! opt-in speculative mode that pre-generates the answer to the top
+//! suggestion in the background (see `chat::prefetch_answer`) so
+//! clicking it feels instant.
+
+use rusqlite::params;
+use tauri::{AppHandle, Manager, State};
+
+use crate::compression;
+use crate::db::Db;
+use crate::error::AppResult;
+use crate::research;
+use crate::settings;
+use crate::web;
+
+const FOLLOW_UP_COUNT: usize = 3;
+/// Settings key for the opt-in prefetch mode; "true" enables it.
+pub const PREFETCH_KEY: &str = "speculative_prefetch";
+
+/// The last user/assistant exchange, oldest first.
+fn last_exchange(db: &Db, chat_id: &str) -> AppResult<Vec<(String, String)>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT role, content, compressed, content_zstd FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL AND role IN ('user', 'assistant')
+         ORDER BY created_at DESC LIMIT 2",
+    )?;
+    let mut rows = stmt
+        .query_map(params![chat_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                compression::stored(row.get(1)?, row.get::<_, i64>(2)? != 0, row.get(3)?),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.reverse();
+    Ok(rows)
+}
+
+/// Suggest likely follow-up questions for the current chat. When the
+/// speculative prefetch setting is on, the top suggestion's answer is
+/// generated in the background and parked in the prompt cache.
+#[tauri::command]
+pub async fn generate_follow_ups(
+    app: AppHandle,
+    db: State<'_, Db>,
+    chat_id: String,
+    model: String,
+) -> AppResult<Vec<String>> {
+    let exchange = last_exchange(&db, &chat_id)?;
+    if exchange.is_empty() {
+        return Ok(Vec::new());
+    }
+    let transcript = exchange
+        .iter()
+        .map(|(role, content)| format!("{}: {}", role, content))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let response = web::generate(
+        &model,
+        &format!(
+            "Given this exchange, suggest {} short follow-up questions the user \
+             is likely to ask next. Reply with one question per line and nothing \
+             else.\n\n{}",
+            FOLLOW_UP_COUNT, transcript
+        ),
+    )
+    .await?;
+    let mut suggestions = research::parse_queries(&response);
+    suggestions.truncate(FOLLOW_UP_COUNT);
+
+    let prefetch = settings::get(&db, PREFETCH_KEY).as_deref() == Some("true");
+    if prefetch {
+        if let Some(top) = suggestions.first().cloned() {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let db = app.state::<Db>();
+                if let Err(e) =
+                    crate::chat::prefetch_answer(&app, &db, &chat_id, &model, &top).await
+                {
+                    tracing::debug!("speculative prefetch failed: {}", e);
+                }
+            });
+        }
+    }
+    Ok(suggestions)
+}
@@ -0,0 +1,241 @@
+//! App-wide automation hooks: user-defined webhooks or local scripts
+//! fired on lifecycle events (`chat-created`, `chat-complete`,
+//! `document-ingested`), with optional payload templates. Unlike
+//! `triggers`, which bind to a single chat, hooks observe the whole app
+//! — the way to pipe every finished generation into external tools.
+
+use regex::Regex;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::error::{AppError, AppResult};
+
+const EVENTS: [&str; 3] = ["chat-created", "chat-complete", "document-ingested"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hook {
+    pub id: String,
+    pub name: String,
+    /// One of `EVENTS`.
+    pub event: String,
+    /// `webhook` (target is a URL POSTed to) or `script` (target is an
+    /// executable fed the body on stdin).
+    pub kind: String,
+    pub target: String,
+    /// Optional body template; `{{field}}` and `{{nested.field}}` are
+    /// substituted from the event payload. Without one the raw payload
+    /// JSON is sent.
+    pub payload_template: Option<String>,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HookOutcome {
+    pub hook_id: String,
+    pub event: String,
+    pub error: Option<String>,
+}
+
+/// Substitute `{{field}}` placeholders with values from `payload`.
+/// Dotted names traverse nested objects; unknown fields render empty.
+pub fn render_template(template: &str, payload: &Value) -> String {
+    let placeholder = Regex::new(r"\{\{\s*([A-Za-z0-9_.]+)\s*\}\}").expect("placeholder regex");
+    placeholder
+        .replace_all(template, |caps: &regex::Captures| {
+            let pointer = format!("/{}", caps[1].replace('.', "/"));
+            match payload.pointer(&pointer) {
+                Some(Value::String(s)) => s.clone(),
+                Some(value) => value.to_string(),
+                None => String::new(),
+            }
+        })
+        .into_owned()
+}
+
+#[tauri::command]
+pub fn add_hook(
+    db: State<Db>,
+    name: String,
+    event: String,
+    kind: String,
+    target: String,
+    payload_template: Option<String>,
+) -> AppResult<Hook> {
+    if !EVENTS.contains(&event.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "unknown hook event: {} (expected one of {})",
+            event,
+            EVENTS.join(", ")
+        )));
+    }
+    if !matches!(kind.as_str(), "webhook" | "script") {
+        return Err(AppError::InvalidInput(format!("unknown hook kind: {}", kind)));
+    }
+    let hook = Hook {
+        id: Uuid::new_v4().to_string(),
+        name,
+        event,
+        kind,
+        target,
+        payload_template,
+        enabled: true,
+        created_at: db::now(),
+    };
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO hooks (id, name, event, kind, target, payload_template, enabled, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7)",
+        params![
+            hook.id,
+            hook.name,
+            hook.event,
+            hook.kind,
+            hook.target,
+            hook.payload_template,
+            hook.created_at
+        ],
+    )?;
+    Ok(hook)
+}
+
+#[tauri::command]
+pub fn remove_hook(db: State<Db>, hook_id: String) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute("DELETE FROM hooks WHERE id = ?1", params![hook_id])?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_hook_enabled(db: State<Db>, hook_id: String, enabled: bool) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE hooks SET enabled = ?1 WHERE id = ?2",
+        params![enabled as i64, hook_id],
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_hooks(db: State<Db>) -> AppResult<Vec<Hook>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, name, event, kind, target, payload_template, enabled, created_at
+         FROM hooks ORDER BY created_at ASC",
+    )?;
+    let hooks = stmt
+        .query_map([], |row| {
+            Ok(Hook {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                event: row.get(2)?,
+                kind: row.get(3)?,
+                target: row.get(4)?,
+                payload_template: row.get(5)?,
+                enabled: row.get::<_, i64>(6)? != 0,
+                created_at: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(hooks)
+}
+
+async fn dispatch(hook: &Hook, body: &str) -> Result<(), String> {
+    match hook.kind.as_str() {
+        "webhook" => {
+            let client = reqwest::Client::new();
+            let resp = client
+                .post(&hook.target)
+                .header("content-type", "application/json")
+                .body(body.to_string())
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !resp.status().is_success() {
+                return Err(format!("webhook returned {}", resp.status()));
+            }
+            Ok(())
+        }
+        "script" => {
+            let mut child = Command::new(&hook.target)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| e.to_string())?;
+            if let Some(stdin) = child.stdin.as_mut() {
+                stdin.write_all(body.as_bytes()).map_err(|e| e.to_string())?;
+            }
+            let status = child.wait().map_err(|e| e.to_string())?;
+            if !status.success() {
+                return Err(format!("script exited with {}", status));
+            }
+            Ok(())
+        }
+        other => Err(format!("unknown hook kind: {}", other)),
+    }
+}
+
+/// Fire all enabled hooks for `event` in the background. Outcomes
+/// (including failures) are emitted as `hook-fired` events rather than
+/// failing the operation that triggered them.
+pub(crate) fn fire(app: &AppHandle, event: &str, payload: Value) {
+    let app = app.clone();
+    let event = event.to_string();
+    tauri::async_runtime::spawn(async move {
+        let hooks = {
+            let db = app.state::<Db>();
+            match get_hooks(db) {
+                Ok(hooks) => hooks,
+                Err(_) => return,
+            }
+        };
+        for hook in hooks
+            .into_iter()
+            .filter(|h| h.enabled && h.event == event)
+        {
+            let body = match &hook.payload_template {
+                Some(template) => render_template(template, &payload),
+                None => payload.to_string(),
+            };
+            let outcome = HookOutcome {
+                hook_id: hook.id.clone(),
+                event: event.clone(),
+                error: dispatch(&hook, &body).await.err(),
+            };
+            let _ = app.emit("hook-fired", &outcome);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_template;
+
+    #[test]
+    fn placeholders_substitute_from_payload() {
+        let payload = serde_json::json!({
+            "chat_id": "c1",
+            "message": { "content": "hello" },
+            "count": 3,
+        });
+        assert_eq!(
+            render_template("{{chat_id}}: {{ message.content }} ({{count}})", &payload),
+            "c1: hello (3)"
+        );
+    }
+
+    #[test]
+    fn unknown_fields_render_empty() {
+        assert_eq!(
+            render_template("x{{missing}}y", &serde_json::json!({})),
+            "xy"
+        );
+    }
+}
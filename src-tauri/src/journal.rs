@@ -0,0 +1,84 @@
+//! Row-level change journal. Every chat/message mutation appends an
+//! entry with a monotonically increasing sequence number (the logical
+//! clock), so sync clients and external mirrors can ask "what changed
+//! since cursor N" instead of diffing full exports.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::db::{self, Db};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Op {
+    Create,
+    Update,
+    Delete,
+}
+
+impl Op {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Op::Create => "create",
+            Op::Update => "update",
+            Op::Delete => "delete",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEntry {
+    pub seq: i64,
+    pub entity: String,
+    pub entity_id: String,
+    pub op: String,
+    /// JSON snapshot of the row after the change; `None` for deletes.
+    pub payload: Option<String>,
+    pub recorded_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeBatch {
+    pub changes: Vec<ChangeEntry>,
+    /// Pass this back as the next `cursor`.
+    pub cursor: i64,
+}
+
+/// Append a journal entry. Takes the already-held connection so callers
+/// journal in the same critical section as the mutation itself.
+pub fn record(conn: &Connection, entity: &str, entity_id: &str, op: Op, payload: Option<String>) {
+    let _ = conn.execute(
+        "INSERT INTO change_journal (entity, entity_id, op, payload, recorded_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![entity, entity_id, op.as_str(), payload, db::now()],
+    );
+}
+
+/// All changes with `seq > cursor`, oldest first. A cursor of 0 replays
+/// the full journal.
+#[tauri::command]
+pub fn get_changes_since(db: State<Db>, cursor: i64) -> Result<ChangeBatch, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT seq, entity, entity_id, op, payload, recorded_at
+             FROM change_journal WHERE seq > ?1 ORDER BY seq ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let changes = stmt
+        .query_map(params![cursor], |row| {
+            Ok(ChangeEntry {
+                seq: row.get(0)?,
+                entity: row.get(1)?,
+                entity_id: row.get(2)?,
+                op: row.get(3)?,
+                payload: row.get(4)?,
+                recorded_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let cursor = changes.last().map(|c| c.seq).unwrap_or(cursor);
+    Ok(ChangeBatch { changes, cursor })
+}
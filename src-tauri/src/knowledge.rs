@@ -0,0 +1,287 @@
+//! Knowledge base: markdown documents chunked, embedded via Ollama and
+//! stored in SQLite for cosine-similarity retrieval.
+
+use rusqlite::params;
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+use crate::compression;
+use crate::db::{self, Db};
+use crate::ollama::OLLAMA_BASE_URL;
+
+pub const EMBED_MODEL: &str = "nomic-embed-text";
+/// Target chunk size in characters; chunks are split on blank lines.
+const CHUNK_SIZE: usize = 1200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeHit {
+    pub document_path: String,
+    pub content: String,
+    pub score: f32,
+}
+
+/// Split markdown into chunks of roughly `CHUNK_SIZE` characters along
+/// paragraph boundaries, so headings stay attached to their body text.
+pub fn chunk_markdown(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n") {
+        if !current.is_empty() && current.len() + paragraph.len() > CHUNK_SIZE {
+            chunks.push(current.trim().to_string());
+            current = String::new();
+        }
+        current.push_str(paragraph);
+        current.push_str("\n\n");
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current.trim().to_string());
+    }
+    chunks
+}
+
+pub(crate) async fn embed(text: &str) -> Result<Vec<f32>, String> {
+    let client = reqwest::Client::new();
+    let resp: serde_json::Value = client
+        .post(format!("{}/api/embeddings", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({ "model": EMBED_MODEL, "prompt": text }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    let embedding = resp
+        .get("embedding")
+        .and_then(|v| v.as_array())
+        .ok_or("embedding missing from Ollama response")?
+        .iter()
+        .filter_map(|v| v.as_f64().map(|f| f as f32))
+        .collect();
+    Ok(embedding)
+}
+
+fn embedding_to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+pub(crate) fn blob_to_embedding(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Re-chunk and re-embed a single document, replacing any previous chunks
+/// for the same path.
+pub async fn ingest_file(db: &Db, folder_id: &str, path: &Path) -> Result<usize, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ingest_text(db, folder_id, &path.to_string_lossy(), &text).await
+}
+
+/// Chunk and embed arbitrary text under a source identifier (a file path
+/// or a virtual source like `youtube:<id>`), replacing previous chunks
+/// for the same source.
+pub async fn ingest_text(
+    db: &Db,
+    folder_id: &str,
+    source: &str,
+    text: &str,
+) -> Result<usize, String> {
+    let path_str = source.to_string();
+    let chunks = chunk_markdown(text);
+
+    let mut embedded = Vec::with_capacity(chunks.len());
+    for chunk in &chunks {
+        embedded.push(embed(chunk).await?);
+    }
+
+    let conn = db.conn();
+    let document_id = Uuid::new_v4().to_string();
+    conn.execute(
+        "DELETE FROM kb_documents WHERE path = ?1",
+        params![path_str],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO kb_documents (id, folder_id, path, updated_at) VALUES (?1, ?2, ?3, ?4)",
+        params![document_id, folder_id, path_str, db::now()],
+    )
+    .map_err(|e| e.to_string())?;
+    for (chunk, embedding) in chunks.iter().zip(&embedded) {
+        conn.execute(
+            "INSERT INTO kb_chunks (id, document_id, content, embedding) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                Uuid::new_v4().to_string(),
+                document_id,
+                chunk,
+                embedding_to_blob(embedding)
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(chunks.len())
+}
+
+pub fn remove_file(db: &Db, path: &Path) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute(
+        "DELETE FROM kb_documents WHERE path = ?1",
+        params![path.to_string_lossy().into_owned()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Embed a chat message and store the vector for semantic search.
+/// Failures are logged and swallowed: embedding is best-effort and must
+/// never break message persistence.
+async fn embed_message(db: &Db, message_id: &str, chat_id: &str, content: &str) {
+    if content.trim().is_empty() {
+        return;
+    }
+    let embedding = match embed(content).await {
+        Ok(embedding) => embedding,
+        Err(e) => {
+            tracing::debug!("skipping message embedding: {}", e);
+            return;
+        }
+    };
+    let conn = db.conn();
+    let _ = conn.execute(
+        "INSERT OR REPLACE INTO message_embeddings (message_id, chat_id, embedding)
+         VALUES (?1, ?2, ?3)",
+        params![message_id, chat_id, embedding_to_blob(&embedding)],
+    );
+}
+
+/// Spawn background embedding for a just-saved message, so generation
+/// latency never waits on the embedding model.
+pub(crate) fn embed_message_background(app: &AppHandle, message: &crate::chat::Message) {
+    let app = app.clone();
+    let message = message.clone();
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<Db>();
+        embed_message(&db, &message.id, &message.chat_id, &message.content).await;
+    });
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SemanticHit {
+    pub message_id: String,
+    pub chat_id: String,
+    pub chat_title: String,
+    pub role: String,
+    pub content: String,
+    pub score: f32,
+}
+
+/// Embed the query and return the `top_k` most semantically similar past
+/// messages, even when no keywords match. Only messages embedded since
+/// this feature landed participate; older history fills in as chats are
+/// revisited.
+#[tauri::command]
+pub async fn semantic_search_chats(
+    db: State<'_, Db>,
+    query: String,
+    top_k: usize,
+) -> Result<Vec<SemanticHit>, String> {
+    let query_embedding = embed(&query).await?;
+    let mut hits: Vec<SemanticHit> = {
+        let conn = db.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT e.message_id, e.chat_id, c.title, m.role, m.content, e.embedding,
+                        m.compressed, m.content_zstd
+                 FROM message_embeddings e
+                 JOIN messages m ON m.id = e.message_id
+                 JOIN chats c ON c.id = e.chat_id
+                 WHERE m.deleted_at IS NULL AND c.deleted_at IS NULL",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    compression::stored(
+                        row.get(4)?,
+                        row.get::<_, i64>(6)? != 0,
+                        row.get(7)?,
+                    ),
+                    row.get::<_, Vec<u8>>(5)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows.into_iter()
+            .map(|(message_id, chat_id, chat_title, role, content, blob)| SemanticHit {
+                message_id,
+                chat_id,
+                chat_title,
+                role,
+                content,
+                score: cosine_similarity(&query_embedding, &blob_to_embedding(&blob)),
+            })
+            .collect()
+    };
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(top_k);
+    Ok(hits)
+}
+
+/// Embed the query and return the `top_k` most similar chunks.
+#[tauri::command]
+pub async fn search_knowledge_base(
+    db: State<'_, Db>,
+    query: String,
+    top_k: usize,
+) -> Result<Vec<KnowledgeHit>, String> {
+    let query_embedding = embed(&query).await?;
+    let mut hits: Vec<KnowledgeHit> = {
+        let conn = db.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT d.path, c.content, c.embedding
+                 FROM kb_chunks c JOIN kb_documents d ON d.id = c.document_id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows.into_iter()
+            .map(|(path, content, blob)| KnowledgeHit {
+                document_path: path,
+                score: cosine_similarity(&query_embedding, &blob_to_embedding(&blob)),
+                content,
+            })
+            .collect()
+    };
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(top_k);
+    Ok(hits)
+}
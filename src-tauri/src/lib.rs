@@ -0,0 +1,316 @@
+pub mod academic;
+pub mod accessibility;
+pub mod appdata;
+pub mod attachments;
+pub mod automations;
+pub mod batch;
+pub mod cache;
+pub mod chat;
+pub mod chatrefs;
+pub mod citations;
+pub mod commands;
+pub mod compression;
+pub mod constraints;
+pub mod context;
+pub mod crypto;
+pub mod db;
+pub mod draft;
+pub mod error;
+pub mod export;
+pub mod followups;
+pub mod hooks;
+pub mod journal;
+pub mod knowledge;
+pub mod library;
+pub mod logging;
+pub mod mcp;
+pub mod monitor;
+pub mod ndjson;
+pub mod ollama;
+pub mod onboarding;
+pub mod operations;
+pub mod permissions;
+pub mod personas;
+pub mod playground;
+pub mod plugins;
+pub mod postprocess;
+pub mod privacy;
+pub mod profiles;
+pub mod replay;
+pub mod proofread;
+pub mod reports;
+pub mod research;
+pub mod selection;
+pub mod settings;
+pub mod snapshots;
+pub mod sources;
+pub mod stats;
+pub mod structured;
+pub mod sync;
+pub mod templates;
+pub mod training;
+pub mod translate;
+pub mod tray;
+pub mod triggers;
+pub mod watcher;
+pub mod web;
+pub mod youtube;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Debug, Clone, Serialize)]
+struct ReadyStage {
+    stage: String,
+}
+
+/// Staged `app-ready` events so the frontend can unblock the UI as soon
+/// as the database is usable instead of waiting for every subsystem.
+fn emit_ready(app: &AppHandle, stage: &str) {
+    let _ = app.emit(
+        "app-ready",
+        &ReadyStage {
+            stage: stage.to_string(),
+        },
+    );
+}
+
+/// Everything that needs a usable database: runs from setup for a
+/// plaintext database, or from `unlock_database` once the passphrase
+/// opened an encrypted one. Only the quick template seeding happens
+/// inline; folder watchers (filesystem scans), the tray (Ollama
+/// probing) and the watchdog are deferred to a background task so cold
+/// start does not block the window on them.
+pub(crate) fn finish_startup(app: &AppHandle) -> Result<(), String> {
+    let db = app.state::<db::Db>();
+    templates::seed_builtins(&db)?;
+    emit_ready(app, "database");
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = watcher::restore_watchers(&app) {
+            tracing::warn!("failed to restore folder watchers: {}", e);
+        }
+        emit_ready(&app, "watchers");
+        {
+            let db = app.state::<db::Db>();
+            if let Err(e) = plugins::load_plugins(app.clone(), db) {
+                tracing::warn!("failed to load plugins: {}", e);
+            }
+        }
+        tray::init(app.clone());
+        emit_ready(&app, "tray");
+        if let Err(e) = selection::register_shortcut(&app) {
+            tracing::warn!("{}", e);
+        }
+        db::start_watchdog(app.clone());
+        automations::start_scheduler(app.clone());
+        chat::start_trash_purge(app.clone());
+        monitor::start_monitor(app.clone());
+        emit_ready(&app, "complete");
+    });
+    Ok(())
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .setup(|app| {
+            match logging::init(app.handle()) {
+                Ok(guard) => {
+                    app.manage(guard);
+                }
+                Err(e) => eprintln!("failed to initialize logging: {}", e),
+            }
+            app.manage(batch::BatchState::default());
+            app.manage(mcp::McpState::default());
+            app.manage(ollama::ActivePulls::default());
+            app.manage(operations::ActiveOperations::default());
+            app.manage(chat::ActiveGenerations::default());
+            app.manage(chat::GenerationQueue::default());
+            app.manage(chat::GenerationScheduler::default());
+            app.manage(draft::DraftState::default());
+            app.manage(library::LibraryCache::default());
+            app.manage(permissions::PendingPermissions::default());
+            app.manage(plugins::PluginState::default());
+            app.manage(watcher::WatcherState::default());
+            app.manage(tray::TrayState::default());
+            let app_data_dir = profiles::data_dir(app.handle()).map_err(std::io::Error::other)?;
+            if crypto::is_encrypted(&app_data_dir) {
+                // The frontend shows the passphrase prompt and calls
+                // unlock_database, which manages the Db and finishes
+                // startup.
+            } else {
+                let db = db::init(app_data_dir).map_err(std::io::Error::other)?;
+                app.manage(db);
+                finish_startup(app.handle()).map_err(std::io::Error::other)?;
+            }
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // Closing the main window minimizes to tray; Quit in the tray
+            // menu actually exits.
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if window.label() == "main" {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
+        .invoke_handler(tauri::generate_handler![
+            academic::search_academic,
+            accessibility::stream_chat_plaintext,
+            academic::get_academic_search_defaults,
+            academic::set_academic_search_defaults,
+            appdata::export_app_data,
+            appdata::import_app_data,
+            automations::add_automation,
+            automations::remove_automation,
+            automations::set_automation_enabled,
+            automations::get_automations,
+            automations::run_automation_now,
+            batch::submit_batch,
+            batch::pause_batch,
+            batch::resume_batch,
+            batch::cancel_batch,
+            batch::get_batch_jobs,
+            batch::get_batch_items,
+            cache::clear_prompt_cache,
+            cache::set_prompt_cache_bypass,
+            chat::create_chat,
+            chat::get_chats,
+            chat::get_chats_page,
+            chat::get_messages,
+            chat::get_chat_messages_page,
+            chat::rename_chat,
+            chat::update_chat_model,
+            chat::delete_chat,
+            chat::list_trashed_chats,
+            chat::restore_chat,
+            chat::delete_message,
+            chat::restore_message,
+            chat::purge_trash,
+            chat::duplicate_chat,
+            chat::fork_chat,
+            chat::chat,
+            chat::stop_generation,
+            chat::continue_generation,
+            chat::promote_queued_generation,
+            chat::cancel_queued_generation,
+            chat::search_in_chat,
+            chat::set_pruning_policy,
+            chat::set_context_boundary,
+            chat::set_chat_keep_alive,
+            chat::pin_message,
+            chat::set_message_feedback,
+            chat::add_message_note,
+            chat::set_message_bookmarked,
+            chat::list_bookmarked_messages,
+            chat::lock_chat,
+            chat::unlock_chat,
+            chat::get_chat_lock,
+            chatrefs::resolve_chat_reference,
+            commands::list_slash_commands,
+            compression::compact_database,
+            context::get_context_history,
+            crypto::is_database_encrypted,
+            draft::chat_with_draft,
+            draft::keep_draft,
+            crypto::unlock_database,
+            crypto::enable_encryption,
+            crypto::change_passphrase,
+            export::export_chat,
+            export::import_chat,
+            export::diff_chats,
+            followups::generate_follow_ups,
+            hooks::add_hook,
+            hooks::remove_hook,
+            hooks::set_hook_enabled,
+            hooks::get_hooks,
+            ollama::list_models,
+            ollama::pull_model,
+            ollama::delete_model,
+            ollama::get_model_details,
+            ollama::check_model_fit,
+            ollama::pause_pull,
+            ollama::resume_pull,
+            ollama::cancel_pull,
+            ollama::get_pulls,
+            ollama::warm_model,
+            ollama::warm_chat_model,
+            ollama::unload_model,
+            onboarding::detect_environment,
+            onboarding::install_recommended_model,
+            onboarding::complete_onboarding,
+            permissions::respond_permission,
+            permissions::get_tool_grants,
+            permissions::revoke_tool_grant,
+            permissions::get_tool_audit_log,
+            personas::save_persona,
+            personas::get_personas,
+            personas::delete_persona,
+            personas::export_persona_pack,
+            personas::import_persona_pack,
+            playground::run_parameter_sweep,
+            plugins::load_plugins,
+            plugins::list_plugins,
+            plugins::set_plugin_permission,
+            playground::get_sweeps,
+            playground::delete_sweep,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::switch_profile,
+            proofread::proofread,
+            replay::replay_chat,
+            reports::generate_report,
+            research::literature_review,
+            selection::process_selection,
+            attachments::attach_file,
+            attachments::get_attachments,
+            journal::get_changes_since,
+            knowledge::search_knowledge_base,
+            knowledge::semantic_search_chats,
+            library::browse_model_library,
+            logging::get_recent_logs,
+            logging::create_diagnostics_bundle,
+            mcp::add_mcp_server,
+            mcp::remove_mcp_server,
+            mcp::get_mcp_servers,
+            mcp::connect_mcp_server,
+            mcp::disconnect_mcp_server,
+            mcp::list_mcp_tools,
+            monitor::get_system_info,
+            operations::list_active_operations,
+            settings::get_setting,
+            settings::set_setting,
+            snapshots::create_chat_snapshot,
+            snapshots::restore_chat_snapshot,
+            snapshots::list_chat_snapshots,
+            snapshots::delete_chat_snapshot,
+            sources::get_message_sources,
+            stats::get_chat_statistics,
+            sync::configure_sync,
+            sync::get_sync_status,
+            sync::sync_now,
+            templates::get_templates,
+            templates::save_template,
+            templates::delete_template,
+            templates::run_template,
+            training::export_training_dataset,
+            translate::translate_message,
+            tray::refresh_tray_menu,
+            triggers::add_trigger,
+            triggers::remove_trigger,
+            triggers::set_trigger_enabled,
+            triggers::get_triggers,
+            watcher::add_watched_folder,
+            watcher::remove_watched_folder,
+            watcher::get_watched_folders,
+            web::summarize_url,
+            youtube::fetch_youtube_transcript,
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
@@ -0,0 +1,197 @@
+//! Ollama library browsing. The registry has no JSON API, so this
+//! scrapes the library/search pages the same tolerant way `web.rs`
+//! reads articles: model cards are keyed off their `/library/<name>`
+//! links and everything else (description, size tags, pull counts) is
+//! best-effort. Listings are cached in memory for an hour per
+//! query/category pair, and every size tag gets a fit verdict from the
+//! same estimator as `check_model_fit` so the picker can warn before a
+//! multi-gigabyte pull.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppResult;
+use crate::ollama;
+use crate::web;
+
+const LIBRARY_URL: &str = "https://ollama.com";
+const CACHE_TTL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeTag {
+    /// Tag suffix like `7b` or `70b`.
+    pub tag: String,
+    /// `fits`, `marginal`, `wont_fit`, or `None` when unestimable.
+    pub fit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryModel {
+    pub name: String,
+    pub description: String,
+    pub sizes: Vec<SizeTag>,
+    /// Display pull count as shown on the site, e.g. `5.2M`.
+    pub pulls: Option<String>,
+}
+
+#[derive(Default)]
+pub struct LibraryCache(Mutex<HashMap<String, (Instant, Vec<LibraryModel>)>>);
+
+/// Parse the model cards out of a library or search page. Cards are
+/// delimited by their `/library/<name>` links; a model linked more than
+/// once keeps its first card.
+pub fn parse_library_html(html: &str) -> Vec<LibraryModel> {
+    let size_re = Regex::new(r"\b(\d+(?:\.\d+)?b)\b").expect("valid size pattern");
+    let pulls_re = Regex::new(r"([\d.]+[KMB]?)\s+Pulls").expect("valid pulls pattern");
+    let mut models: Vec<LibraryModel> = Vec::new();
+    for segment in html.split("href=\"/library/").skip(1) {
+        let Some(name_end) = segment.find('"') else {
+            continue;
+        };
+        let name = &segment[..name_end];
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_')
+            || models.iter().any(|m| m.name == name)
+        {
+            continue;
+        }
+        let card = &segment[name_end..];
+        let description = card
+            .find("<p")
+            .and_then(|i| {
+                let after = &card[i..];
+                let start = after.find('>')? + 1;
+                let end = after.find("</p>")?;
+                (start <= end).then(|| web::extract_text(&after[start..end]))
+            })
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let text = web::extract_text(card);
+        let mut sizes = Vec::new();
+        for m in size_re.find_iter(&text) {
+            let tag = m.as_str().to_string();
+            if !sizes.contains(&tag) {
+                sizes.push(tag);
+            }
+        }
+        let pulls = pulls_re
+            .captures(&text)
+            .map(|c| c[1].to_string());
+        models.push(LibraryModel {
+            name: name.to_string(),
+            description,
+            sizes: sizes
+                .into_iter()
+                .map(|tag| SizeTag { tag, fit: None })
+                .collect(),
+            pulls,
+        });
+    }
+    models
+}
+
+fn listing_url(query: &str, category: &str) -> String {
+    if query.is_empty() && category.is_empty() {
+        return format!("{}/library", LIBRARY_URL);
+    }
+    let mut url = format!("{}/search?q={}", LIBRARY_URL, urlencode(query));
+    if !category.is_empty() {
+        url.push_str(&format!("&c={}", urlencode(category)));
+    }
+    url
+}
+
+fn urlencode(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| {
+            if c.is_ascii_alphanumeric() || "-_.~".contains(c) {
+                vec![c.to_string()]
+            } else {
+                c.to_string()
+                    .bytes()
+                    .map(|b| format!("%{:02X}", b))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Browse the Ollama model library. `query` filters by name/description
+/// and `category` by the site's capability facets (`vision`, `tools`,
+/// `embedding`, ...); both empty returns the front library page.
+/// Results are cached for an hour.
+#[tauri::command]
+pub async fn browse_model_library(
+    app: AppHandle,
+    query: String,
+    category: String,
+) -> AppResult<Vec<LibraryModel>> {
+    let cache_key = format!("{}|{}", query, category);
+    {
+        let cache = app.state::<LibraryCache>();
+        let cached = cache.0.lock().unwrap();
+        if let Some((fetched, models)) = cached.get(&cache_key) {
+            if fetched.elapsed() < CACHE_TTL {
+                return Ok(models.clone());
+            }
+        }
+    }
+    let html = reqwest::get(listing_url(&query, &category))
+        .await?
+        .text()
+        .await?;
+    let mut models = parse_library_html(&html);
+    for model in &mut models {
+        for size in &mut model.sizes {
+            size.fit = ollama::quick_fit_verdict(&format!("{}:{}", model.name, size.tag));
+        }
+    }
+    let cache = app.state::<LibraryCache>();
+    cache
+        .0
+        .lock()
+        .unwrap()
+        .insert(cache_key, (Instant::now(), models.clone()));
+    Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{listing_url, parse_library_html};
+
+    #[test]
+    fn cards_parse_names_descriptions_and_sizes() {
+        let html = r#"
+            <li><a href="/library/llama3"><h2>llama3</h2>
+            <p>Meta&#39;s flagship open model.</p>
+            <span>8b</span><span>70b</span><span>5.2M Pulls</span></a></li>
+            <li><a href="/library/nomic-embed-text"><h2>nomic-embed-text</h2>
+            <p>Text embeddings.</p><span>137M Pulls</span></a></li>
+        "#;
+        let models = parse_library_html(html);
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].name, "llama3");
+        assert_eq!(models[0].description, "Meta's flagship open model.");
+        let sizes: Vec<&str> = models[0].sizes.iter().map(|s| s.tag.as_str()).collect();
+        assert_eq!(sizes, vec!["8b", "70b"]);
+        assert_eq!(models[0].pulls.as_deref(), Some("5.2M"));
+        assert!(models[1].sizes.is_empty());
+    }
+
+    #[test]
+    fn listing_urls_encode_query_and_category() {
+        assert_eq!(listing_url("", ""), "https://ollama.com/library");
+        assert_eq!(
+            listing_url("code llama", "tools"),
+            "https://ollama.com/search?q=code%20llama&c=tools"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Structured logging and diagnostics. Tracing output goes to stderr
+//! and to daily-rotating files under `<app data>/logs`;
+//! `create_diagnostics_bundle` zips recent logs together with version
+//! and model information for bug reports.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::ollama::OLLAMA_BASE_URL;
+
+/// Keeps the non-blocking writer alive for the lifetime of the app.
+pub struct LogGuard(#[allow(dead_code)] tracing_appender::non_blocking::WorkerGuard);
+
+fn logs_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("logs");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Install the tracing subscriber: INFO and up to rotating files,
+/// everything the `RUST_LOG` filter allows to stderr.
+pub fn init(app: &AppHandle) -> Result<LogGuard, String> {
+    let dir = logs_dir(app)?;
+    let appender = tracing_appender::rolling::daily(dir, "cortex.log");
+    let (file_writer, guard) = tracing_appender::non_blocking(appender);
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+            tracing_subscriber::EnvFilter::new("info")
+        }))
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(file_writer)
+                .with_ansi(false),
+        )
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .try_init()
+        .map_err(|e| e.to_string())?;
+    Ok(LogGuard(guard))
+}
+
+/// Log lines from the newest files, newest first. `level` filters to
+/// lines at that level (`ERROR`, `WARN`, `INFO`, ...); empty means all.
+#[tauri::command]
+pub fn get_recent_logs(app: AppHandle, level: String, limit: usize) -> Result<Vec<String>, String> {
+    let dir = logs_dir(&app)?;
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect();
+    // Daily rotation names files by date, so the name orders them.
+    files.sort();
+    let level = level.to_uppercase();
+    let mut lines: Vec<String> = Vec::new();
+    for file in files.iter().rev() {
+        let raw = fs::read_to_string(file).map_err(|e| e.to_string())?;
+        for line in raw.lines().rev() {
+            if level.is_empty() || line.contains(&format!(" {} ", level)) {
+                lines.push(line.to_string());
+                if lines.len() >= limit {
+                    return Ok(lines);
+                }
+            }
+        }
+    }
+    Ok(lines)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsBundle {
+    pub path: String,
+    pub size: u64,
+}
+
+async fn ollama_version() -> String {
+    match reqwest::get(format!("{}/api/version", OLLAMA_BASE_URL)).await {
+        Ok(resp) => resp
+            .json::<Value>()
+            .await
+            .ok()
+            .and_then(|v| v.get("version").and_then(Value::as_str).map(str::to_string))
+            .unwrap_or_else(|| "unknown".to_string()),
+        Err(e) => format!("unreachable: {}", e),
+    }
+}
+
+/// Zip recent logs plus environment information into
+/// `<app data>/diagnostics-<timestamp>.zip` and return its path.
+#[tauri::command]
+pub async fn create_diagnostics_bundle(app: AppHandle) -> Result<DiagnosticsBundle, String> {
+    let op = crate::operations::start(&app, "diagnostics", "Creating diagnostics bundle");
+    let bundle = build_diagnostics_bundle(&app).await;
+    crate::operations::finish(&app, &op, if bundle.is_ok() { "done" } else { "failed" });
+    bundle
+}
+
+async fn build_diagnostics_bundle(app: &AppHandle) -> Result<DiagnosticsBundle, String> {
+    let info = serde_json::json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "ollama_version": ollama_version().await,
+        "installed_models": match crate::ollama::list_models().await {
+            Ok(models) => serde_json::to_value(models).unwrap_or_default(),
+            Err(e) => Value::String(format!("unavailable: {}", e)),
+        },
+        "system": crate::monitor::get_system_info(),
+        "created_at": crate::db::now(),
+    });
+    let logs = get_recent_logs(app.clone(), String::new(), 2000)?;
+    let out_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(format!(
+            "diagnostics-{}.zip",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+    let file = fs::File::create(&out_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file("info.json", options)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(
+        serde_json::to_string_pretty(&info)
+            .map_err(|e| e.to_string())?
+            .as_bytes(),
+    )
+    .map_err(|e| e.to_string())?;
+    zip.start_file("recent.log", options)
+        .map_err(|e| e.to_string())?;
+    // get_recent_logs returns newest first; the bundle reads better
+    // chronologically.
+    for line in logs.iter().rev() {
+        writeln!(zip, "{}", line).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    let size = fs::metadata(&out_path).map_err(|e| e.to_string())?.len();
+    Ok(DiagnosticsBundle {
+        path: out_path.to_string_lossy().to_string(),
+        size,
+    })
+}
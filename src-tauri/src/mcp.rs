@@ -0,0 +1,476 @@
+//! MCP (Model Context Protocol) client. Servers are defined in the DB
+//! (stdio command or SSE URL), launched/connected on demand, and their
+//! tools are exposed to the tool-calling loop in `chat` under
+//! `server__tool` names. Both transports speak JSON-RPC 2.0; responses
+//! are routed back to callers by request id.
+
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Connected clients keyed by server id.
+#[derive(Default)]
+pub struct McpState(pub Mutex<HashMap<String, Arc<McpClient>>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServer {
+    pub id: String,
+    pub name: String,
+    /// `stdio` or `sse`.
+    pub transport: String,
+    /// The command to launch (stdio) or the SSE endpoint URL.
+    pub target: String,
+    pub args: Vec<String>,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct McpTool {
+    pub server: String,
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
+
+type Pending = Arc<Mutex<HashMap<i64, oneshot::Sender<Value>>>>;
+
+enum Transport {
+    Stdio {
+        stdin: tokio::sync::Mutex<tokio::process::ChildStdin>,
+        // Held so the server process is killed when the client drops.
+        _child: tokio::process::Child,
+    },
+    Sse {
+        endpoint: String,
+        client: reqwest::Client,
+    },
+}
+
+pub struct McpClient {
+    pub server_name: String,
+    next_id: AtomicI64,
+    pending: Pending,
+    transport: Transport,
+}
+
+/// Route one incoming JSON-RPC message: responses wake the pending
+/// caller, everything else (notifications, server requests) is ignored.
+fn dispatch(pending: &Pending, value: Value) {
+    if let Some(id) = value.get("id").and_then(Value::as_i64) {
+        if let Some(sender) = pending.lock().unwrap().remove(&id) {
+            let _ = sender.send(value);
+        }
+    }
+}
+
+impl McpClient {
+    async fn send(&self, message: &Value) -> Result<(), String> {
+        match &self.transport {
+            Transport::Stdio { stdin, .. } => {
+                let mut line = serde_json::to_string(message).map_err(|e| e.to_string())?;
+                line.push('\n');
+                let mut stdin = stdin.lock().await;
+                stdin
+                    .write_all(line.as_bytes())
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            Transport::Sse { endpoint, client } => {
+                client
+                    .post(endpoint)
+                    .json(message)
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+        let response = tokio::time::timeout(std::time::Duration::from_secs(30), rx)
+            .await
+            .map_err(|_| format!("{}: timed out waiting for {}", self.server_name, method))?
+            .map_err(|_| format!("{}: connection closed", self.server_name))?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{}: {}", self.server_name, error));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn initialize(&self) -> Result<(), String> {
+        self.request(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "cortex-ai-desktop", "version": env!("CARGO_PKG_VERSION") },
+            }),
+        )
+        .await?;
+        self.send(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await
+    }
+
+    pub async fn list_tools(&self) -> Result<Vec<McpTool>, String> {
+        let result = self.request("tools/list", json!({})).await?;
+        let tools = result
+            .get("tools")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        Ok(tools
+            .iter()
+            .map(|tool| McpTool {
+                server: self.server_name.clone(),
+                name: tool
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                description: tool
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                input_schema: tool
+                    .get("inputSchema")
+                    .cloned()
+                    .unwrap_or_else(|| json!({ "type": "object" })),
+            })
+            .collect())
+    }
+
+    /// Call a tool and flatten its content blocks to text.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<String, String> {
+        let result = self
+            .request("tools/call", json!({ "name": name, "arguments": arguments }))
+            .await?;
+        let text = result
+            .get("content")
+            .and_then(Value::as_array)
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter_map(|b| b.get("text").and_then(Value::as_str))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default();
+        if result.get("isError").and_then(Value::as_bool) == Some(true) {
+            return Err(text);
+        }
+        Ok(text)
+    }
+}
+
+async fn connect_stdio(server: &McpServer) -> Result<Arc<McpClient>, String> {
+    let mut child = tokio::process::Command::new(&server.target)
+        .args(&server.args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to launch {}: {}", server.target, e))?;
+    let stdin = child.stdin.take().ok_or("no stdin handle")?;
+    let stdout = child.stdout.take().ok_or("no stdout handle")?;
+    let pending: Pending = Arc::default();
+    let reader_pending = pending.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Ok(value) = serde_json::from_str::<Value>(&line) {
+                dispatch(&reader_pending, value);
+            }
+        }
+    });
+    let client = Arc::new(McpClient {
+        server_name: server.name.clone(),
+        next_id: AtomicI64::new(1),
+        pending,
+        transport: Transport::Stdio {
+            stdin: tokio::sync::Mutex::new(stdin),
+            _child: child,
+        },
+    });
+    client.initialize().await?;
+    Ok(client)
+}
+
+/// SSE transport: the server streams JSON-RPC messages as `data:` lines
+/// from a GET on the configured URL; the first `endpoint` event names
+/// the URL we POST our messages to.
+async fn connect_sse(server: &McpServer) -> Result<Arc<McpClient>, String> {
+    use futures_util::StreamExt;
+    let http = reqwest::Client::new();
+    let resp = http
+        .get(&server.target)
+        .header("Accept", "text/event-stream")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let (endpoint_tx, endpoint_rx) = oneshot::channel::<String>();
+    let pending: Pending = Arc::default();
+    let reader_pending = pending.clone();
+    let base = server.target.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut endpoint_tx = Some(endpoint_tx);
+        let mut event = String::new();
+        let mut buffer = String::new();
+        let mut stream = resp.bytes_stream();
+        while let Some(Ok(chunk)) = stream.next().await {
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+            while let Some(newline) = buffer.find('\n') {
+                let line = buffer[..newline].trim_end_matches('\r').to_string();
+                buffer.drain(..=newline);
+                if let Some(name) = line.strip_prefix("event:") {
+                    event = name.trim().to_string();
+                } else if let Some(data) = line.strip_prefix("data:") {
+                    let data = data.trim();
+                    if event == "endpoint" {
+                        if let Some(tx) = endpoint_tx.take() {
+                            // Endpoint may be relative to the SSE URL's origin.
+                            let url = if data.starts_with("http") {
+                                data.to_string()
+                            } else {
+                                let origin = base
+                                    .find("://")
+                                    .and_then(|i| base[i + 3..].find('/').map(|j| i + 3 + j))
+                                    .map(|end| &base[..end])
+                                    .unwrap_or(&base);
+                                format!("{}{}", origin, data)
+                            };
+                            let _ = tx.send(url);
+                        }
+                    } else if let Ok(value) = serde_json::from_str::<Value>(data) {
+                        dispatch(&reader_pending, value);
+                    }
+                }
+            }
+        }
+    });
+    let endpoint = tokio::time::timeout(std::time::Duration::from_secs(10), endpoint_rx)
+        .await
+        .map_err(|_| "timed out waiting for SSE endpoint event")?
+        .map_err(|_| "SSE stream closed before the endpoint event")?;
+    let client = Arc::new(McpClient {
+        server_name: server.name.clone(),
+        next_id: AtomicI64::new(1),
+        pending,
+        transport: Transport::Sse {
+            endpoint,
+            client: http,
+        },
+    });
+    client.initialize().await?;
+    Ok(client)
+}
+
+fn get_servers_internal(db: &Db) -> Result<Vec<McpServer>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, transport, target, args, enabled, created_at
+             FROM mcp_servers ORDER BY created_at ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let servers = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i64>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    servers
+        .into_iter()
+        .map(|(id, name, transport, target, args, enabled, created_at)| {
+            Ok(McpServer {
+                id,
+                name,
+                transport,
+                target,
+                args: serde_json::from_str(&args).map_err(|e| e.to_string())?,
+                enabled: enabled != 0,
+                created_at,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn add_mcp_server(
+    db: State<Db>,
+    name: String,
+    transport: String,
+    target: String,
+    args: Vec<String>,
+) -> Result<McpServer, String> {
+    if transport != "stdio" && transport != "sse" {
+        return Err(format!("unknown transport: {}", transport));
+    }
+    let server = McpServer {
+        id: Uuid::new_v4().to_string(),
+        name,
+        transport,
+        target,
+        args,
+        enabled: true,
+        created_at: db::now(),
+    };
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO mcp_servers (id, name, transport, target, args, enabled, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6)",
+        params![
+            server.id,
+            server.name,
+            server.transport,
+            server.target,
+            serde_json::to_string(&server.args).map_err(|e| e.to_string())?,
+            server.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(server)
+}
+
+#[tauri::command]
+pub fn remove_mcp_server(
+    db: State<Db>,
+    state: State<McpState>,
+    server_id: String,
+) -> Result<(), String> {
+    state.0.lock().unwrap().remove(&server_id);
+    let conn = db.conn();
+    conn.execute("DELETE FROM mcp_servers WHERE id = ?1", params![server_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_mcp_servers(db: State<Db>) -> Result<Vec<McpServer>, String> {
+    get_servers_internal(&db)
+}
+
+#[tauri::command]
+pub async fn connect_mcp_server(app: AppHandle, server_id: String) -> Result<Vec<McpTool>, String> {
+    let server = {
+        let db = app.state::<Db>();
+        get_servers_internal(&db)?
+            .into_iter()
+            .find(|s| s.id == server_id)
+            .ok_or_else(|| format!("no MCP server {}", server_id))?
+    };
+    let client = match server.transport.as_str() {
+        "stdio" => connect_stdio(&server).await?,
+        "sse" => connect_sse(&server).await?,
+        other => return Err(format!("unknown transport: {}", other)),
+    };
+    let tools = client.list_tools().await?;
+    app.state::<McpState>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(server_id, client);
+    Ok(tools)
+}
+
+#[tauri::command]
+pub fn disconnect_mcp_server(state: State<McpState>, server_id: String) -> Result<(), String> {
+    state.0.lock().unwrap().remove(&server_id);
+    Ok(())
+}
+
+/// Tools across all connected servers.
+#[tauri::command]
+pub async fn list_mcp_tools(app: AppHandle) -> Result<Vec<McpTool>, String> {
+    let clients: Vec<Arc<McpClient>> = app
+        .state::<McpState>()
+        .0
+        .lock()
+        .unwrap()
+        .values()
+        .cloned()
+        .collect();
+    let mut tools = Vec::new();
+    for client in clients {
+        tools.extend(client.list_tools().await?);
+    }
+    Ok(tools)
+}
+
+/// Ollama tool specs for every connected server's tools, namespaced as
+/// `server__tool` so calls route back to the right server.
+pub async fn ollama_tool_specs(app: &AppHandle) -> Vec<Value> {
+    let clients: Vec<Arc<McpClient>> = app
+        .state::<McpState>()
+        .0
+        .lock()
+        .unwrap()
+        .values()
+        .cloned()
+        .collect();
+    let mut specs = Vec::new();
+    for client in clients {
+        if let Ok(tools) = client.list_tools().await {
+            for tool in tools {
+                specs.push(json!({
+                    "type": "function",
+                    "function": {
+                        "name": format!("{}__{}", tool.server, tool.name),
+                        "description": tool.description,
+                        "parameters": tool.input_schema,
+                    }
+                }));
+            }
+        }
+    }
+    specs
+}
+
+/// Execute one namespaced tool call from the model.
+pub async fn dispatch_tool_call(
+    app: &AppHandle,
+    name: &str,
+    arguments: Value,
+) -> Result<String, String> {
+    let (server, tool) = name
+        .split_once("__")
+        .ok_or_else(|| format!("malformed tool name: {}", name))?;
+    let client = app
+        .state::<McpState>()
+        .0
+        .lock()
+        .unwrap()
+        .values()
+        .find(|c| c.server_name == server)
+        .cloned()
+        .ok_or_else(|| format!("no connected MCP server named {}", server))?;
+    client.call_tool(tool, arguments).await
+}
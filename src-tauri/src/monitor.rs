@@ -0,0 +1,108 @@
+//! Lightweight system resource monitor. Emits periodic `system-stats`
+//! events with CPU, RAM and model VRAM usage so the UI can warn before
+//! pulling or loading a model that will not fit. VRAM comes from
+//! Ollama's `/api/ps` (per loaded model) rather than vendor APIs, so it
+//! works the same on NVIDIA, AMD and Apple Silicon.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+use sysinfo::System;
+use tauri::{AppHandle, Emitter};
+
+use crate::ollama::OLLAMA_BASE_URL;
+
+const MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    pub total_memory: u64,
+    pub available_memory: u64,
+    pub cpu_count: usize,
+    pub os: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedModel {
+    pub name: String,
+    pub size: u64,
+    pub size_vram: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemStats {
+    pub cpu_percent: f32,
+    pub used_memory: u64,
+    pub total_memory: u64,
+    /// Total VRAM in use by loaded models, per Ollama.
+    pub vram_used: u64,
+    pub loaded_models: Vec<LoadedModel>,
+}
+
+/// Static machine facts for "will this model fit" checks.
+#[tauri::command]
+pub fn get_system_info() -> SystemInfo {
+    let mut system = System::new();
+    system.refresh_memory();
+    SystemInfo {
+        total_memory: system.total_memory(),
+        available_memory: system.available_memory(),
+        cpu_count: num_cpus(),
+        os: std::env::consts::OS.to_string(),
+    }
+}
+
+fn num_cpus() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Models currently loaded by Ollama, with their VRAM footprint.
+async fn loaded_models() -> Vec<LoadedModel> {
+    let Ok(resp) = reqwest::get(format!("{}/api/ps", OLLAMA_BASE_URL)).await else {
+        return Vec::new();
+    };
+    let Ok(value) = resp.json::<Value>().await else {
+        return Vec::new();
+    };
+    value
+        .get("models")
+        .and_then(Value::as_array)
+        .map(|models| {
+            models
+                .iter()
+                .map(|m| LoadedModel {
+                    name: m
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    size: m.get("size").and_then(Value::as_u64).unwrap_or(0),
+                    size_vram: m.get("size_vram").and_then(Value::as_u64).unwrap_or(0),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Sample CPU/RAM/VRAM every few seconds and emit `system-stats`.
+pub fn start_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        loop {
+            tokio::time::sleep(MONITOR_INTERVAL).await;
+            system.refresh_cpu_usage();
+            system.refresh_memory();
+            let models = loaded_models().await;
+            let stats = SystemStats {
+                cpu_percent: system.global_cpu_info().cpu_usage(),
+                used_memory: system.used_memory(),
+                total_memory: system.total_memory(),
+                vram_used: models.iter().map(|m| m.size_vram).sum(),
+                loaded_models: models,
+            };
+            let _ = app.emit("system-stats", &stats);
+        }
+    });
+}
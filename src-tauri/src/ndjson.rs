@@ -0,0 +1,88 @@
+//! Line-buffered NDJSON decoding for Ollama's streaming endpoints.
+//!
+//! TCP chunks do not respect line boundaries: a JSON line regularly
+//! arrives split across two chunks, and one chunk can carry several
+//! lines. The decoder keeps incomplete trailing bytes buffered instead
+//! of discarding them, so no tokens are lost.
+
+use serde_json::Value;
+
+#[derive(Default)]
+pub struct NdjsonDecoder {
+    buffer: Vec<u8>,
+}
+
+impl NdjsonDecoder {
+    pub fn new() -> Self {
+        NdjsonDecoder::default()
+    }
+
+    /// Feed a chunk and return every complete JSON line it finished.
+    /// Bytes after the last newline stay buffered for the next chunk.
+    /// Lines that are empty or not valid JSON are skipped.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Value> {
+        self.buffer.extend_from_slice(chunk);
+        let mut values = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            if let Some(value) = parse_line(&line) {
+                values.push(value);
+            }
+        }
+        values
+    }
+
+    /// Parse whatever is left in the buffer (a final line without a
+    /// trailing newline) once the stream has ended.
+    pub fn finish(&mut self) -> Option<Value> {
+        let line = std::mem::take(&mut self.buffer);
+        parse_line(&line)
+    }
+}
+
+fn parse_line(line: &[u8]) -> Option<Value> {
+    let text = std::str::from_utf8(line).ok()?.trim();
+    if text.is_empty() {
+        return None;
+    }
+    serde_json::from_str(text).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn line_split_across_chunks_is_not_dropped() {
+        let mut decoder = NdjsonDecoder::new();
+        assert!(decoder.push(b"{\"message\":{\"content\":\"hel").is_empty());
+        let values = decoder.push(b"lo\"},\"done\":false}\n");
+        assert_eq!(
+            values,
+            vec![json!({"message": {"content": "hello"}, "done": false})]
+        );
+    }
+
+    #[test]
+    fn several_lines_in_one_chunk_all_decode() {
+        let mut decoder = NdjsonDecoder::new();
+        let values = decoder.push(b"{\"a\":1}\n{\"b\":2}\n{\"c\":3}\n");
+        assert_eq!(values, vec![json!({"a": 1}), json!({"b": 2}), json!({"c": 3})]);
+    }
+
+    #[test]
+    fn finish_flushes_unterminated_last_line() {
+        let mut decoder = NdjsonDecoder::new();
+        assert!(decoder.push(b"{\"done\":true}").is_empty());
+        assert_eq!(decoder.finish(), Some(json!({"done": true})));
+        assert_eq!(decoder.finish(), None);
+    }
+
+    #[test]
+    fn blank_and_garbage_lines_are_skipped() {
+        let mut decoder = NdjsonDecoder::new();
+        let values = decoder.push(b"\n\nnot json\n{\"ok\":true}\n");
+        assert_eq!(values, vec![json!({"ok": true})]);
+    }
+}
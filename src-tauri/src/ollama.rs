@@ -0,0 +1,589 @@
+//! Ollama HTTP API client and model management commands.
+
+use futures_util::StreamExt;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::ndjson::NdjsonDecoder;
+use crate::operations;
+
+pub const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    pub size: u64,
+    pub modified_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelConfig {
+    pub context_window: usize,
+}
+
+impl ModelConfig {
+    /// Known context windows for common models; anything unknown falls
+    /// back to a conservative 4k window.
+    pub fn get_default_config(model: &str) -> ModelConfig {
+        let context_window = if model.starts_with("llama3") {
+            8192
+        } else if model.starts_with("mistral") || model.starts_with("mixtral") {
+            32768
+        } else if model.starts_with("phi3") {
+            4096
+        } else if model.starts_with("gemma") {
+            8192
+        } else {
+            4096
+        };
+        ModelConfig { context_window }
+    }
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    models: Vec<OllamaModel>,
+}
+
+#[tauri::command]
+pub async fn list_models() -> AppResult<Vec<OllamaModel>> {
+    let resp = reqwest::get(format!("{}/api/tags", OLLAMA_BASE_URL))
+        .await?;
+    let tags: TagsResponse = resp.json().await?;
+    Ok(tags.models)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullProgress {
+    pub model: String,
+    pub status: String,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+}
+
+/// Stop flags for in-flight pulls, keyed by model name. Ollama resumes
+/// partially downloaded blobs server-side, so pausing simply drops the
+/// stream and resuming re-issues the pull.
+#[derive(Default)]
+pub struct ActivePulls(pub std::sync::Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullState {
+    pub model: String,
+    /// `downloading`, `paused` or `done`.
+    pub status: String,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+    pub updated_at: String,
+}
+
+fn save_pull_state(
+    db: &Db,
+    model: &str,
+    status: &str,
+    total: Option<u64>,
+    completed: Option<u64>,
+) {
+    let conn = db.conn();
+    let _ = conn.execute(
+        "INSERT INTO model_pulls (model, status, total, completed, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(model) DO UPDATE SET
+             status = excluded.status,
+             total = COALESCE(excluded.total, model_pulls.total),
+             completed = COALESCE(excluded.completed, model_pulls.completed),
+             updated_at = excluded.updated_at",
+        params![
+            model,
+            status,
+            total.map(|v| v as i64),
+            completed.map(|v| v as i64),
+            crate::db::now()
+        ],
+    );
+}
+
+/// Pull a model from the Ollama registry, streaming NDJSON progress lines
+/// and forwarding them to the frontend as `pull-progress` events keyed by
+/// model name. Pulls run concurrently; each one's state persists so an
+/// interrupted download shows up as resumable after a restart.
+#[tauri::command]
+pub async fn pull_model(app: AppHandle, model: String) -> AppResult<()> {
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    {
+        let pulls = app.state::<ActivePulls>();
+        let mut pulls = pulls.0.lock().unwrap();
+        if pulls.contains_key(&model) {
+            return Err(AppError::InvalidInput(format!(
+                "{} is already downloading",
+                model
+            )));
+        }
+        pulls.insert(model.clone(), stop_flag.clone());
+    }
+    {
+        let db = app.state::<Db>();
+        save_pull_state(&db, &model, "downloading", None, None);
+    }
+    let op = operations::start(&app, "model_pull", &format!("Pulling {}", model));
+    let result = pull_model_inner(&app, &model, &stop_flag, &op).await;
+    operations::finish(&app, &op, if result.is_ok() { "done" } else { "failed" });
+    app.state::<ActivePulls>().0.lock().unwrap().remove(&model);
+    let db = app.state::<Db>();
+    match &result {
+        Ok(true) => {
+            let conn = db.conn();
+            let _ = conn.execute("DELETE FROM model_pulls WHERE model = ?1", params![model]);
+            crate::tray::notify_if_unfocused(
+                &app,
+                "Model ready",
+                &format!("{} finished downloading", model),
+            );
+        }
+        Ok(false) => save_pull_state(&db, &model, "paused", None, None),
+        // A dropped connection leaves a resumable paused state rather
+        // than losing the partial download.
+        Err(_) => save_pull_state(&db, &model, "paused", None, None),
+    }
+    result.map(|_| ())
+}
+
+/// Returns `Ok(true)` when the pull completed, `Ok(false)` when it was
+/// paused or cancelled mid-stream.
+async fn pull_model_inner(
+    app: &AppHandle,
+    model: &str,
+    stop_flag: &AtomicBool,
+    op: &str,
+) -> AppResult<bool> {
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{}/api/pull", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({ "name": model, "stream": true }))
+        .send()
+        .await?;
+
+    let emit_progress = |value: &Value| -> AppResult<()> {
+        let progress = PullProgress {
+            model: model.to_string(),
+            status: value
+                .get("status")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            total: value.get("total").and_then(Value::as_u64),
+            completed: value.get("completed").and_then(Value::as_u64),
+        };
+        if let (Some(total), Some(completed)) = (progress.total, progress.completed) {
+            let db = app.state::<Db>();
+            save_pull_state(&db, model, "downloading", progress.total, progress.completed);
+            if total > 0 {
+                operations::progress(app, op, completed as f64 / total as f64);
+            }
+        }
+        Ok(app.emit("pull-progress", &progress)?)
+    };
+    let mut stream = resp.bytes_stream();
+    let mut decoder = NdjsonDecoder::new();
+    while let Some(chunk) = stream.next().await {
+        if stop_flag.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        let chunk = chunk?;
+        for value in decoder.push(&chunk) {
+            emit_progress(&value)?;
+        }
+    }
+    if let Some(value) = decoder.finish() {
+        emit_progress(&value)?;
+    }
+    Ok(true)
+}
+
+/// Stop the stream but keep the persisted state so the download shows
+/// as resumable.
+#[tauri::command]
+pub fn pause_pull(pulls: State<ActivePulls>, model: String) -> AppResult<()> {
+    pulls
+        .0
+        .lock()
+        .unwrap()
+        .get(&model)
+        .ok_or_else(|| AppError::NotFound(format!("{} is not downloading", model)))?
+        .store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Re-issue the pull; Ollama continues from the blobs it already has.
+#[tauri::command]
+pub async fn resume_pull(app: AppHandle, model: String) -> AppResult<()> {
+    pull_model(app, model).await
+}
+
+/// Stop the stream and forget the download entirely.
+#[tauri::command]
+pub fn cancel_pull(db: State<Db>, pulls: State<ActivePulls>, model: String) -> AppResult<()> {
+    if let Some(flag) = pulls.0.lock().unwrap().get(&model) {
+        flag.store(true, Ordering::Relaxed);
+    }
+    let conn = db.conn();
+    conn.execute("DELETE FROM model_pulls WHERE model = ?1", params![model])?;
+    Ok(())
+}
+
+/// Persisted download states, including paused pulls from previous runs.
+#[tauri::command]
+pub fn get_pulls(db: State<Db>) -> AppResult<Vec<PullState>> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT model, status, total, completed, updated_at
+             FROM model_pulls ORDER BY updated_at DESC",
+        )?;
+    let pulls = stmt
+        .query_map([], |row| {
+            Ok(PullState {
+                model: row.get(0)?,
+                status: row.get(1)?,
+                total: row.get::<_, Option<i64>>(2)?.map(|v| v as u64),
+                completed: row.get::<_, Option<i64>>(3)?.map(|v| v as u64),
+                updated_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(pulls)
+}
+
+#[tauri::command]
+pub async fn delete_model(model: String) -> AppResult<()> {
+    let client = reqwest::Client::new();
+    client
+        .delete(format!("{}/api/delete", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({ "name": model }))
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// Keep-alive used when warming a model without an explicit policy.
+const DEFAULT_KEEP_ALIVE: &str = "10m";
+
+/// Preload a model by sending an empty generate request with
+/// `keep_alive`; returns once the weights are in memory, eliminating
+/// the first-token load latency on the next real request.
+#[tauri::command]
+pub async fn warm_model(model: String, keep_alive: Option<String>) -> AppResult<()> {
+    let client = reqwest::Client::new();
+    client
+        .post(format!("{}/api/generate", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({
+            "model": model,
+            "keep_alive": keep_alive.as_deref().unwrap_or(DEFAULT_KEEP_ALIVE),
+        }))
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// Ask Ollama to unload a model immediately (`keep_alive: 0`).
+#[tauri::command]
+pub async fn unload_model(model: String) -> AppResult<()> {
+    let client = reqwest::Client::new();
+    client
+        .post(format!("{}/api/generate", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({ "model": model, "keep_alive": 0 }))
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// A chat's keep-alive policy, when one is set.
+pub(crate) fn chat_keep_alive(db: &Db, chat_id: &str) -> Option<String> {
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT keep_alive FROM chats WHERE id = ?1",
+        [chat_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .ok()
+    .flatten()
+}
+
+/// Warm the model a chat uses, under the chat's keep-alive policy.
+/// Called by the frontend when a chat is opened so the model is loaded
+/// before the first message is sent.
+#[tauri::command]
+pub async fn warm_chat_model(db: State<'_, Db>, chat_id: String) -> AppResult<()> {
+    let (model, keep_alive) = {
+        let conn = db.conn();
+        conn.query_row(
+            "SELECT model, keep_alive FROM chats WHERE id = ?1",
+            [&chat_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                ))
+            },
+        )?
+    };
+    warm_model(model, keep_alive).await
+}
+
+/// Fetch `/api/show` details for a model (parameters, template, model_info).
+#[tauri::command]
+pub async fn get_model_details(model: String) -> AppResult<Value> {
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{}/api/show", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({ "name": model }))
+        .send()
+        .await?;
+    Ok(resp.json().await?)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelFit {
+    /// `fits`, `marginal` or `wont_fit`.
+    pub verdict: String,
+    pub estimated_bytes: u64,
+    pub available_memory: u64,
+    pub total_memory: u64,
+    pub parameter_count: Option<u64>,
+    pub quantization: Option<String>,
+    /// Smaller quantizations of the same model that would fit.
+    pub suggestions: Vec<String>,
+}
+
+/// Parameter count hinted by the model tag, in raw parameters
+/// (`llama3:8b` → 8e9). Looks for a `<number>b` token in the tag.
+fn params_from_name(name: &str) -> Option<u64> {
+    name.to_lowercase()
+        .split(&[':', '-', '_'][..])
+        .find_map(|part| {
+            let digits = part.strip_suffix('b')?;
+            let value: f64 = digits.parse().ok()?;
+            (value > 0.0 && value < 2000.0).then_some((value * 1e9) as u64)
+        })
+}
+
+/// Quantization hinted by the model tag (`q4_K_M`, `q8_0`, `fp16`, ...).
+fn quant_from_name(name: &str) -> Option<String> {
+    let lower = name.to_lowercase();
+    lower
+        .split(&[':', '-'][..])
+        .find(|part| {
+            let mut chars = part.chars();
+            chars.next() == Some('q') && chars.next().is_some_and(|c| c.is_ascii_digit())
+        })
+        .or_else(|| ["fp16", "bf16", "f16"].into_iter().find(|q| lower.contains(q)))
+        .map(str::to_string)
+}
+
+/// Bytes per parameter for a quantization level, including a margin for
+/// KV cache and runtime overhead.
+fn bytes_per_param(quantization: Option<&str>) -> f64 {
+    let base = match quantization {
+        Some(q) if q.starts_with("q2") => 0.35,
+        Some(q) if q.starts_with("q3") => 0.45,
+        Some(q) if q.starts_with("q4") => 0.60,
+        Some(q) if q.starts_with("q5") => 0.70,
+        Some(q) if q.starts_with("q6") => 0.85,
+        Some(q) if q.starts_with("q8") => 1.1,
+        Some("fp16") | Some("f16") | Some("bf16") => 2.0,
+        // Ollama defaults to ~q4 quantized weights.
+        _ => 0.60,
+    };
+    base * 1.15
+}
+
+fn estimate_bytes(parameter_count: u64, quantization: Option<&str>) -> u64 {
+    (parameter_count as f64 * bytes_per_param(quantization)) as u64
+}
+
+/// Will this model fit in memory? Uses `/api/show` metadata when the
+/// model is already local, otherwise falls back to parsing the tag, and
+/// compares the estimate against current memory from the system
+/// monitor's source of truth.
+#[tauri::command]
+pub async fn check_model_fit(model: String) -> AppResult<ModelFit> {
+    let details = get_model_details(model.clone()).await.ok();
+    let parameter_count = details
+        .as_ref()
+        .and_then(|d| d.pointer("/model_info/general.parameter_count"))
+        .and_then(Value::as_u64)
+        .or_else(|| params_from_name(&model));
+    let quantization = details
+        .as_ref()
+        .and_then(|d| d.pointer("/details/quantization_level"))
+        .and_then(Value::as_str)
+        .map(|q| q.to_lowercase())
+        .or_else(|| quant_from_name(&model));
+    let info = crate::monitor::get_system_info();
+    let Some(parameter_count) = parameter_count else {
+        return Err(AppError::InvalidInput(format!(
+            "cannot estimate size of {}: no parameter count in metadata or tag",
+            model
+        )));
+    };
+    let estimated_bytes = estimate_bytes(parameter_count, quantization.as_deref());
+    let verdict = if estimated_bytes <= info.available_memory * 8 / 10 {
+        "fits"
+    } else if estimated_bytes <= info.total_memory {
+        "marginal"
+    } else {
+        "wont_fit"
+    };
+    let base = model.split(':').next().unwrap_or(&model);
+    let suggestions = ["q4_K_M", "q3_K_M", "q2_K"]
+        .into_iter()
+        .filter(|q| {
+            Some(q.to_lowercase()) != quantization
+                && estimate_bytes(parameter_count, Some(&q.to_lowercase()))
+                    <= info.available_memory * 8 / 10
+        })
+        .map(|q| format!("{}:{}", base, q))
+        .collect();
+    Ok(ModelFit {
+        verdict: verdict.to_string(),
+        estimated_bytes,
+        available_memory: info.available_memory,
+        total_memory: info.total_memory,
+        parameter_count: Some(parameter_count),
+        quantization,
+        suggestions,
+    })
+}
+
+/// Tag-only fit verdict for models that are not local yet (library
+/// browsing), reusing the same estimator as `check_model_fit`. `None`
+/// when the tag carries no parameter hint.
+pub(crate) fn quick_fit_verdict(model: &str) -> Option<String> {
+    let parameter_count = params_from_name(model)?;
+    let estimated = estimate_bytes(parameter_count, quant_from_name(model).as_deref());
+    let info = crate::monitor::get_system_info();
+    let verdict = if estimated <= info.available_memory * 8 / 10 {
+        "fits"
+    } else if estimated <= info.total_memory {
+        "marginal"
+    } else {
+        "wont_fit"
+    };
+    Some(verdict.to_string())
+}
+
+/// Pull the context window out of `/api/show` output: an explicit
+/// `num_ctx` parameter wins, otherwise the architecture's
+/// `<arch>.context_length` from `model_info`.
+pub fn parse_context_window(details: &Value) -> Option<usize> {
+    if let Some(parameters) = details.get("parameters").and_then(Value::as_str) {
+        for line in parameters.lines() {
+            let mut parts = line.split_whitespace();
+            if parts.next() == Some("num_ctx") {
+                if let Some(value) = parts.next().and_then(|v| v.parse::<usize>().ok()) {
+                    return Some(value);
+                }
+            }
+        }
+    }
+    let model_info = details.get("model_info")?.as_object()?;
+    model_info
+        .iter()
+        .find(|(key, _)| key.ends_with(".context_length"))
+        .and_then(|(_, value)| value.as_u64())
+        .map(|v| v as usize)
+}
+
+/// Context window for a model, preferring (in order) the cached value
+/// detected earlier, live detection via `/api/show`, and finally the
+/// hardcoded defaults. Detection results are cached in the DB.
+pub async fn detect_context_window(db: &Db, model: &str) -> usize {
+    {
+        let conn = db.conn();
+        let cached: Option<usize> = conn
+            .query_row(
+                "SELECT context_window FROM model_configs WHERE model = ?1",
+                params![model],
+                |row| row.get::<_, i64>(0),
+            )
+            .ok()
+            .map(|v| v as usize);
+        if let Some(window) = cached {
+            return window;
+        }
+    }
+    if let Ok(details) = get_model_details(model.to_string()).await {
+        if let Some(window) = parse_context_window(&details) {
+            let conn = db.conn();
+            let _ = conn.execute(
+                "INSERT INTO model_configs (model, context_window, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(model) DO UPDATE SET
+                     context_window = excluded.context_window,
+                     updated_at = excluded.updated_at",
+                params![model, window as i64, crate::db::now()],
+            );
+            return window;
+        }
+    }
+    ModelConfig::get_default_config(model).context_window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimate_bytes, params_from_name, parse_context_window, quant_from_name};
+    use serde_json::json;
+
+    #[test]
+    fn model_size_from_tag() {
+        assert_eq!(params_from_name("llama3:8b"), Some(8_000_000_000));
+        assert_eq!(params_from_name("mistral:7b-instruct"), Some(7_000_000_000));
+        assert_eq!(params_from_name("nomic-embed-text"), None);
+    }
+
+    #[test]
+    fn quantization_from_tag() {
+        assert_eq!(
+            quant_from_name("llama3:8b-instruct-q4_K_M"),
+            Some("q4_k_m".to_string())
+        );
+        assert_eq!(quant_from_name("llama3:8b-fp16"), Some("fp16".to_string()));
+        assert_eq!(quant_from_name("llama3:8b"), None);
+    }
+
+    #[test]
+    fn lower_quantization_estimates_smaller() {
+        let q8 = estimate_bytes(8_000_000_000, Some("q8_0"));
+        let q4 = estimate_bytes(8_000_000_000, Some("q4_k_m"));
+        let q2 = estimate_bytes(8_000_000_000, Some("q2_k"));
+        assert!(q2 < q4 && q4 < q8);
+    }
+
+    #[test]
+    fn num_ctx_parameter_wins_over_model_info() {
+        let details = json!({
+            "parameters": "num_ctx 16384\ntemperature 0.7",
+            "model_info": { "llama.context_length": 8192 }
+        });
+        assert_eq!(parse_context_window(&details), Some(16384));
+    }
+
+    #[test]
+    fn falls_back_to_architecture_context_length() {
+        let details = json!({
+            "model_info": { "qwen2.context_length": 32768, "qwen2.embedding_length": 3584 }
+        });
+        assert_eq!(parse_context_window(&details), Some(32768));
+    }
+
+    #[test]
+    fn missing_everywhere_is_none() {
+        assert_eq!(parse_context_window(&json!({"license": "MIT"})), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! First-run onboarding backend. `detect_environment` reports whether
+//! Ollama is installed and running, how much disk is free and a
+//! hardware summary, so the frontend can walk a new user through setup
+//! instead of failing with bare connection errors.
+//! `install_recommended_model` pulls a starter model sized to the
+//! machine through the normal pull pipeline (with its progress events),
+//! and completion is persisted in settings so the flow runs once.
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::monitor::{self, SystemInfo};
+use crate::ollama::{self, OLLAMA_BASE_URL};
+use crate::profiles;
+use crate::settings;
+
+/// Settings key: "true" once the user has finished (or skipped) the flow.
+pub const COMPLETED_KEY: &str = "onboarding_completed";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Environment {
+    /// The `ollama` binary is on PATH.
+    pub ollama_installed: bool,
+    /// The Ollama server answered `/api/version`.
+    pub ollama_running: bool,
+    pub ollama_version: Option<String>,
+    /// Free space on the disk holding the app's data directory.
+    pub available_disk: u64,
+    pub hardware: SystemInfo,
+    pub installed_models: usize,
+    pub onboarding_completed: bool,
+    /// The starter profile this machine's memory suggests.
+    pub recommended_profile: String,
+}
+
+/// Starter profile for a machine with this much total memory, in bytes.
+pub fn profile_for_memory(total_memory: u64) -> &'static str {
+    const GIB: u64 = 1024 * 1024 * 1024;
+    if total_memory < 8 * GIB {
+        "minimal"
+    } else if total_memory < 16 * GIB {
+        "balanced"
+    } else {
+        "capable"
+    }
+}
+
+/// The starter model each profile installs.
+pub fn recommended_model(profile: &str) -> Option<&'static str> {
+    match profile {
+        "minimal" => Some("llama3.2:1b"),
+        "balanced" => Some("llama3.2:3b"),
+        "capable" => Some("llama3.1:8b"),
+        _ => None,
+    }
+}
+
+fn ollama_installed() -> bool {
+    std::process::Command::new("ollama")
+        .arg("--version")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Free space on the disk whose mount point contains `path`, judged by
+/// the longest matching prefix.
+fn available_disk(path: &std::path::Path) -> u64 {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+        .or_else(|| disks.iter().map(|disk| disk.available_space()).max())
+        .unwrap_or(0)
+}
+
+/// Everything the first-run flow needs to decide what to show: Ollama
+/// state, disk headroom, hardware and whether onboarding already ran.
+#[tauri::command]
+pub async fn detect_environment(app: AppHandle, db: State<'_, Db>) -> AppResult<Environment> {
+    let hardware = monitor::get_system_info();
+    let ollama_version = match reqwest::get(format!("{}/api/version", OLLAMA_BASE_URL)).await {
+        Ok(resp) => resp
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|v| v.get("version").and_then(serde_json::Value::as_str).map(str::to_string)),
+        Err(_) => None,
+    };
+    let ollama_running = ollama_version.is_some();
+    let installed_models = if ollama_running {
+        ollama::list_models().await.map(|m| m.len()).unwrap_or(0)
+    } else {
+        0
+    };
+    let data_dir = profiles::data_dir(&app).map_err(AppError::Internal)?;
+    Ok(Environment {
+        ollama_installed: ollama_installed() || ollama_running,
+        ollama_running,
+        ollama_version,
+        available_disk: available_disk(&data_dir),
+        recommended_profile: profile_for_memory(hardware.total_memory).to_string(),
+        hardware,
+        installed_models,
+        onboarding_completed: settings::get(&db, COMPLETED_KEY).as_deref() == Some("true"),
+    })
+}
+
+/// Pull the starter model for `profile` through the normal pull
+/// pipeline, so progress streams as `pull-progress` events and the pull
+/// can be paused or cancelled like any other. Returns the model name.
+#[tauri::command]
+pub async fn install_recommended_model(app: AppHandle, profile: String) -> AppResult<String> {
+    let model = recommended_model(&profile).ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "unknown onboarding profile: {} (expected minimal, balanced or capable)",
+            profile
+        ))
+    })?;
+    ollama::pull_model(app, model.to_string()).await?;
+    Ok(model.to_string())
+}
+
+/// Mark the flow as done so it never shows again.
+#[tauri::command]
+pub fn complete_onboarding(db: State<Db>) -> AppResult<()> {
+    settings::set(&db, COMPLETED_KEY, "true")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{profile_for_memory, recommended_model};
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn profiles_scale_with_memory() {
+        assert_eq!(profile_for_memory(4 * GIB), "minimal");
+        assert_eq!(profile_for_memory(8 * GIB), "balanced");
+        assert_eq!(profile_for_memory(32 * GIB), "capable");
+    }
+
+    #[test]
+    fn every_profile_has_a_starter_model() {
+        for profile in ["minimal", "balanced", "capable"] {
+            assert!(recommended_model(profile).is_some());
+        }
+        assert!(recommended_model("enormous").is_none());
+    }
+}
@@ -0,0 +1,90 @@
+//! Unified progress channel for long-running work. Features register an
+//! operation (model pull, document ingestion, batch job, bundle export),
+//! push progress through it and finish it; every change goes out on one
+//! `operation-update` event and the current set is queryable via
+//! `list_active_operations`, so the title bar has a single source of
+//! truth instead of one ad-hoc event stream per feature.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Operation {
+    pub id: String,
+    /// Feature identifier: `model_pull`, `ingest`, `batch`, `diagnostics`.
+    pub kind: String,
+    /// Human-readable label for the status UI.
+    pub label: String,
+    /// Completion in `0.0..=1.0` when the total is known.
+    pub progress: Option<f64>,
+    /// `running`, `done` or `failed`.
+    pub status: String,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Default)]
+pub struct ActiveOperations(Mutex<HashMap<String, Operation>>);
+
+fn emit_update(app: &AppHandle, operation: &Operation) {
+    let _ = app.emit("operation-update", operation);
+}
+
+/// Register a new running operation and return its id.
+pub fn start(app: &AppHandle, kind: &str, label: &str) -> String {
+    let now = crate::db::now();
+    let operation = Operation {
+        id: Uuid::new_v4().to_string(),
+        kind: kind.to_string(),
+        label: label.to_string(),
+        progress: None,
+        status: "running".to_string(),
+        started_at: now.clone(),
+        updated_at: now,
+    };
+    let id = operation.id.clone();
+    emit_update(app, &operation);
+    app.state::<ActiveOperations>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(id.clone(), operation);
+    id
+}
+
+/// Update an operation's progress (clamped to `0.0..=1.0`).
+pub fn progress(app: &AppHandle, id: &str, progress: f64) {
+    let operations = app.state::<ActiveOperations>();
+    let mut operations = operations.0.lock().unwrap();
+    if let Some(operation) = operations.get_mut(id) {
+        operation.progress = Some(progress.clamp(0.0, 1.0));
+        operation.updated_at = crate::db::now();
+        emit_update(app, operation);
+    }
+}
+
+/// Mark an operation finished (`done` or `failed`) and drop it from the
+/// active set. The terminal state still goes out on `operation-update`.
+pub fn finish(app: &AppHandle, id: &str, status: &str) {
+    let operations = app.state::<ActiveOperations>();
+    let removed = operations.0.lock().unwrap().remove(id);
+    if let Some(mut operation) = removed {
+        operation.status = status.to_string();
+        operation.updated_at = crate::db::now();
+        if status == "done" {
+            operation.progress = Some(1.0);
+        }
+        emit_update(app, &operation);
+    }
+}
+
+/// Everything currently running, oldest first.
+#[tauri::command]
+pub fn list_active_operations(operations: State<ActiveOperations>) -> Vec<Operation> {
+    let mut active: Vec<Operation> = operations.0.lock().unwrap().values().cloned().collect();
+    active.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    active
+}
@@ -0,0 +1,251 @@
+//! Per-tool permission prompts and audit logging for model-triggered
+//! tool use. Before a tool call dispatches, the grant table is
+//! consulted; without a standing grant the frontend is asked over a
+//! `permission-request` event and answers through the
+//! `respond_permission` command, choosing a scope: `once` (this call
+//! only), `chat` (this conversation) or `always`. Denials persist the
+//! same way. Every privileged call — allowed or not — lands in the
+//! tool_audit_log table.
+
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::error::{AppError, AppResult};
+
+/// How long a prompt waits for the user before counting as denied.
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolGrant {
+    pub id: String,
+    pub tool: String,
+    /// `chat` or `always` (`once` answers are never persisted).
+    pub scope: String,
+    pub chat_id: Option<String>,
+    /// `granted` or `denied`.
+    pub decision: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: String,
+    pub chat_id: Option<String>,
+    pub tool: String,
+    pub arguments: String,
+    /// `ok`, `error` or `denied`.
+    pub outcome: String,
+    pub detail: Option<String>,
+    pub created_at: String,
+}
+
+struct Answer {
+    granted: bool,
+    scope: String,
+}
+
+/// Prompts waiting on a frontend answer, keyed by request id.
+#[derive(Default)]
+pub struct PendingPermissions(Mutex<HashMap<String, oneshot::Sender<Answer>>>);
+
+/// A standing decision for this tool, if one exists. Chat-scoped grants
+/// only count inside their chat; `always` rows win over `chat` rows.
+fn standing_decision(db: &Db, tool: &str, chat_id: &str) -> Option<bool> {
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT decision FROM tool_grants
+         WHERE tool = ?1 AND (scope = 'always' OR (scope = 'chat' AND chat_id = ?2))
+         ORDER BY CASE scope WHEN 'always' THEN 0 ELSE 1 END, created_at DESC
+         LIMIT 1",
+        params![tool, chat_id],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+    .map(|decision| decision == "granted")
+}
+
+fn persist_grant(db: &Db, tool: &str, scope: &str, chat_id: &str, granted: bool) {
+    let conn = db.conn();
+    let _ = conn.execute(
+        "INSERT INTO tool_grants (id, tool, scope, chat_id, decision, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            Uuid::new_v4().to_string(),
+            tool,
+            scope,
+            (scope == "chat").then(|| chat_id.to_string()),
+            if granted { "granted" } else { "denied" },
+            db::now()
+        ],
+    );
+}
+
+/// Gate one model-triggered tool call. Returns `Ok(())` when allowed;
+/// a denial (standing, answered, or prompt timeout) returns an error
+/// string that rides back to the model as the tool result.
+pub(crate) async fn ensure_tool_allowed(
+    app: &AppHandle,
+    db: &Db,
+    chat_id: &str,
+    tool: &str,
+    arguments: &Value,
+) -> Result<(), String> {
+    if let Some(granted) = standing_decision(db, tool, chat_id) {
+        return if granted {
+            Ok(())
+        } else {
+            Err(format!("permission for {} was denied", tool))
+        };
+    }
+    let request_id = Uuid::new_v4().to_string();
+    let (tx, rx) = oneshot::channel();
+    app.state::<PendingPermissions>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(request_id.clone(), tx);
+    let _ = app.emit(
+        "permission-request",
+        serde_json::json!({
+            "request_id": request_id,
+            "tool": tool,
+            "chat_id": chat_id,
+            "arguments": arguments,
+        }),
+    );
+    let answer = match tokio::time::timeout(PROMPT_TIMEOUT, rx).await {
+        Ok(Ok(answer)) => answer,
+        // Timed out or the sender was dropped: treat as a one-off
+        // denial, persisting nothing.
+        _ => {
+            app.state::<PendingPermissions>()
+                .0
+                .lock()
+                .unwrap()
+                .remove(&request_id);
+            return Err(format!("permission prompt for {} was not answered", tool));
+        }
+    };
+    if answer.scope == "chat" || answer.scope == "always" {
+        persist_grant(db, tool, &answer.scope, chat_id, answer.granted);
+    }
+    if answer.granted {
+        Ok(())
+    } else {
+        Err(format!("permission for {} was denied", tool))
+    }
+}
+
+/// Record a privileged action in the audit log.
+pub(crate) fn audit(
+    db: &Db,
+    chat_id: Option<&str>,
+    tool: &str,
+    arguments: &Value,
+    outcome: &str,
+    detail: Option<&str>,
+) {
+    let conn = db.conn();
+    let _ = conn.execute(
+        "INSERT INTO tool_audit_log (id, chat_id, tool, arguments, outcome, detail, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            Uuid::new_v4().to_string(),
+            chat_id,
+            tool,
+            arguments.to_string(),
+            outcome,
+            detail,
+            db::now()
+        ],
+    );
+}
+
+/// Frontend answer to a `permission-request` event.
+#[tauri::command]
+pub fn respond_permission(
+    app: AppHandle,
+    request_id: String,
+    granted: bool,
+    scope: String,
+) -> AppResult<()> {
+    if !["once", "chat", "always"].contains(&scope.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "unknown permission scope: {}",
+            scope
+        )));
+    }
+    let sender = app
+        .state::<PendingPermissions>()
+        .0
+        .lock()
+        .unwrap()
+        .remove(&request_id)
+        .ok_or_else(|| {
+            AppError::NotFound(format!("no pending permission request {}", request_id))
+        })?;
+    let _ = sender.send(Answer { granted, scope });
+    Ok(())
+}
+
+/// Standing grants, newest first.
+#[tauri::command]
+pub fn get_tool_grants(db: State<Db>) -> AppResult<Vec<ToolGrant>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, tool, scope, chat_id, decision, created_at
+         FROM tool_grants ORDER BY created_at DESC",
+    )?;
+    let grants = stmt
+        .query_map([], |row| {
+            Ok(ToolGrant {
+                id: row.get(0)?,
+                tool: row.get(1)?,
+                scope: row.get(2)?,
+                chat_id: row.get(3)?,
+                decision: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(grants)
+}
+
+#[tauri::command]
+pub fn revoke_tool_grant(db: State<Db>, grant_id: String) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute("DELETE FROM tool_grants WHERE id = ?1", params![grant_id])?;
+    Ok(())
+}
+
+/// The most recent audit entries, newest first.
+#[tauri::command]
+pub fn get_tool_audit_log(db: State<Db>, limit: usize) -> AppResult<Vec<AuditEntry>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, chat_id, tool, arguments, outcome, detail, created_at
+         FROM tool_audit_log ORDER BY created_at DESC LIMIT ?1",
+    )?;
+    let entries = stmt
+        .query_map(params![limit], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                tool: row.get(2)?,
+                arguments: row.get(3)?,
+                outcome: row.get(4)?,
+                detail: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
@@ -0,0 +1,209 @@
+//! Personas: a named assistant setup (system prompt, model, parameter
+//! presets, linked workflow templates) plus "prompt pack" export/import
+//! so a teammate can reproduce the exact setup in one step.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::ollama;
+use crate::templates::{self, TemplateStep};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Persona {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub system_prompt: String,
+    pub model: String,
+    /// Generation options (temperature, top_p, …) as sent to Ollama.
+    pub options: Value,
+    /// Workflow templates that belong to this persona.
+    pub template_ids: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Everything needed to reproduce a persona on another machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaPack {
+    pub version: u32,
+    pub persona: Persona,
+    pub templates: Vec<templates::WorkflowTemplate>,
+    /// Models the persona depends on; missing ones are pulled on import.
+    pub required_models: Vec<String>,
+}
+
+fn row_to_persona(row: &rusqlite::Row) -> rusqlite::Result<Persona> {
+    let options: String = row.get(5)?;
+    let template_ids: String = row.get(6)?;
+    Ok(Persona {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        system_prompt: row.get(3)?,
+        model: row.get(4)?,
+        options: serde_json::from_str(&options).unwrap_or(Value::Null),
+        template_ids: serde_json::from_str(&template_ids).unwrap_or_default(),
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+const PERSONA_COLUMNS: &str =
+    "id, name, description, system_prompt, model, options, template_ids, created_at, updated_at";
+
+fn upsert(db: &Db, persona: &Persona) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO personas (id, name, description, system_prompt, model, options, template_ids, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+             name = excluded.name,
+             description = excluded.description,
+             system_prompt = excluded.system_prompt,
+             model = excluded.model,
+             options = excluded.options,
+             template_ids = excluded.template_ids,
+             updated_at = excluded.updated_at",
+        params![
+            persona.id,
+            persona.name,
+            persona.description,
+            persona.system_prompt,
+            persona.model,
+            serde_json::to_string(&persona.options).map_err(|e| e.to_string())?,
+            serde_json::to_string(&persona.template_ids).map_err(|e| e.to_string())?,
+            persona.created_at,
+            persona.updated_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn save_persona(
+    db: State<Db>,
+    id: Option<String>,
+    name: String,
+    description: String,
+    system_prompt: String,
+    model: String,
+    options: Value,
+    template_ids: Vec<String>,
+) -> Result<Persona, String> {
+    let persona = Persona {
+        id: id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+        name,
+        description,
+        system_prompt,
+        model,
+        options,
+        template_ids,
+        created_at: db::now(),
+        updated_at: db::now(),
+    };
+    upsert(&db, &persona)?;
+    Ok(persona)
+}
+
+#[tauri::command]
+pub fn get_personas(db: State<Db>) -> Result<Vec<Persona>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM personas ORDER BY name ASC",
+            PERSONA_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let personas = stmt
+        .query_map([], row_to_persona)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(personas)
+}
+
+#[tauri::command]
+pub fn delete_persona(db: State<Db>, persona_id: String) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute("DELETE FROM personas WHERE id = ?1", params![persona_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Bundle a persona with its templates and required models into a
+/// self-contained JSON pack.
+#[tauri::command]
+pub fn export_persona_pack(db: State<Db>, persona_id: String) -> Result<String, String> {
+    let persona = {
+        let conn = db.conn();
+        conn.query_row(
+            &format!("SELECT {} FROM personas WHERE id = ?1", PERSONA_COLUMNS),
+            params![persona_id],
+            row_to_persona,
+        )
+        .map_err(|e| e.to_string())?
+    };
+    let all_templates = templates::get_templates_internal(&db)?;
+    let bundled: Vec<_> = all_templates
+        .into_iter()
+        .filter(|t| persona.template_ids.contains(&t.id))
+        .collect();
+    let required_models = vec![persona.model.clone()];
+    let pack = PersonaPack {
+        version: 1,
+        persona,
+        templates: bundled,
+        required_models,
+    };
+    serde_json::to_string_pretty(&pack).map_err(|e| e.to_string())
+}
+
+/// Import a pack exported elsewhere: persona and templates are upserted
+/// under their original ids, and any required model not installed
+/// locally is pulled in the background (progress via pull-progress).
+#[tauri::command]
+pub async fn import_persona_pack(
+    app: AppHandle,
+    db: State<'_, Db>,
+    pack: String,
+) -> Result<Persona, String> {
+    let pack: PersonaPack = serde_json::from_str(&pack).map_err(|e| e.to_string())?;
+    if pack.version != 1 {
+        return Err(format!("unsupported persona pack version {}", pack.version));
+    }
+    for template in &pack.templates {
+        let steps: Vec<TemplateStep> = template.steps.clone();
+        templates::save_template_internal(
+            &db,
+            &template.id,
+            &template.name,
+            &template.description,
+            &steps,
+        )?;
+    }
+    upsert(&db, &pack.persona)?;
+
+    let installed: Vec<String> = ollama::list_models()
+        .await
+        .map(|models| models.into_iter().map(|m| m.name).collect())
+        .unwrap_or_default();
+    for model in pack
+        .required_models
+        .iter()
+        .filter(|m| !installed.contains(m))
+        .cloned()
+    {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = ollama::pull_model(app, model).await;
+        });
+    }
+    Ok(pack.persona)
+}
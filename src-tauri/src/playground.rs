@@ -0,0 +1,192 @@
+//! Playground mode: run one prompt across a grid of parameter values
+//! (temperature × top_p, optionally across models) and collect outputs
+//! plus timing metrics into a stored comparison artifact.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::ollama::OLLAMA_BASE_URL;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepRequest {
+    pub prompt: String,
+    pub models: Vec<String>,
+    pub temperatures: Vec<f64>,
+    pub top_ps: Vec<f64>,
+}
+
+/// One cell of the comparison table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepCell {
+    pub model: String,
+    pub temperature: f64,
+    pub top_p: f64,
+    pub output: String,
+    pub eval_tokens: Option<u64>,
+    pub tokens_per_second: Option<f64>,
+    pub total_duration_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepResult {
+    pub id: String,
+    pub prompt: String,
+    pub cells: Vec<SweepCell>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepProgress {
+    pub sweep_id: String,
+    pub completed: usize,
+    pub total: usize,
+}
+
+async fn run_cell(prompt: &str, model: &str, temperature: f64, top_p: f64) -> SweepCell {
+    let client = reqwest::Client::new();
+    let payload = serde_json::json!({
+        "model": model,
+        "prompt": prompt,
+        "stream": false,
+        "options": { "temperature": temperature, "top_p": top_p },
+    });
+    let mut cell = SweepCell {
+        model: model.to_string(),
+        temperature,
+        top_p,
+        output: String::new(),
+        eval_tokens: None,
+        tokens_per_second: None,
+        total_duration_ms: None,
+        error: None,
+    };
+    let response: Result<Value, String> = async {
+        client
+            .post(format!("{}/api/generate", OLLAMA_BASE_URL))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())
+    }
+    .await;
+    match response {
+        Ok(value) => {
+            cell.output = value
+                .get("response")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            cell.eval_tokens = value.get("eval_count").and_then(Value::as_u64);
+            let eval_duration_ns = value.get("eval_duration").and_then(Value::as_u64);
+            cell.total_duration_ms = value
+                .get("total_duration")
+                .and_then(Value::as_u64)
+                .map(|ns| ns / 1_000_000);
+            if let (Some(tokens), Some(ns)) = (cell.eval_tokens, eval_duration_ns) {
+                if ns > 0 {
+                    cell.tokens_per_second = Some(tokens as f64 / (ns as f64 / 1e9));
+                }
+            }
+        }
+        Err(e) => cell.error = Some(e),
+    }
+    cell
+}
+
+/// Run the full parameter grid sequentially (local models fight for the
+/// same GPU anyway) and persist the comparison table.
+#[tauri::command]
+pub async fn run_parameter_sweep(
+    app: AppHandle,
+    db: State<'_, Db>,
+    request: SweepRequest,
+) -> Result<SweepResult, String> {
+    if request.models.is_empty() || request.temperatures.is_empty() || request.top_ps.is_empty() {
+        return Err("sweep needs at least one model, temperature and top_p".to_string());
+    }
+    let sweep_id = Uuid::new_v4().to_string();
+    let total = request.models.len() * request.temperatures.len() * request.top_ps.len();
+    let mut cells = Vec::with_capacity(total);
+    for model in &request.models {
+        for &temperature in &request.temperatures {
+            for &top_p in &request.top_ps {
+                cells.push(run_cell(&request.prompt, model, temperature, top_p).await);
+                let _ = app.emit(
+                    "sweep-progress",
+                    &SweepProgress {
+                        sweep_id: sweep_id.clone(),
+                        completed: cells.len(),
+                        total,
+                    },
+                );
+            }
+        }
+    }
+    let result = SweepResult {
+        id: sweep_id,
+        prompt: request.prompt,
+        cells,
+        created_at: db::now(),
+    };
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO playground_sweeps (id, prompt, results, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            result.id,
+            result.prompt,
+            serde_json::to_string(&result.cells).map_err(|e| e.to_string())?,
+            result.created_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn get_sweeps(db: State<Db>) -> Result<Vec<SweepResult>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare("SELECT id, prompt, results, created_at FROM playground_sweeps ORDER BY created_at DESC")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    rows.into_iter()
+        .map(|(id, prompt, results, created_at)| {
+            Ok(SweepResult {
+                id,
+                prompt,
+                cells: serde_json::from_str(&results).map_err(|e| e.to_string())?,
+                created_at,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn delete_sweep(db: State<Db>, sweep_id: String) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute(
+        "DELETE FROM playground_sweeps WHERE id = ?1",
+        params![sweep_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,360 @@
+//! WASM plugin host. Modules dropped into `<app data>/plugins/*.wasm`
+//! can declare custom tools that join the tool-calling loop alongside
+//! MCP tools. Plugins run without WASI — pure compute over the ABI
+//! below — and any capabilities they declare (`network`, `filesystem`)
+//! must be granted per plugin before their tools become callable.
+//!
+//! Guest ABI: the module exports `memory`, `alloc(len: i32) -> i32`,
+//! `describe() -> i64` and `invoke(ptr: i32, len: i32) -> i64`. The
+//! i64 returns pack pointer and length (`ptr << 32 | len`) of a JSON
+//! byte string in guest memory. `describe` yields a manifest
+//! `{ name, capabilities: [...], tools: [{ name, description,
+//! parameters }] }` where `parameters` is a JSON schema; `invoke`
+//! receives `{ tool, arguments }` and returns the tool result.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, State};
+use wasmtime::{Engine, Instance, Module, Store};
+
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::settings;
+
+const CAPABILITIES: [&str; 2] = ["network", "filesystem"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginTool {
+    pub name: String,
+    pub description: String,
+    /// JSON schema for the tool's arguments.
+    pub parameters: Value,
+}
+
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    pub capabilities: Vec<String>,
+    pub tools: Vec<PluginTool>,
+    module: Module,
+    engine: Engine,
+}
+
+#[derive(Default)]
+pub struct PluginState(pub Mutex<HashMap<String, Arc<Plugin>>>);
+
+/// Serializable summary for the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub path: String,
+    pub capabilities: Vec<String>,
+    pub tools: Vec<PluginTool>,
+    pub granted: bool,
+}
+
+fn plugins_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?
+        .join("plugins");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn grant_key(plugin: &str, capability: &str) -> String {
+    format!("plugin_grant:{}:{}", plugin, capability)
+}
+
+/// True when every capability the plugin declares has been granted.
+fn is_granted(db: &Db, plugin: &Plugin) -> bool {
+    plugin
+        .capabilities
+        .iter()
+        .all(|cap| settings::get(db, &grant_key(&plugin.name, cap)).as_deref() == Some("granted"))
+}
+
+/// Read the packed `ptr << 32 | len` return convention out of guest
+/// memory.
+fn read_packed(store: &mut Store<()>, instance: &Instance, packed: i64) -> Result<Vec<u8>, String> {
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or("plugin exports no memory")?;
+    let ptr = (packed >> 32) as usize;
+    let len = (packed & 0xFFFF_FFFF) as usize;
+    let mut buffer = vec![0u8; len];
+    memory
+        .read(&mut *store, ptr, &mut buffer)
+        .map_err(|e| e.to_string())?;
+    Ok(buffer)
+}
+
+fn call_guest(
+    engine: &Engine,
+    module: &Module,
+    export: &str,
+    input: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let mut store = Store::new(engine, ());
+    let instance = Instance::new(&mut store, module, &[]).map_err(|e| e.to_string())?;
+    let packed = match input {
+        None => {
+            let describe = instance
+                .get_typed_func::<(), i64>(&mut store, export)
+                .map_err(|e| e.to_string())?;
+            describe.call(&mut store, ()).map_err(|e| e.to_string())?
+        }
+        Some(input) => {
+            let alloc = instance
+                .get_typed_func::<i32, i32>(&mut store, "alloc")
+                .map_err(|e| e.to_string())?;
+            let ptr = alloc
+                .call(&mut store, input.len() as i32)
+                .map_err(|e| e.to_string())?;
+            let memory = instance
+                .get_memory(&mut store, "memory")
+                .ok_or("plugin exports no memory")?;
+            memory
+                .write(&mut store, ptr as usize, input)
+                .map_err(|e| e.to_string())?;
+            let func = instance
+                .get_typed_func::<(i32, i32), i64>(&mut store, export)
+                .map_err(|e| e.to_string())?;
+            func.call(&mut store, (ptr, input.len() as i32))
+                .map_err(|e| e.to_string())?
+        }
+    };
+    read_packed(&mut store, &instance, packed)
+}
+
+/// Parse a `describe` manifest into name, capabilities and tools.
+pub fn parse_manifest(manifest: &Value) -> Result<(String, Vec<String>, Vec<PluginTool>), String> {
+    let name = manifest
+        .get("name")
+        .and_then(Value::as_str)
+        .filter(|n| !n.is_empty())
+        .ok_or("plugin manifest has no name")?
+        .to_string();
+    let capabilities: Vec<String> = manifest
+        .get("capabilities")
+        .and_then(Value::as_array)
+        .map(|caps| {
+            caps.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(unknown) = capabilities
+        .iter()
+        .find(|cap| !CAPABILITIES.contains(&cap.as_str()))
+    {
+        return Err(format!("unknown plugin capability: {}", unknown));
+    }
+    let tools = manifest
+        .get("tools")
+        .and_then(Value::as_array)
+        .ok_or("plugin manifest has no tools")?
+        .iter()
+        .map(|tool| {
+            Ok(PluginTool {
+                name: tool
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .filter(|n| !n.is_empty())
+                    .ok_or("plugin tool has no name")?
+                    .to_string(),
+                description: tool
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                parameters: tool
+                    .get("parameters")
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::json!({ "type": "object" })),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok((name, capabilities, tools))
+}
+
+fn load_module(path: &PathBuf) -> Result<Arc<Plugin>, String> {
+    let engine = Engine::default();
+    let module = Module::from_file(&engine, path).map_err(|e| e.to_string())?;
+    let raw = call_guest(&engine, &module, "describe", None)?;
+    let manifest: Value =
+        serde_json::from_slice(&raw).map_err(|e| format!("bad plugin manifest: {}", e))?;
+    let (name, capabilities, tools) = parse_manifest(&manifest)?;
+    Ok(Arc::new(Plugin {
+        name,
+        path: path.clone(),
+        capabilities,
+        tools,
+        module,
+        engine,
+    }))
+}
+
+fn info(db: &Db, plugin: &Plugin) -> PluginInfo {
+    PluginInfo {
+        name: plugin.name.clone(),
+        path: plugin.path.to_string_lossy().to_string(),
+        capabilities: plugin.capabilities.clone(),
+        tools: plugin.tools.clone(),
+        granted: is_granted(db, plugin),
+    }
+}
+
+/// Scan the plugins directory and (re)load every module. Plugins that
+/// declare ungranted capabilities emit a `plugin-permission-request`
+/// event so the frontend can prompt; their tools stay unavailable until
+/// granted.
+#[tauri::command]
+pub fn load_plugins(app: AppHandle, db: State<Db>) -> AppResult<Vec<PluginInfo>> {
+    let dir = plugins_dir(&app)?;
+    let state = app.state::<PluginState>();
+    let mut loaded = Vec::new();
+    state.0.lock().unwrap().clear();
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("wasm") {
+            continue;
+        }
+        match load_module(&path) {
+            Ok(plugin) => {
+                if !plugin.capabilities.is_empty() && !is_granted(&db, &plugin) {
+                    let _ = app.emit(
+                        "plugin-permission-request",
+                        serde_json::json!({
+                            "plugin": plugin.name,
+                            "capabilities": plugin.capabilities,
+                        }),
+                    );
+                }
+                loaded.push(info(&db, &plugin));
+                state
+                    .0
+                    .lock()
+                    .unwrap()
+                    .insert(plugin.name.clone(), plugin);
+            }
+            Err(e) => tracing::warn!("skipping plugin {}: {}", path.display(), e),
+        }
+    }
+    Ok(loaded)
+}
+
+#[tauri::command]
+pub fn list_plugins(app: AppHandle, db: State<Db>) -> Vec<PluginInfo> {
+    let state = app.state::<PluginState>();
+    let plugins = state.0.lock().unwrap();
+    plugins.values().map(|p| info(&db, p)).collect()
+}
+
+/// Record the user's answer to a capability prompt.
+#[tauri::command]
+pub fn set_plugin_permission(
+    db: State<Db>,
+    plugin: String,
+    capability: String,
+    granted: bool,
+) -> AppResult<()> {
+    if !CAPABILITIES.contains(&capability.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "unknown plugin capability: {}",
+            capability
+        )));
+    }
+    settings::set(
+        &db,
+        &grant_key(&plugin, &capability),
+        if granted { "granted" } else { "denied" },
+    )
+}
+
+/// Ollama tool specs for every callable plugin tool, namespaced
+/// `plugin__tool` like MCP's `server__tool` so dispatch routes back.
+pub(crate) fn ollama_tool_specs(app: &AppHandle) -> Vec<Value> {
+    let db = app.state::<Db>();
+    let state = app.state::<PluginState>();
+    let plugins = state.0.lock().unwrap();
+    let mut specs = Vec::new();
+    for plugin in plugins.values().filter(|p| is_granted(&db, p)) {
+        for tool in &plugin.tools {
+            specs.push(serde_json::json!({
+                "type": "function",
+                "function": {
+                    "name": format!("{}__{}", plugin.name, tool.name),
+                    "description": tool.description,
+                    "parameters": tool.parameters,
+                }
+            }));
+        }
+    }
+    specs
+}
+
+/// Execute one namespaced tool call if it belongs to a loaded plugin;
+/// `None` lets the caller fall through to MCP dispatch.
+pub(crate) async fn dispatch_tool_call(
+    app: &AppHandle,
+    name: &str,
+    arguments: Value,
+) -> Option<Result<String, String>> {
+    let (plugin_name, tool) = name.split_once("__")?;
+    let plugin = {
+        let state = app.state::<PluginState>();
+        let plugins = state.0.lock().unwrap();
+        plugins.get(plugin_name).cloned()?
+    };
+    if !plugin.tools.iter().any(|t| t.name == tool) {
+        return None;
+    }
+    {
+        let db = app.state::<Db>();
+        if !is_granted(&db, &plugin) {
+            return Some(Err(format!(
+                "plugin {} is waiting on capability grants: {}",
+                plugin.name,
+                plugin.capabilities.join(", ")
+            )));
+        }
+    }
+    let input = serde_json::json!({ "tool": tool, "arguments": arguments }).to_string();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        call_guest(&plugin.engine, &plugin.module, "invoke", Some(input.as_bytes()))
+            .and_then(|raw| String::from_utf8(raw).map_err(|e| e.to_string()))
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()));
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_manifest;
+
+    #[test]
+    fn manifests_parse_and_reject_unknown_capabilities() {
+        let manifest = serde_json::json!({
+            "name": "word-tools",
+            "capabilities": ["network"],
+            "tools": [{ "name": "count", "description": "Count words" }],
+        });
+        let (name, caps, tools) = parse_manifest(&manifest).unwrap();
+        assert_eq!(name, "word-tools");
+        assert_eq!(caps, vec!["network"]);
+        assert_eq!(tools[0].name, "count");
+        assert_eq!(tools[0].parameters["type"], "object");
+
+        let bad = serde_json::json!({
+            "name": "x", "capabilities": ["root"], "tools": [],
+        });
+        assert!(parse_manifest(&bad).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Response post-processing pipeline, applied once a generation is
+//! done. Processors are opted into via the `post_processors` setting (a
+//! comma-separated list): `fix_fences` closes dangling code fences,
+//! `format_code` runs rustfmt/prettier over code blocks when those
+//! tools are on PATH, and `strip_filler` drops the "Sure! Here's..."
+//! preamble line. The raw model output is kept in `raw_content`
+//! alongside the processed text, so nothing is lost.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::db::Db;
+use crate::settings;
+
+const SETTING_KEY: &str = "post_processors";
+
+/// Close an unterminated fenced code block: streaming cut-offs and
+/// models that forget the closing fence both leave an odd fence count,
+/// which breaks rendering of everything after the block.
+pub fn fix_fences(text: &str) -> String {
+    let fences = text.lines().filter(|l| l.trim_start().starts_with("```")).count();
+    if fences % 2 == 1 {
+        let mut fixed = text.trim_end().to_string();
+        fixed.push_str("\n```");
+        fixed
+    } else {
+        text.to_string()
+    }
+}
+
+/// Drop a boilerplate preamble line ("Sure! Here's the function you
+/// asked for:") when real content follows it. Only the first line is
+/// ever considered, and only when it clearly reads as filler.
+pub fn strip_filler(text: &str) -> String {
+    let Some((first, rest)) = text.split_once('\n') else {
+        return text.to_string();
+    };
+    let lowered = first.trim().to_lowercase();
+    let filler_start = [
+        "sure",
+        "certainly",
+        "of course",
+        "absolutely",
+        "great question",
+        "no problem",
+        "here's",
+        "here is",
+    ]
+    .iter()
+    .any(|p| lowered.starts_with(p));
+    let filler_end = lowered.ends_with(':') || lowered.ends_with('!') || lowered.ends_with('.');
+    if filler_start && filler_end && !rest.trim().is_empty() {
+        rest.trim_start_matches('\n').to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Pipe `input` through a formatter; `None` when the tool is missing or
+/// rejects the input, in which case the block stays as generated.
+fn run_formatter(program: &str, args: &[&str], input: &str) -> Option<String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.as_mut()?.write_all(input.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+fn format_block(lang: &str, code: &str) -> Option<String> {
+    match lang {
+        "rust" | "rs" => run_formatter("rustfmt", &["--edition", "2021"], code),
+        "js" | "javascript" => run_formatter("prettier", &["--stdin-filepath", "x.js"], code),
+        "ts" | "typescript" => run_formatter("prettier", &["--stdin-filepath", "x.ts"], code),
+        "json" => run_formatter("prettier", &["--stdin-filepath", "x.json"], code),
+        "css" => run_formatter("prettier", &["--stdin-filepath", "x.css"], code),
+        _ => None,
+    }
+}
+
+/// Reformat every fenced code block whose language has a formatter on
+/// PATH. Blocks the formatter cannot handle pass through untouched.
+pub fn format_code(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, segment) in text.split("```").enumerate() {
+        if i > 0 {
+            out.push_str("```");
+        }
+        if i % 2 == 0 {
+            out.push_str(segment);
+            continue;
+        }
+        let (lang, code) = match segment.split_once('\n') {
+            Some((lang, code)) => (lang.trim(), code),
+            None => ("", segment),
+        };
+        match format_block(lang, code) {
+            Some(formatted) => {
+                out.push_str(lang);
+                out.push('\n');
+                out.push_str(formatted.trim_end());
+                out.push('\n');
+            }
+            None => out.push_str(segment),
+        }
+    }
+    out
+}
+
+/// Names of the processors the user has enabled, in pipeline order.
+fn enabled(db: &Db) -> Vec<String> {
+    settings::get(db, SETTING_KEY)
+        .unwrap_or_default()
+        .split(',')
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Run the configured pipeline over a finished response. Returns the
+/// processed text and whether anything changed; fence fixing always
+/// runs before code formatting so formatters see complete blocks.
+pub(crate) fn apply(db: &Db, text: &str) -> (String, bool) {
+    let enabled = enabled(db);
+    let mut processed = text.to_string();
+    for processor in ["fix_fences", "format_code", "strip_filler"] {
+        if !enabled.iter().any(|p| p == processor) {
+            continue;
+        }
+        processed = match processor {
+            "fix_fences" => fix_fences(&processed),
+            "format_code" => format_code(&processed),
+            "strip_filler" => strip_filler(&processed),
+            _ => processed,
+        };
+    }
+    let changed = processed != text;
+    (processed, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fix_fences, strip_filler};
+
+    #[test]
+    fn dangling_fences_get_closed() {
+        let broken = "look:\n```rust\nfn main() {}";
+        assert_eq!(fix_fences(broken), "look:\n```rust\nfn main() {}\n```");
+        let fine = "```\nok\n```";
+        assert_eq!(fix_fences(fine), fine);
+    }
+
+    #[test]
+    fn filler_preambles_are_stripped_conservatively() {
+        assert_eq!(
+            strip_filler("Sure! Here's the code:\n\nfn main() {}"),
+            "fn main() {}"
+        );
+        // A real first line that happens to end with ':' but doesn't
+        // read as filler stays.
+        let kept = "The steps are:\n1. build\n2. test";
+        assert_eq!(strip_filler(kept), kept);
+        // Filler with nothing after it stays too.
+        assert_eq!(strip_filler("Sure!"), "Sure!");
+    }
+}
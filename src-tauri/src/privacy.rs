@@ -0,0 +1,228 @@
+//! Outbound content safety filter. Before an assembled context leaves
+//! the app for a model endpoint, it is scanned for emails, phone
+//! numbers and API keys/secrets (pattern plus entropy checks). The
+//! `outbound_filter_mode` setting picks the response: `off` (default,
+//! everything stays on localhost anyway), `redact` to scrub findings
+//! out of the payload, or `block` to refuse the request. Either way a
+//! warning event tells the UI what was found.
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::settings;
+
+/// Settings key: `off`, `redact` or `block`.
+pub const FILTER_MODE_KEY: &str = "outbound_filter_mode";
+
+const REDACTED: &str = "[REDACTED]";
+/// Tokens at least this long are candidates for the entropy check.
+const ENTROPY_MIN_LEN: usize = 24;
+/// Shannon entropy (bits per character) above which a long token is
+/// treated as a secret. English prose sits around 3; random base64
+/// above 4.
+const ENTROPY_THRESHOLD: f64 = 3.8;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub kind: String,
+    /// A safe excerpt: the first few characters only, never the value.
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboundWarning {
+    pub chat_id: String,
+    pub mode: String,
+    pub findings: Vec<Finding>,
+}
+
+fn patterns() -> Vec<(&'static str, Regex)> {
+    [
+        ("email", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+        ("phone", r"\+?\d[\d \-().]{7,}\d"),
+        (
+            "api_key",
+            r#"(?i)(api[_-]?key|secret|token|password)["':=\s]+\S{12,}"#,
+        ),
+    ]
+    .into_iter()
+    .map(|(kind, pattern)| (kind, Regex::new(pattern).expect("scrub pattern is valid")))
+    .collect()
+}
+
+/// Shannon entropy of `text` in bits per character.
+pub fn shannon_entropy(text: &str) -> f64 {
+    let mut counts = std::collections::HashMap::new();
+    let mut total = 0.0;
+    for c in text.chars() {
+        *counts.entry(c).or_insert(0.0) += 1.0;
+        total += 1.0;
+    }
+    if total == 0.0 {
+        return 0.0;
+    }
+    counts
+        .values()
+        .map(|count| {
+            let p = count / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn excerpt(value: &str) -> String {
+    let head: String = value.chars().take(4).collect();
+    format!("{}…", head)
+}
+
+/// Scan text for PII and secrets. High-entropy long tokens are caught
+/// even when no pattern matches, which is what finds random keys pasted
+/// without context.
+pub fn scan(text: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (kind, regex) in patterns() {
+        for m in regex.find_iter(text) {
+            findings.push(Finding {
+                kind: kind.to_string(),
+                excerpt: excerpt(m.as_str()),
+            });
+        }
+    }
+    for token in text.split_whitespace() {
+        if token.chars().count() >= ENTROPY_MIN_LEN
+            && token.chars().all(|c| !c.is_whitespace())
+            && shannon_entropy(token) >= ENTROPY_THRESHOLD
+        {
+            findings.push(Finding {
+                kind: "high_entropy".to_string(),
+                excerpt: excerpt(token),
+            });
+        }
+    }
+    findings
+}
+
+/// Replace everything `scan` would flag with `[REDACTED]`.
+pub fn redact(text: &str) -> String {
+    let mut out = text.to_string();
+    for (_, regex) in patterns() {
+        out = regex.replace_all(&out, REDACTED).into_owned();
+    }
+    // Tokenize on any whitespace, exactly like `scan`, keeping each
+    // delimiter so the surrounding text survives unchanged.
+    let mut scrubbed = String::with_capacity(out.len());
+    for segment in out.split_inclusive(char::is_whitespace) {
+        let token = segment.trim_end_matches(char::is_whitespace);
+        if token.chars().count() >= ENTROPY_MIN_LEN && shannon_entropy(token) >= ENTROPY_THRESHOLD {
+            scrubbed.push_str(REDACTED);
+        } else {
+            scrubbed.push_str(token);
+        }
+        scrubbed.push_str(&segment[token.len()..]);
+    }
+    scrubbed
+}
+
+/// Filter stage applied to an assembled chat payload just before it is
+/// sent. Under `redact` the message contents are scrubbed in place;
+/// under `block` the request fails with the findings. Both emit an
+/// `outbound-filter-warning` event.
+pub(crate) fn apply_outbound_filter(
+    app: &AppHandle,
+    db: &Db,
+    chat_id: &str,
+    payload: &mut Value,
+) -> AppResult<()> {
+    let mode = settings::get(db, FILTER_MODE_KEY).unwrap_or_else(|| "off".to_string());
+    if mode == "off" {
+        return Ok(());
+    }
+    let Some(messages) = payload["messages"].as_array_mut() else {
+        return Ok(());
+    };
+    let mut findings = Vec::new();
+    for message in messages.iter() {
+        if let Some(content) = message.get("content").and_then(Value::as_str) {
+            findings.extend(scan(content));
+        }
+    }
+    if findings.is_empty() {
+        return Ok(());
+    }
+    let count = findings.len();
+    let _ = app.emit(
+        "outbound-filter-warning",
+        &OutboundWarning {
+            chat_id: chat_id.to_string(),
+            mode: mode.clone(),
+            findings,
+        },
+    );
+    match mode.as_str() {
+        "block" => Err(AppError::InvalidInput(format!(
+            "request blocked by the outbound filter: {} sensitive item(s) in the context",
+            count
+        ))),
+        _ => {
+            for message in messages.iter_mut() {
+                if let Some(content) = message.get("content").and_then(Value::as_str) {
+                    message["content"] = Value::String(redact(content));
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact, scan, shannon_entropy};
+
+    #[test]
+    fn scan_finds_emails_phones_and_keys() {
+        let kinds: Vec<String> = scan(
+            "Mail me at jo@example.com or call +1 (555) 123-4567. \
+             api_key: abcdef123456789012",
+        )
+        .into_iter()
+        .map(|f| f.kind)
+        .collect();
+        assert!(kinds.contains(&"email".to_string()));
+        assert!(kinds.contains(&"phone".to_string()));
+        assert!(kinds.contains(&"api_key".to_string()));
+        assert!(scan("nothing sensitive here").is_empty());
+    }
+
+    #[test]
+    fn high_entropy_tokens_are_flagged_without_a_pattern() {
+        let findings = scan("paste: kJ8zQ2xW9vB4nM7rT5yH3cF6dL1pS0aG");
+        assert!(findings.iter().any(|f| f.kind == "high_entropy"));
+        // Long but repetitive strings are not secrets.
+        assert!(scan(&"ab".repeat(20)).is_empty());
+    }
+
+    #[test]
+    fn redact_scrubs_what_scan_flags() {
+        let out = redact("contact jo@example.com now");
+        assert!(!out.contains("jo@example.com"));
+        assert!(out.contains("[REDACTED]"));
+    }
+
+    #[test]
+    fn redact_catches_secrets_on_any_whitespace_boundary() {
+        let out = redact("token:\nkJ8zQ2xW9vB4nM7rT5yH3cF6dL1pS0aG\tdone");
+        assert!(!out.contains("kJ8zQ2xW9vB4nM7rT5yH3cF6dL1pS0aG"));
+        // Delimiters survive so the surrounding text is untouched.
+        assert_eq!(out, "token:\n[REDACTED]\tdone");
+    }
+
+    #[test]
+    fn entropy_orders_prose_below_random() {
+        assert!(shannon_entropy("aaaa") < 1.0);
+        assert!(shannon_entropy("kJ8zQ2xW9vB4nM7rT5yH3cF6dL1pS0aG") > 3.8);
+    }
+}
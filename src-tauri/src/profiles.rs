@@ -0,0 +1,152 @@
+//! Multi-profile support. Each profile owns an isolated database (and
+//! encryption sidecar) under its own data directory: the original app
+//! data directory for the `default` profile, `profiles/<name>/` for
+//! everything else. The active profile is recorded in `profile.json`
+//! and resolved before the database opens at startup.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::Db;
+
+const PROFILE_FILE: &str = "profile.json";
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProfileConfig {
+    active: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+    pub encrypted: bool,
+}
+
+fn root_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_data_dir().map_err(|e| e.to_string())
+}
+
+fn active_profile(root: &Path) -> String {
+    fs::read_to_string(root.join(PROFILE_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<ProfileConfig>(&raw).ok())
+        .map(|c| c.active)
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+fn set_active_profile(root: &Path, name: &str) -> Result<(), String> {
+    fs::create_dir_all(root).map_err(|e| e.to_string())?;
+    let config = ProfileConfig {
+        active: name.to_string(),
+    };
+    fs::write(
+        root.join(PROFILE_FILE),
+        serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn profile_dir(root: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        root.to_path_buf()
+    } else {
+        root.join("profiles").join(name)
+    }
+}
+
+/// Data directory of the active profile — where the database and the
+/// encryption sidecar live. Startup and the crypto commands resolve
+/// their paths through this.
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let root = root_dir(app)?;
+    let active = active_profile(&root);
+    Ok(profile_dir(&root, &active))
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    let root = root_dir(&app)?;
+    let active = active_profile(&root);
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    let profiles_dir = root.join("profiles");
+    if profiles_dir.is_dir() {
+        for entry in fs::read_dir(&profiles_dir).map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            if entry.path().is_dir() {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    Ok(names
+        .into_iter()
+        .map(|name| ProfileInfo {
+            active: name == active,
+            encrypted: crate::crypto::is_encrypted(&profile_dir(&root, &name)),
+            name,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn create_profile(app: AppHandle, name: String) -> Result<ProfileInfo, String> {
+    if !valid_name(&name) {
+        return Err("profile names are alphanumeric with - and _".to_string());
+    }
+    if name == DEFAULT_PROFILE {
+        return Err("the default profile already exists".to_string());
+    }
+    let root = root_dir(&app)?;
+    let dir = profile_dir(&root, &name);
+    if dir.exists() {
+        return Err(format!("profile {} already exists", name));
+    }
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(ProfileInfo {
+        name,
+        active: false,
+        encrypted: false,
+    })
+}
+
+/// Switch the managed database to another profile. Encrypted profiles
+/// cannot be opened without their passphrase, so for those the switch is
+/// recorded and takes effect on restart (the passphrase prompt comes up
+/// like any encrypted startup).
+#[tauri::command]
+pub fn switch_profile(
+    app: AppHandle,
+    db: State<Db>,
+    watchers: State<crate::watcher::WatcherState>,
+    name: String,
+) -> Result<(), String> {
+    let root = root_dir(&app)?;
+    let dir = profile_dir(&root, &name);
+    if !dir.is_dir() {
+        return Err(format!("no profile named {}", name));
+    }
+    set_active_profile(&root, &name)?;
+    if crate::crypto::is_encrypted(&dir) {
+        return Err("profile is encrypted; restart the app to unlock it".to_string());
+    }
+    db.switch_to(dir)?;
+    crate::templates::seed_builtins(&db)?;
+    // The old profile's folder watchers no longer apply.
+    watchers.0.lock().unwrap().clear();
+    crate::watcher::restore_watchers(&app)?;
+    let _ = app.emit("profile-switched", &name);
+    Ok(())
+}
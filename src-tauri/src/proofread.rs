@@ -0,0 +1,170 @@
+//! Spell/grammar assist: a small local model proposes corrections with
+//! constrained JSON output; offsets are recomputed locally so highlights
+//! are exact even when the model's own indices are off.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ollama::OLLAMA_BASE_URL;
+
+/// Small, fast model used for proofreading unless the caller overrides.
+const DEFAULT_PROOFREAD_MODEL: &str = "llama3.2:1b";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Correction {
+    pub original: String,
+    pub suggestion: String,
+    pub reason: String,
+    /// Byte range of `original` in the submitted text.
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Deserialize)]
+struct ModelCorrection {
+    original: String,
+    suggestion: String,
+    #[serde(default)]
+    reason: String,
+}
+
+#[derive(Deserialize)]
+struct ModelCorrections {
+    corrections: Vec<ModelCorrection>,
+}
+
+/// Locate each suggested correction in the original text. Repeated
+/// occurrences are matched left to right so duplicate typos each get
+/// their own offset.
+fn resolve_offsets(text: &str, corrections: Vec<ModelCorrection>) -> Vec<Correction> {
+    let mut cursor_by_needle: std::collections::HashMap<String, usize> =
+        std::collections::HashMap::new();
+    let mut resolved = Vec::new();
+    for correction in corrections {
+        if correction.original.is_empty() || correction.original == correction.suggestion {
+            continue;
+        }
+        let cursor = cursor_by_needle
+            .entry(correction.original.clone())
+            .or_insert(0);
+        if let Some(pos) = text[*cursor..].find(&correction.original) {
+            let start = *cursor + pos;
+            let end = start + correction.original.len();
+            *cursor = end;
+            resolved.push(Correction {
+                original: correction.original,
+                suggestion: correction.suggestion,
+                reason: correction.reason,
+                start,
+                end,
+            });
+        }
+    }
+    resolved
+}
+
+/// Proofread text with a small local model. Returns corrections with
+/// exact byte offsets; an empty list means nothing to fix.
+#[tauri::command]
+pub async fn proofread(text: String, model: Option<String>) -> Result<Vec<Correction>, String> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let model = model.unwrap_or_else(|| DEFAULT_PROOFREAD_MODEL.to_string());
+    let format = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "corrections": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "original": { "type": "string" },
+                        "suggestion": { "type": "string" },
+                        "reason": { "type": "string" }
+                    },
+                    "required": ["original", "suggestion"]
+                }
+            }
+        },
+        "required": ["corrections"]
+    });
+    let payload = serde_json::json!({
+        "model": model,
+        "messages": [
+            {
+                "role": "system",
+                "content": "You are a spelling and grammar checker. List only real \
+                            spelling or grammar mistakes in the user's text, with the \
+                            exact original fragment and your suggested replacement. \
+                            Do not rewrite style. Return an empty list if the text is fine."
+            },
+            { "role": "user", "content": text }
+        ],
+        "stream": false,
+        "format": format,
+    });
+    let client = reqwest::Client::new();
+    let response: Value = client
+        .post(format!("{}/api/chat", OLLAMA_BASE_URL))
+        .json(&payload)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    let content = response
+        .pointer("/message/content")
+        .and_then(Value::as_str)
+        .ok_or("malformed response from Ollama")?;
+    let parsed: ModelCorrections =
+        serde_json::from_str(content).map_err(|e| format!("model returned invalid JSON: {}", e))?;
+    Ok(resolve_offsets(&text, parsed.corrections))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model_correction(original: &str, suggestion: &str) -> ModelCorrection {
+        ModelCorrection {
+            original: original.to_string(),
+            suggestion: suggestion.to_string(),
+            reason: String::new(),
+        }
+    }
+
+    #[test]
+    fn offsets_point_at_the_original_fragment() {
+        let text = "Teh cat sat on teh mat";
+        let resolved = resolve_offsets(text, vec![model_correction("Teh", "The")]);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(&text[resolved[0].start..resolved[0].end], "Teh");
+    }
+
+    #[test]
+    fn repeated_typos_resolve_left_to_right() {
+        let text = "teh one and teh two";
+        let resolved = resolve_offsets(
+            text,
+            vec![model_correction("teh", "the"), model_correction("teh", "the")],
+        );
+        assert_eq!(resolved.len(), 2);
+        assert_eq!(resolved[0].start, 0);
+        assert_eq!(resolved[1].start, 12);
+    }
+
+    #[test]
+    fn no_op_and_unlocatable_corrections_are_dropped() {
+        let text = "all good here";
+        let resolved = resolve_offsets(
+            text,
+            vec![
+                model_correction("good", "good"),
+                model_correction("absent", "present"),
+            ],
+        );
+        assert!(resolved.is_empty());
+    }
+}
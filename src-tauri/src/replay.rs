@@ -0,0 +1,230 @@
+//! Deterministic conversation replay for debugging. `replay_chat`
+//! re-runs every user turn of an existing conversation against a
+//! (possibly different) model with a fixed seed, building a parallel
+//! replay chat and a per-turn diff against the original answers, so a
+//! new model or parameter set can be judged on real past conversations
+//! instead of synthetic prompts.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::chat::{self, Chat};
+use crate::compression;
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::ollama::OLLAMA_BASE_URL;
+
+/// Seed used when the caller doesn't pass one; any fixed value makes
+/// two replays of the same chat comparable.
+const DEFAULT_SEED: i64 = 42;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReplayParams {
+    pub seed: Option<i64>,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+}
+
+/// One user turn: the original answer next to the replayed one.
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnDiff {
+    pub turn: usize,
+    pub prompt: String,
+    pub original: String,
+    pub replayed: String,
+    /// Word-level Jaccard similarity between the two answers, 0–1.
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub source_chat_id: String,
+    pub replay_chat: Chat,
+    pub model: String,
+    pub seed: i64,
+    pub turns: Vec<TurnDiff>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ReplayProgress {
+    replay_chat_id: String,
+    completed: usize,
+    total: usize,
+}
+
+/// Jaccard similarity over lowercase word sets. Crude, but enough to
+/// rank which turns a model change affected most.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let set = |text: &str| {
+        text.split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<std::collections::HashSet<_>>()
+    };
+    let (a, b) = (set(a), set(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// The source chat's turns in order: each user message paired with the
+/// assistant answer that followed it (empty when the chat ended on a
+/// user turn).
+fn source_turns(db: &Db, chat_id: &str) -> AppResult<(String, Vec<(String, String)>)> {
+    let conn = db.conn();
+    let title: String = conn.query_row(
+        "SELECT title FROM chats WHERE id = ?1 AND deleted_at IS NULL",
+        params![chat_id],
+        |row| row.get(0),
+    )?;
+    let mut stmt = conn.prepare(
+        "SELECT role, content, compressed, content_zstd FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL AND role IN ('user', 'assistant')
+         ORDER BY created_at ASC",
+    )?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map(params![chat_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                compression::stored(row.get(1)?, row.get::<_, i64>(2)? != 0, row.get(3)?),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut turns = Vec::new();
+    for (role, content) in rows {
+        if role == "user" {
+            turns.push((content, String::new()));
+        } else if let Some((_, original)) = turns.last_mut() {
+            if original.is_empty() {
+                *original = content;
+            }
+        }
+    }
+    Ok((title, turns))
+}
+
+async fn generate_turn(
+    model: &str,
+    messages: &[Value],
+    seed: i64,
+    params: &ReplayParams,
+) -> AppResult<String> {
+    let mut options = serde_json::json!({ "seed": seed });
+    if let Some(temperature) = params.temperature {
+        options["temperature"] = temperature.into();
+    }
+    if let Some(top_p) = params.top_p {
+        options["top_p"] = top_p.into();
+    }
+    let payload = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "stream": false,
+        "options": options,
+    });
+    let client = reqwest::Client::new();
+    let value: Value = client
+        .post(format!("{}/api/chat", OLLAMA_BASE_URL))
+        .json(&payload)
+        .send()
+        .await?
+        .json()
+        .await?;
+    if let Some(error) = value.get("error").and_then(Value::as_str) {
+        return Err(AppError::Internal(error.to_string()));
+    }
+    Ok(value
+        .pointer("/message/content")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Re-run every user turn of `chat_id` against `model` with a fixed
+/// seed, sequentially (each replayed answer feeds the next turn's
+/// context, like the original conversation). The replayed transcript
+/// persists as a normal chat and the report pairs each turn's original
+/// and replayed answers. Progress streams as
+/// `replay-progress-{replay_chat_id}` events.
+#[tauri::command]
+pub async fn replay_chat(
+    app: AppHandle,
+    db: State<'_, Db>,
+    chat_id: String,
+    model: String,
+    params: Option<ReplayParams>,
+) -> AppResult<ReplayReport> {
+    let params = params.unwrap_or_default();
+    let seed = params.seed.unwrap_or(DEFAULT_SEED);
+    let (title, turns) = source_turns(&db, &chat_id)?;
+    if turns.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "chat {} has no user turns to replay",
+            chat_id
+        )));
+    }
+
+    let replay_chat = chat::create_chat_internal(
+        &db,
+        format!("Replay: {} ({})", title, model),
+        model.clone(),
+    )?;
+    let total = turns.len();
+    let mut messages: Vec<Value> = Vec::new();
+    let mut diffs = Vec::with_capacity(total);
+    for (index, (prompt, original)) in turns.into_iter().enumerate() {
+        messages.push(serde_json::json!({ "role": "user", "content": prompt }));
+        let replayed = generate_turn(&model, &messages, seed, &params).await?;
+        messages.push(serde_json::json!({ "role": "assistant", "content": replayed }));
+
+        chat::insert_message(&db, &replay_chat.id, "user", &prompt, None)?;
+        chat::insert_message(&db, &replay_chat.id, "assistant", &replayed, Some(&model))?;
+        diffs.push(TurnDiff {
+            turn: index + 1,
+            similarity: similarity(&original, &replayed),
+            prompt,
+            original,
+            replayed,
+        });
+        let _ = app.emit(
+            &format!("replay-progress-{}", replay_chat.id),
+            &ReplayProgress {
+                replay_chat_id: replay_chat.id.clone(),
+                completed: index + 1,
+                total,
+            },
+        );
+    }
+
+    Ok(ReplayReport {
+        source_chat_id: chat_id,
+        replay_chat,
+        model,
+        seed,
+        turns: diffs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::similarity;
+
+    #[test]
+    fn identical_answers_score_one() {
+        assert_eq!(similarity("the same answer", "the same answer"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+    }
+
+    #[test]
+    fn disjoint_answers_score_zero() {
+        assert_eq!(similarity("alpha beta", "gamma delta"), 0.0);
+        let partial = similarity("alpha beta gamma", "alpha beta delta");
+        assert!(partial > 0.4 && partial < 0.6);
+    }
+}
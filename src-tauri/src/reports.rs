@@ -0,0 +1,292 @@
+//! Template-driven reports over finished conversations. A report
+//! template names an ordered set of sections (executive summary,
+//! decisions made, action items, ...); each selected chat is condensed
+//! into notes, every section is drafted from those notes, and the
+//! result lands as a Markdown or DOCX file under `<app data>/reports`.
+//! Progress streams over `report-progress` plus the shared operations
+//! channel.
+
+use serde::Serialize;
+use std::io::Write;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::compression;
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::operations;
+use crate::web;
+
+/// Per-chat transcript budget for the condensation pass; anything
+/// longer is truncated from the front so recent turns survive.
+const NOTES_INPUT_CHARS: usize = 24_000;
+
+/// Ordered sections for a built-in report template: heading plus the
+/// instruction handed to the model for that section.
+pub fn template_sections(template: &str) -> Option<Vec<(&'static str, &'static str)>> {
+    match template {
+        "meeting" => Some(vec![
+            (
+                "Executive Summary",
+                "Write a short executive summary of what was discussed and concluded.",
+            ),
+            (
+                "Decisions",
+                "List every decision that was made, one bullet each, with its rationale.",
+            ),
+            (
+                "Action Items",
+                "List concrete action items as bullets, each with an owner if one is \
+                 implied and a deadline if one was mentioned.",
+            ),
+        ]),
+        "status" => Some(vec![
+            (
+                "Progress",
+                "Summarize what was accomplished or moved forward.",
+            ),
+            (
+                "Blockers",
+                "List open problems, risks and blockers as bullets.",
+            ),
+            (
+                "Next Steps",
+                "List the planned next steps as bullets.",
+            ),
+        ]),
+        "decision_log" => Some(vec![
+            (
+                "Context",
+                "Briefly describe the problem or question under discussion.",
+            ),
+            (
+                "Options Considered",
+                "List the options that were weighed, with their trade-offs.",
+            ),
+            (
+                "Decision",
+                "State the decision that was reached and why.",
+            ),
+        ]),
+        _ => None,
+    }
+}
+
+/// Assemble the final Markdown document from drafted sections.
+pub fn render_markdown(title: &str, sections: &[(String, String)]) -> String {
+    let mut out = format!("# {}\n", title);
+    for (heading, body) in sections {
+        out.push_str(&format!("\n## {}\n\n{}\n", heading, body.trim()));
+    }
+    out
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ReportProgress {
+    stage: String,
+    completed: usize,
+    total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportResult {
+    pub path: String,
+    pub template: String,
+    pub format: String,
+    pub chat_count: usize,
+}
+
+fn emit_progress(app: &AppHandle, op: &str, stage: &str, completed: usize, total: usize) {
+    let _ = app.emit(
+        "report-progress",
+        &ReportProgress {
+            stage: stage.to_string(),
+            completed,
+            total,
+        },
+    );
+    if total > 0 {
+        operations::progress(app, op, completed as f64 / total as f64);
+    }
+}
+
+fn transcript(db: &Db, chat_id: &str) -> AppResult<(String, String)> {
+    let conn = db.conn();
+    let title: String = conn.query_row(
+        "SELECT title FROM chats WHERE id = ?1 AND deleted_at IS NULL",
+        rusqlite::params![chat_id],
+        |row| row.get(0),
+    )?;
+    let mut stmt = conn.prepare(
+        "SELECT role, content, compressed, content_zstd FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+    )?;
+    let lines = stmt
+        .query_map(rusqlite::params![chat_id], |row| {
+            Ok(format!(
+                "{}: {}",
+                row.get::<_, String>(0)?,
+                compression::stored(row.get(1)?, row.get::<_, i64>(2)? != 0, row.get(3)?)
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut text = lines.join("\n\n");
+    if text.len() > NOTES_INPUT_CHARS {
+        let start = text.len() - NOTES_INPUT_CHARS;
+        let start = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .find(|&i| i >= start)
+            .unwrap_or(0);
+        text = text[start..].to_string();
+    }
+    Ok((title, text))
+}
+
+/// Write the rendered Markdown as a minimally formatted DOCX: headings
+/// become bold oversized paragraphs, everything else plain paragraphs.
+fn write_docx(path: &std::path::Path, title: &str, sections: &[(String, String)]) -> AppResult<()> {
+    use docx_rs::{Docx, Paragraph, Run};
+    let mut docx = Docx::new().add_paragraph(
+        Paragraph::new().add_run(Run::new().add_text(title).bold().size(48)),
+    );
+    for (heading, body) in sections {
+        docx = docx.add_paragraph(
+            Paragraph::new().add_run(Run::new().add_text(heading.as_str()).bold().size(32)),
+        );
+        for line in body.trim().lines() {
+            docx = docx.add_paragraph(Paragraph::new().add_run(Run::new().add_text(line)));
+        }
+    }
+    let file = std::fs::File::create(path)?;
+    docx.build()
+        .pack(file)
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    Ok(())
+}
+
+/// Generate a report over the selected chats and write it to
+/// `<app data>/reports/<template>-<timestamp>.<ext>`. `format` is
+/// `markdown` or `docx`.
+#[tauri::command]
+pub async fn generate_report(
+    app: AppHandle,
+    db: State<'_, Db>,
+    chat_ids: Vec<String>,
+    template: String,
+    model: String,
+    format: String,
+) -> AppResult<ReportResult> {
+    if chat_ids.is_empty() {
+        return Err(AppError::InvalidInput("no chats selected".to_string()));
+    }
+    if format != "markdown" && format != "docx" {
+        return Err(AppError::InvalidInput(format!(
+            "unknown report format: {}",
+            format
+        )));
+    }
+    let sections = template_sections(&template)
+        .ok_or_else(|| AppError::InvalidInput(format!("unknown report template: {}", template)))?;
+
+    let op = operations::start(&app, "report", &format!("Generating {} report", template));
+    let result = build_report(&app, &db, &op, &chat_ids, &template, &sections, &model, &format)
+        .await;
+    operations::finish(&app, &op, if result.is_ok() { "done" } else { "failed" });
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn build_report(
+    app: &AppHandle,
+    db: &Db,
+    op: &str,
+    chat_ids: &[String],
+    template: &str,
+    sections: &[(&'static str, &'static str)],
+    model: &str,
+    format: &str,
+) -> AppResult<ReportResult> {
+    let total = chat_ids.len() + sections.len();
+    let mut notes = Vec::new();
+    for (i, chat_id) in chat_ids.iter().enumerate() {
+        emit_progress(app, op, "condensing", i, total);
+        let (title, text) = transcript(db, chat_id)?;
+        let prompt = format!(
+            "Condense the following conversation into dense factual notes: what was \
+             discussed, concluded, decided and left open. Keep every concrete detail \
+             (names, numbers, dates). Do not editorialize.\n\nConversation \"{}\":\n\n{}",
+            title, text
+        );
+        notes.push(format!("## {}\n{}", title, web::generate(model, &prompt).await?));
+    }
+    let notes = notes.join("\n\n");
+
+    let mut drafted: Vec<(String, String)> = Vec::new();
+    for (i, (heading, instruction)) in sections.iter().enumerate() {
+        emit_progress(app, op, "drafting", chat_ids.len() + i, total);
+        let prompt = format!(
+            "You are writing the \"{}\" section of a report based on conversation \
+             notes. {} Use Markdown, no top-level heading. Base everything strictly \
+             on the notes.\n\nNotes:\n\n{}",
+            heading, instruction, notes
+        );
+        drafted.push((heading.to_string(), web::generate(model, &prompt).await?));
+    }
+
+    let title = format!("{} report", capitalize(template));
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?
+        .join("reports");
+    std::fs::create_dir_all(&dir)?;
+    let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    let path = if format == "docx" {
+        let path = dir.join(format!("{}-{}.docx", template, stamp));
+        write_docx(&path, &title, &drafted)?;
+        path
+    } else {
+        let path = dir.join(format!("{}-{}.md", template, stamp));
+        let mut file = std::fs::File::create(&path)?;
+        file.write_all(render_markdown(&title, &drafted).as_bytes())?;
+        path
+    };
+    emit_progress(app, op, "done", total, total);
+    Ok(ReportResult {
+        path: path.to_string_lossy().to_string(),
+        template: template.to_string(),
+        format: format.to_string(),
+        chat_count: chat_ids.len(),
+    })
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_markdown, template_sections};
+
+    #[test]
+    fn builtin_templates_have_sections() {
+        assert_eq!(template_sections("meeting").unwrap().len(), 3);
+        assert!(template_sections("quarterly").is_none());
+    }
+
+    #[test]
+    fn markdown_rendering_joins_sections() {
+        let sections = vec![
+            ("Summary".to_string(), "All good.\n".to_string()),
+            ("Decisions".to_string(), "- Ship it".to_string()),
+        ];
+        let doc = render_markdown("Meeting report", &sections);
+        assert!(doc.starts_with("# Meeting report\n"));
+        assert!(doc.contains("\n## Summary\n\nAll good.\n"));
+        assert!(doc.contains("\n## Decisions\n\n- Ship it\n"));
+    }
+}
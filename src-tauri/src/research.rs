@@ -0,0 +1,239 @@
+//! Literature review mode: expand a topic into several search queries
+//! with the model, pool the academic results, dedupe them by DOI, and
+//! stream a synthesized review with a numbered bibliography into a
+//! fresh chat. Orchestration only — searching lives in `academic` and
+//! streaming in the shared chat pipeline.
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::academic::{self, Paper};
+use crate::chat::{self, Chat, Message};
+use crate::citations;
+use crate::db::Db;
+use crate::error::AppResult;
+use crate::sources;
+use crate::web;
+
+/// Upper bound on expanded queries regardless of requested depth.
+const MAX_QUERIES: usize = 6;
+/// Cap on sources fed to the synthesis prompt, to keep it inside a
+/// reasonable context window.
+const MAX_PAPERS: usize = 20;
+
+/// One query per line from the expansion response, with numbering,
+/// bullets and quotes stripped, empties and duplicates dropped.
+pub fn parse_queries(response: &str) -> Vec<String> {
+    let mut queries = Vec::new();
+    for line in response.lines() {
+        let query = line
+            .trim()
+            .trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*'))
+            .trim()
+            .trim_matches('"')
+            .to_string();
+        if !query.is_empty() && !queries.contains(&query) {
+            queries.push(query);
+        }
+    }
+    queries
+}
+
+/// Drop duplicate papers found by more than one source: by DOI
+/// (case-insensitive) when both have one, otherwise by normalized title.
+pub fn dedupe_papers(papers: Vec<Paper>) -> Vec<Paper> {
+    let mut seen = std::collections::HashSet::new();
+    papers
+        .into_iter()
+        .filter(|paper| {
+            let key = match &paper.doi {
+                Some(doi) => format!("doi:{}", doi.to_lowercase()),
+                None => format!(
+                    "title:{}",
+                    paper.title.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ")
+                ),
+            };
+            seen.insert(key)
+        })
+        .collect()
+}
+
+/// A numbered bibliography entry per paper, in citation order.
+pub fn bibliography(papers: &[Paper]) -> String {
+    papers
+        .iter()
+        .enumerate()
+        .map(|(index, paper)| {
+            let mut entry = format!("[{}] {}", index + 1, paper.authors.join(", "));
+            if let Some(year) = paper.year {
+                entry.push_str(&format!(" ({})", year));
+            }
+            entry.push_str(&format!(". {}.", paper.title));
+            if let Some(venue) = &paper.venue {
+                entry.push_str(&format!(" {}.", venue));
+            }
+            if let Some(url) = &paper.url {
+                entry.push_str(&format!(" {}", url));
+            }
+            entry
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn synthesis_prompt(topic: &str, papers: &[Paper]) -> String {
+    let sources = papers
+        .iter()
+        .enumerate()
+        .map(|(index, paper)| {
+            format!(
+                "[{}] {}\nAbstract: {}",
+                index + 1,
+                paper.title,
+                paper.abstract_text.as_deref().unwrap_or("(not available)")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    format!(
+        "Write a literature review on \"{}\" synthesizing the sources below. \
+         Organize by theme, cite sources inline as [n], note agreements and open \
+         questions, and end with a \"Bibliography\" section reproducing this list \
+         verbatim:\n\n{}\n\nSources:\n\n{}",
+        topic,
+        bibliography(papers),
+        sources
+    )
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiteratureReview {
+    pub chat: Chat,
+    pub queries: Vec<String>,
+    pub papers: Vec<Paper>,
+    pub message: Message,
+}
+
+/// Run the full pipeline: expand `topic` into up to `depth` queries,
+/// search each, dedupe, and stream the synthesized review into a new
+/// chat (so it journals, embeds and notifies like any other answer).
+#[tauri::command]
+pub async fn literature_review(
+    app: AppHandle,
+    db: State<'_, Db>,
+    topic: String,
+    depth: usize,
+    model: String,
+) -> AppResult<LiteratureReview> {
+    let depth = depth.clamp(1, MAX_QUERIES);
+    let expansion = web::generate(
+        &model,
+        &format!(
+            "Expand the research topic \"{}\" into {} distinct academic search \
+             queries covering its main aspects. Reply with one query per line \
+             and nothing else.",
+            topic, depth
+        ),
+    )
+    .await?;
+    let mut queries = parse_queries(&expansion);
+    queries.truncate(depth);
+    if queries.is_empty() {
+        queries.push(topic.clone());
+    }
+
+    let options = academic::load_defaults(&db);
+    let mut papers = Vec::new();
+    for query in &queries {
+        papers.extend(academic::run_search(query, &options).await);
+    }
+    let mut papers = dedupe_papers(papers);
+    papers.truncate(MAX_PAPERS);
+    if papers.is_empty() {
+        return Err(crate::error::AppError::NotFound(format!(
+            "no papers found for \"{}\"",
+            topic
+        )));
+    }
+
+    let chat = chat::create_chat_internal(&db, format!("Literature review: {}", topic), model)?;
+    crate::hooks::fire(
+        &app,
+        "chat-created",
+        serde_json::to_value(&chat).unwrap_or_default(),
+    );
+    let message = chat::run_generation(
+        &app,
+        &db,
+        &chat.id,
+        &chat.model,
+        &synthesis_prompt(&topic, &papers),
+        None,
+    )
+    .await?;
+    citations::report(&app, &chat.id, &message.id, &message.content, papers.len());
+    let paper_sources: Vec<sources::RetrievedSource> = papers
+        .iter()
+        .enumerate()
+        .map(|(index, paper)| sources::RetrievedSource {
+            kind: "paper".to_string(),
+            origin: paper
+                .url
+                .clone()
+                .or_else(|| paper.doi.clone())
+                .unwrap_or_else(|| paper.source.clone()),
+            chunk: format!(
+                "[{}] {}\n{}",
+                index + 1,
+                paper.title,
+                paper.abstract_text.as_deref().unwrap_or("(no abstract)")
+            ),
+            score: None,
+        })
+        .collect();
+    sources::record_all(&db, &chat.id, &message.id, &paper_sources);
+    Ok(LiteratureReview {
+        chat,
+        queries,
+        papers,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dedupe_papers, parse_queries};
+    use crate::academic::Paper;
+
+    fn paper(title: &str, doi: Option<&str>) -> Paper {
+        Paper {
+            title: title.to_string(),
+            authors: Vec::new(),
+            year: None,
+            venue: None,
+            doi: doi.map(str::to_string),
+            url: None,
+            abstract_text: None,
+            source: "test".to_string(),
+            open_access: false,
+        }
+    }
+
+    #[test]
+    fn queries_are_cleaned_and_deduped() {
+        let queries = parse_queries("1. \"transformer attention\"\n- transformer attention\n\n2) scaling laws\n");
+        assert_eq!(queries, vec!["transformer attention", "scaling laws"]);
+    }
+
+    #[test]
+    fn papers_dedupe_by_doi_then_title() {
+        let papers = vec![
+            paper("A Study", Some("10.1/X")),
+            paper("A study (reprint)", Some("10.1/x")),
+            paper("Another  Study", None),
+            paper("another study", None),
+        ];
+        let deduped = dedupe_papers(papers);
+        assert_eq!(deduped.len(), 2);
+    }
+}
@@ -0,0 +1,159 @@
+//! Clipboard "explain this" integration. A global shortcut grabs the
+//! current clipboard text and emits a `quick-action` event for the
+//! frontend's quick window; `process_selection` runs one of the
+//! predefined actions over the text through the normal chat pipeline in
+//! a transient chat, copies the result back to the clipboard and
+//! returns it.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::chat;
+use crate::db::{self, Db};
+use crate::error::{AppError, AppResult};
+use crate::ollama;
+use crate::settings;
+use crate::tray;
+
+/// Settings key overriding the default quick-action shortcut.
+pub const SHORTCUT_KEY: &str = "quick_action_shortcut";
+const DEFAULT_SHORTCUT: &str = "CmdOrCtrl+Shift+Space";
+/// Settings key pinning the model quick actions use.
+pub const MODEL_KEY: &str = "quick_action_model";
+
+/// The instruction each action wraps around the selected text.
+pub fn prompt_for(action: &str, text: &str) -> Option<String> {
+    let instruction = match action {
+        "explain" => "Explain the following text plainly and briefly:",
+        "translate" => "Translate the following text into English; if it is already English, say so and translate it into Spanish:",
+        "rewrite" => "Rewrite the following text more clearly, keeping its meaning and tone:",
+        "summarize" => "Summarize the following text in a few sentences:",
+        _ => return None,
+    };
+    Some(format!("{}\n\n{}", instruction, text))
+}
+
+/// The model quick actions run on: the pinned setting, else the most
+/// recently used chat's model, else the first installed model.
+async fn pick_model(db: &Db) -> AppResult<String> {
+    if let Some(model) = settings::get(db, MODEL_KEY) {
+        return Ok(model);
+    }
+    let recent: Option<String> = {
+        let conn = db.conn();
+        conn.query_row(
+            "SELECT model FROM chats WHERE deleted_at IS NULL ORDER BY updated_at DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .ok()
+    };
+    if let Some(model) = recent {
+        return Ok(model);
+    }
+    ollama::list_models()
+        .await?
+        .first()
+        .map(|m| m.name.clone())
+        .ok_or_else(|| AppError::NotFound("no models installed to run the action".to_string()))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectionResult {
+    pub action: String,
+    /// The transient chat the action ran in; trashed afterwards but
+    /// recoverable like any deleted chat.
+    pub chat_id: String,
+    pub result: String,
+}
+
+/// Run a predefined action over the given text (or the clipboard when
+/// none is passed) through the chat pipeline in a transient chat. The
+/// result is copied back to the clipboard for pasting and the chat is
+/// moved to trash so quick actions don't clutter the chat list.
+#[tauri::command]
+pub async fn process_selection(
+    app: AppHandle,
+    db: State<'_, Db>,
+    action: String,
+    text: Option<String>,
+) -> AppResult<SelectionResult> {
+    let text = match text.filter(|t| !t.trim().is_empty()) {
+        Some(text) => text,
+        None => app
+            .clipboard()
+            .read_text()
+            .map_err(|e| AppError::InvalidInput(format!("clipboard is not readable: {}", e)))?,
+    };
+    if text.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "nothing selected and the clipboard is empty".to_string(),
+        ));
+    }
+    let prompt = prompt_for(&action, &text).ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "unknown action: {} (expected explain, translate, rewrite or summarize)",
+            action
+        ))
+    })?;
+
+    let model = pick_model(&db).await?;
+    let title = format!("Quick {}: {}", action, tray::preview(&text, 40));
+    let chat = chat::create_chat_internal(&db, title, model)?;
+    let message = chat::run_generation(&app, &db, &chat.id, &chat.model, &prompt, None).await?;
+
+    {
+        let conn = db.conn();
+        conn.execute(
+            "UPDATE chats SET deleted_at = ?1 WHERE id = ?2",
+            rusqlite::params![db::now(), chat.id],
+        )?;
+    }
+    if let Err(e) = app.clipboard().write_text(message.content.clone()) {
+        tracing::warn!("failed to copy quick action result: {}", e);
+    }
+    Ok(SelectionResult {
+        action,
+        chat_id: chat.id,
+        result: message.content,
+    })
+}
+
+/// Register the quick-action shortcut (configurable via settings). On
+/// press the clipboard text rides a `quick-action` event so the
+/// frontend can open its quick window with the selection prefilled.
+pub(crate) fn register_shortcut(app: &AppHandle) -> Result<(), String> {
+    let shortcut = {
+        let db = app.state::<Db>();
+        settings::get(&db, SHORTCUT_KEY).unwrap_or_else(|| DEFAULT_SHORTCUT.to_string())
+    };
+    app.global_shortcut()
+        .on_shortcut(shortcut.as_str(), |app, _shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            let text = app.clipboard().read_text().unwrap_or_default();
+            let _ = app.emit("quick-action", &serde_json::json!({ "text": text }));
+        })
+        .map_err(|e| format!("failed to register quick-action shortcut {}: {}", shortcut, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prompt_for;
+
+    #[test]
+    fn known_actions_wrap_the_text() {
+        for action in ["explain", "translate", "rewrite", "summarize"] {
+            let prompt = prompt_for(action, "fn main() {}").unwrap();
+            assert!(prompt.ends_with("fn main() {}"));
+        }
+    }
+
+    #[test]
+    fn unknown_actions_are_rejected() {
+        assert!(prompt_for("delete-my-files", "text").is_none());
+    }
+}
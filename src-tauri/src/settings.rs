@@ -0,0 +1,38 @@
+//! Small persisted key-value settings store, for feature defaults that
+//! should survive restarts without growing a dedicated table each time.
+
+use rusqlite::params;
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::AppResult;
+
+pub fn get(db: &Db, key: &str) -> Option<String> {
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+pub fn set(db: &Db, key: &str, value: &str) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        params![key, value, crate::db::now()],
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_setting(db: State<Db>, key: String) -> Option<String> {
+    get(&db, &key)
+}
+
+#[tauri::command]
+pub fn set_setting(db: State<Db>, key: String, value: String) -> AppResult<()> {
+    set(&db, &key, &value)
+}
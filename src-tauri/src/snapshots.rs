@@ -0,0 +1,198 @@
+//! Named conversation snapshots. A snapshot freezes a chat's full
+//! message list (every column, trashed rows excluded) as JSON in the
+//! snapshots table; restoring swaps the live messages back to that
+//! state in one transaction. This makes aggressive experimentation —
+//! edits, regenerations, context resets — cheap to undo.
+
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+use uuid::Uuid;
+
+use crate::chat;
+use crate::compression;
+use crate::db::{self, Db};
+use crate::error::{AppError, AppResult};
+
+/// Every message column a snapshot captures and restores verbatim.
+const COLUMNS: [&str; 14] = [
+    "id",
+    "chat_id",
+    "role",
+    "content",
+    "model",
+    "created_at",
+    "pinned",
+    "feedback",
+    "note",
+    "bookmarked",
+    "interrupted",
+    "detected_lang",
+    "translated_content",
+    "raw_content",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub chat_id: String,
+    pub name: String,
+    pub message_count: usize,
+    pub created_at: String,
+}
+
+/// Freeze the chat's current message list under a name.
+#[tauri::command]
+pub fn create_chat_snapshot(db: State<Db>, chat_id: String, name: String) -> AppResult<Snapshot> {
+    if name.trim().is_empty() {
+        return Err(AppError::InvalidInput("snapshot name is empty".to_string()));
+    }
+    let conn = db.conn();
+    // `content` is captured decompressed, so snapshot JSON stays plain
+    // text; restored rows are written uncompressed and picked back up
+    // by `compact_database` if they are large.
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, compressed, content_zstd FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+        COLUMNS.join(", ")
+    ))?;
+    let rows: Vec<Value> = stmt
+        .query_map(params![chat_id], |row| {
+            let mut object = serde_json::Map::new();
+            for (i, column) in COLUMNS.iter().enumerate() {
+                let value = match row.get_ref(i)? {
+                    rusqlite::types::ValueRef::Null => Value::Null,
+                    rusqlite::types::ValueRef::Integer(n) => Value::from(n),
+                    rusqlite::types::ValueRef::Real(n) => Value::from(n),
+                    other => Value::from(String::from_utf8_lossy(other.as_bytes()?).to_string()),
+                };
+                object.insert(column.to_string(), value);
+            }
+            if row.get::<_, i64>(COLUMNS.len())? != 0 {
+                let blob: Option<Vec<u8>> = row.get(COLUMNS.len() + 1)?;
+                object.insert(
+                    "content".to_string(),
+                    Value::from(compression::stored(String::new(), true, blob)),
+                );
+            }
+            Ok(Value::Object(object))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(stmt);
+    let snapshot = Snapshot {
+        id: Uuid::new_v4().to_string(),
+        chat_id: chat_id.clone(),
+        name: name.trim().to_string(),
+        message_count: rows.len(),
+        created_at: db::now(),
+    };
+    conn.execute(
+        "INSERT INTO snapshots (id, chat_id, name, messages, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            snapshot.id,
+            snapshot.chat_id,
+            snapshot.name,
+            serde_json::to_string(&rows)?,
+            snapshot.created_at
+        ],
+    )?;
+    Ok(snapshot)
+}
+
+/// Roll the chat back to a snapshot: the current message list
+/// (including trashed rows) is replaced by the captured one, all inside
+/// a single transaction so a failed restore changes nothing.
+#[tauri::command]
+pub fn restore_chat_snapshot(db: State<Db>, snapshot_id: String) -> AppResult<Snapshot> {
+    let (chat_id, name, messages, created_at) = {
+        let conn = db.conn();
+        conn.query_row(
+            "SELECT chat_id, name, messages, created_at FROM snapshots WHERE id = ?1",
+            params![snapshot_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            },
+        )?
+    };
+    chat::ensure_unlocked(&db, &chat_id)?;
+    let rows: Vec<Value> = serde_json::from_str(&messages)?;
+
+    let conn = db.conn();
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM messages WHERE chat_id = ?1", params![chat_id])?;
+    let placeholders: Vec<String> = (1..=COLUMNS.len()).map(|i| format!("?{}", i)).collect();
+    let mut insert = tx.prepare(&format!(
+        "INSERT INTO messages ({}) VALUES ({})",
+        COLUMNS.join(", "),
+        placeholders.join(", ")
+    ))?;
+    for row in &rows {
+        let values: Vec<rusqlite::types::Value> = COLUMNS
+            .iter()
+            .map(|column| match row.get(*column) {
+                Some(Value::Number(n)) if n.is_i64() => {
+                    rusqlite::types::Value::Integer(n.as_i64().unwrap_or_default())
+                }
+                Some(Value::Number(n)) => {
+                    rusqlite::types::Value::Real(n.as_f64().unwrap_or_default())
+                }
+                Some(Value::String(s)) => rusqlite::types::Value::Text(s.clone()),
+                _ => rusqlite::types::Value::Null,
+            })
+            .collect();
+        insert.execute(rusqlite::params_from_iter(values))?;
+    }
+    drop(insert);
+    tx.execute(
+        "UPDATE chats SET updated_at = ?1 WHERE id = ?2",
+        params![db::now(), chat_id],
+    )?;
+    tx.commit()?;
+
+    Ok(Snapshot {
+        id: snapshot_id,
+        chat_id,
+        name,
+        message_count: rows.len(),
+        created_at,
+    })
+}
+
+/// A chat's snapshots, newest first.
+#[tauri::command]
+pub fn list_chat_snapshots(db: State<Db>, chat_id: String) -> AppResult<Vec<Snapshot>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, chat_id, name, messages, created_at FROM snapshots
+         WHERE chat_id = ?1 ORDER BY created_at DESC",
+    )?;
+    let snapshots = stmt
+        .query_map(params![chat_id], |row| {
+            let messages: String = row.get(3)?;
+            Ok(Snapshot {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                name: row.get(2)?,
+                message_count: serde_json::from_str::<Vec<Value>>(&messages)
+                    .map(|rows| rows.len())
+                    .unwrap_or(0),
+                created_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(snapshots)
+}
+
+#[tauri::command]
+pub fn delete_chat_snapshot(db: State<Db>, snapshot_id: String) -> AppResult<()> {
+    let conn = db.conn();
+    conn.execute("DELETE FROM snapshots WHERE id = ?1", params![snapshot_id])?;
+    Ok(())
+}
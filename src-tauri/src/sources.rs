@@ -0,0 +1,91 @@
+//! Per-response source tracking for retrieved context. Whenever a
+//! generation has chunks injected — attachment documents, `@chat:`
+//! reference spans, literature-review papers — each chunk is recorded
+//! in the message_sources table against the assistant message it
+//! contributed to, so an answer can be audited back to where it came
+//! from.
+
+use rusqlite::params;
+use serde::Serialize;
+use tauri::State;
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::error::AppResult;
+
+/// A chunk injected into a generation, collected while the context is
+/// assembled and persisted once the assistant message exists.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetrievedSource {
+    /// `attachment`, `chat` or `paper`.
+    pub kind: String,
+    /// File name, chat id or URL/DOI the chunk came from.
+    pub origin: String,
+    pub chunk: String,
+    /// Embedding similarity where the retrieval was scored.
+    pub score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageSource {
+    pub id: String,
+    pub message_id: String,
+    pub chat_id: String,
+    pub kind: String,
+    pub origin: String,
+    pub chunk: String,
+    pub score: Option<f64>,
+    pub created_at: String,
+}
+
+/// Persist every collected chunk against the assistant message. Source
+/// tracking is advisory, so failures are swallowed like journal writes.
+pub(crate) fn record_all(db: &Db, chat_id: &str, message_id: &str, sources: &[RetrievedSource]) {
+    if sources.is_empty() {
+        return;
+    }
+    let conn = db.conn();
+    for source in sources {
+        let _ = conn.execute(
+            "INSERT INTO message_sources
+             (id, message_id, chat_id, kind, origin, chunk, score, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                Uuid::new_v4().to_string(),
+                message_id,
+                chat_id,
+                source.kind,
+                source.origin,
+                source.chunk,
+                source.score,
+                db::now()
+            ],
+        );
+    }
+}
+
+/// The chunks that contributed to one assistant message, in the order
+/// they were injected.
+#[tauri::command]
+pub fn get_message_sources(db: State<Db>, message_id: String) -> AppResult<Vec<MessageSource>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, message_id, chat_id, kind, origin, chunk, score, created_at
+         FROM message_sources WHERE message_id = ?1 ORDER BY created_at ASC, id ASC",
+    )?;
+    let sources = stmt
+        .query_map(params![message_id], |row| {
+            Ok(MessageSource {
+                id: row.get(0)?,
+                message_id: row.get(1)?,
+                chat_id: row.get(2)?,
+                kind: row.get(3)?,
+                origin: row.get(4)?,
+                chunk: row.get(5)?,
+                score: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(sources)
+}
@@ -0,0 +1,155 @@
+//! Per-chat statistics, aggregated server-side. Counts, lengths and
+//! timestamps come straight from SQL; only the fenced-code-block scan
+//! walks message content, so the frontend never pages a whole
+//! transcript in just to render a summary panel.
+
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::State;
+
+use crate::compression;
+use crate::db::Db;
+use crate::error::AppResult;
+
+/// Count fenced code blocks and collect the (deduplicated) languages
+/// named on their opening fences. Unlabelled fences count as blocks but
+/// contribute no language.
+pub fn code_blocks(content: &str) -> (usize, Vec<String>) {
+    let mut count = 0;
+    let mut languages = Vec::new();
+    for (i, segment) in content.split("```").enumerate() {
+        // Odd segments sit between a pair of fences.
+        if i % 2 == 0 {
+            continue;
+        }
+        count += 1;
+        let lang = segment.lines().next().unwrap_or("").trim().to_string();
+        if !lang.is_empty() && !languages.contains(&lang) {
+            languages.push(lang);
+        }
+    }
+    (count, languages)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatStatistics {
+    pub chat_id: String,
+    /// Message counts keyed by role.
+    pub messages_by_role: HashMap<String, i64>,
+    pub total_words: usize,
+    /// Assistant response lengths, in characters.
+    pub avg_response_chars: f64,
+    pub max_response_chars: i64,
+    pub code_blocks: usize,
+    pub code_languages: Vec<String>,
+    pub first_activity: Option<String>,
+    pub last_activity: Option<String>,
+}
+
+/// Compute summary statistics for one chat. Trashed messages are
+/// excluded, matching what the transcript view shows.
+#[tauri::command]
+pub fn get_chat_statistics(db: State<Db>, chat_id: String) -> AppResult<ChatStatistics> {
+    let conn = db.conn();
+    let mut messages_by_role = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT role, COUNT(*) FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL GROUP BY role",
+    )?;
+    let rows = stmt.query_map(params![chat_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+    for row in rows {
+        let (role, count) = row?;
+        messages_by_role.insert(role, count);
+    }
+    drop(stmt);
+
+    let (first_activity, last_activity) = conn.query_row(
+        "SELECT MIN(created_at), MAX(created_at) FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL",
+        params![chat_id],
+        |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+            ))
+        },
+    )?;
+
+    // Word counts, code blocks and response lengths all come from one
+    // scan over the decompressed content; LENGTH(content) in SQL would
+    // measure compressed rows as empty.
+    let mut total_words = 0;
+    let mut block_count = 0;
+    let mut code_languages = Vec::new();
+    let mut response_chars_sum = 0i64;
+    let mut response_count = 0i64;
+    let mut max_response_chars = 0i64;
+    let mut stmt = conn.prepare(
+        "SELECT role, content, compressed, content_zstd FROM messages
+         WHERE chat_id = ?1 AND deleted_at IS NULL",
+    )?;
+    let contents = stmt.query_map(params![chat_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            compression::stored(row.get(1)?, row.get::<_, i64>(2)? != 0, row.get(3)?),
+        ))
+    })?;
+    for row in contents {
+        let (role, content) = row?;
+        total_words += content.split_whitespace().count();
+        if role == "assistant" {
+            let chars = content.len() as i64;
+            response_chars_sum += chars;
+            response_count += 1;
+            max_response_chars = max_response_chars.max(chars);
+        }
+        let (blocks, languages) = code_blocks(&content);
+        block_count += blocks;
+        for lang in languages {
+            if !code_languages.contains(&lang) {
+                code_languages.push(lang);
+            }
+        }
+    }
+    let avg_response_chars = if response_count > 0 {
+        response_chars_sum as f64 / response_count as f64
+    } else {
+        0.0
+    };
+
+    Ok(ChatStatistics {
+        chat_id,
+        messages_by_role,
+        total_words,
+        avg_response_chars,
+        max_response_chars,
+        code_blocks: block_count,
+        code_languages,
+        first_activity,
+        last_activity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::code_blocks;
+
+    #[test]
+    fn counts_fences_and_collects_languages() {
+        let text = "intro\n```rust\nfn main() {}\n```\nmid\n```python\nprint(1)\n```\nand\n```rust\nlet x = 1;\n```";
+        let (count, languages) = code_blocks(text);
+        assert_eq!(count, 3);
+        assert_eq!(languages, vec!["rust", "python"]);
+    }
+
+    #[test]
+    fn unlabelled_fences_count_without_a_language() {
+        let (count, languages) = code_blocks("```\nplain\n```");
+        assert_eq!(count, 1);
+        assert!(languages.is_empty());
+        assert_eq!(code_blocks("no code here").0, 0);
+    }
+}
@@ -0,0 +1,136 @@
+//! Tolerant parsing of partially streamed JSON for structured output mode.
+//!
+//! While a structured response is streaming we repeatedly try to close the
+//! JSON prefix received so far (unterminated strings, open objects/arrays,
+//! dangling keys) and, whenever that yields a valid document, emit the
+//! partial object to the frontend so it can render progressively.
+
+use serde_json::Value;
+
+/// Attempt to repair a truncated JSON prefix into a complete document.
+/// Returns `None` when the prefix cannot be made valid (e.g. it does not
+/// even start with `{` or `[` yet).
+pub fn complete_partial_json(input: &str) -> Option<String> {
+    let trimmed = input.trim_start();
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return None;
+    }
+
+    let mut stack: Vec<char> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for ch in trimmed.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '{' => stack.push('}'),
+            '[' => stack.push(']'),
+            '}' | ']' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    let mut repaired = trimmed.to_string();
+    if escaped {
+        // Drop a trailing half-finished escape sequence.
+        repaired.pop();
+    }
+    if in_string {
+        repaired.push('"');
+    }
+
+    // Strip trailing syntax that can't be completed: a dangling comma, a
+    // key waiting for its value, or a half-typed literal like `tru`.
+    loop {
+        let last = match repaired.trim_end().chars().last() {
+            Some(c) => c,
+            None => return None,
+        };
+        let trimmed_len = repaired.trim_end().len();
+        repaired.truncate(trimmed_len);
+        match last {
+            ',' => {
+                repaired.pop();
+            }
+            ':' => {
+                repaired.push_str("null");
+                break;
+            }
+            c if c.is_ascii_alphabetic() => {
+                // Partial `true`/`false`/`null` — remove the whole word.
+                while repaired
+                    .chars()
+                    .last()
+                    .is_some_and(|c| c.is_ascii_alphabetic())
+                {
+                    repaired.pop();
+                }
+            }
+            c if c == '.' || c == '-' || c == '+' || c == 'e' || c == 'E' => {
+                repaired.pop();
+            }
+            _ => break,
+        }
+    }
+
+    for closer in stack.iter().rev() {
+        repaired.push(*closer);
+    }
+    Some(repaired)
+}
+
+/// Parse as much of a streamed structured response as currently possible.
+pub fn parse_partial(input: &str) -> Option<Value> {
+    // Fast path: the prefix may already be complete.
+    if let Ok(value) = serde_json::from_str::<Value>(input) {
+        return Some(value);
+    }
+    let repaired = complete_partial_json(input)?;
+    serde_json::from_str(&repaired).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn completes_open_object_and_string() {
+        let partial = r#"{"title": "Revi"#;
+        assert_eq!(parse_partial(partial), Some(json!({"title": "Revi"})));
+    }
+
+    #[test]
+    fn fills_dangling_key_with_null() {
+        let partial = r#"{"title": "Review", "score":"#;
+        assert_eq!(
+            parse_partial(partial),
+            Some(json!({"title": "Review", "score": null}))
+        );
+    }
+
+    #[test]
+    fn drops_partial_literal_and_closes_nesting() {
+        let partial = r#"{"sections": [{"heading": "Intro", "done": fal"#;
+        assert_eq!(
+            parse_partial(partial),
+            Some(json!({"sections": [{"heading": "Intro", "done": null}]}))
+        );
+    }
+
+    #[test]
+    fn rejects_non_json_prefix() {
+        assert_eq!(parse_partial("Sure, here is the JSON you asked"), None);
+    }
+}
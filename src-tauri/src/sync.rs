@@ -0,0 +1,320 @@
+//! Opt-in chat history sync through a user-provided folder (Dropbox,
+//! Syncthing, a mounted bucket — anything that replicates files).
+//! Each device appends its change journal entries to its own
+//! `<device_id>.log` in the sync folder as NDJSON; `sync_now` publishes
+//! local changes past the last exported sequence number and replays the
+//! unseen tail of every peer's log. Conflicts resolve on row IDs:
+//! creates are first-writer-wins (IDs are UUIDs, so a duplicate create
+//! is the same original row), chat title updates are last-writer-wins
+//! by journal timestamp.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::journal::ChangeEntry;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub configured: bool,
+    pub folder: Option<String>,
+    pub device_id: Option<String>,
+    pub last_exported_seq: i64,
+    pub peer_count: usize,
+    pub last_synced_at: Option<String>,
+}
+
+struct SyncConfig {
+    folder: PathBuf,
+    device_id: String,
+    last_exported_seq: i64,
+}
+
+fn load_config(conn: &Connection) -> Result<Option<SyncConfig>, String> {
+    conn.query_row(
+        "SELECT folder, device_id, last_exported_seq FROM sync_config WHERE id = 1",
+        [],
+        |row| {
+            Ok(SyncConfig {
+                folder: PathBuf::from(row.get::<_, String>(0)?),
+                device_id: row.get(1)?,
+                last_exported_seq: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Point sync at a folder. The device id is generated once and survives
+/// reconfiguration so peers keep recognizing this device's log.
+#[tauri::command]
+pub fn configure_sync(db: State<Db>, folder: String) -> Result<SyncStatus, String> {
+    let path = PathBuf::from(&folder);
+    if !path.is_dir() {
+        return Err(format!("{} is not a directory", folder));
+    }
+    let conn = db.conn();
+    let device_id = load_config(&conn)?
+        .map(|c| c.device_id)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    conn.execute(
+        "INSERT INTO sync_config (id, folder, device_id, last_exported_seq)
+         VALUES (1, ?1, ?2, 0)
+         ON CONFLICT(id) DO UPDATE SET folder = excluded.folder",
+        params![folder, device_id],
+    )
+    .map_err(|e| e.to_string())?;
+    drop(conn);
+    get_sync_status(db)
+}
+
+#[tauri::command]
+pub fn get_sync_status(db: State<Db>) -> Result<SyncStatus, String> {
+    let conn = db.conn();
+    let config = load_config(&conn)?;
+    let peer_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM sync_peers", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let last_synced_at: Option<String> = conn
+        .query_row("SELECT MAX(last_synced_at) FROM sync_peers", [], |row| {
+            row.get(0)
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(SyncStatus {
+        configured: config.is_some(),
+        folder: config
+            .as_ref()
+            .map(|c| c.folder.to_string_lossy().to_string()),
+        device_id: config.as_ref().map(|c| c.device_id.clone()),
+        last_exported_seq: config.as_ref().map(|c| c.last_exported_seq).unwrap_or(0),
+        peer_count: peer_count as usize,
+        last_synced_at,
+    })
+}
+
+/// Append local journal entries past the export cursor to our log file.
+fn export_changes(conn: &Connection, config: &SyncConfig) -> Result<i64, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT seq, entity, entity_id, op, payload, recorded_at
+             FROM change_journal WHERE seq > ?1 AND entity IN ('chat', 'message')
+             ORDER BY seq ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let changes = stmt
+        .query_map(params![config.last_exported_seq], |row| {
+            Ok(ChangeEntry {
+                seq: row.get(0)?,
+                entity: row.get(1)?,
+                entity_id: row.get(2)?,
+                op: row.get(3)?,
+                payload: row.get(4)?,
+                recorded_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if changes.is_empty() {
+        return Ok(config.last_exported_seq);
+    }
+    let log_path = config.folder.join(format!("{}.log", config.device_id));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .map_err(|e| e.to_string())?;
+    for change in &changes {
+        let line = serde_json::to_string(change).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    }
+    let exported = changes.last().map(|c| c.seq).unwrap_or(0);
+    conn.execute(
+        "UPDATE sync_config SET last_exported_seq = ?1 WHERE id = 1",
+        params![exported],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(exported)
+}
+
+/// Apply one remote change. Rows are written directly, bypassing the
+/// journal — the originating device already published the change, so
+/// re-journaling here would echo it back to every peer forever.
+fn apply_change(conn: &Connection, change: &ChangeEntry) -> Result<(), String> {
+    let payload: Option<Value> = change
+        .payload
+        .as_deref()
+        .and_then(|p| serde_json::from_str(p).ok());
+    match (change.entity.as_str(), change.op.as_str()) {
+        ("chat", "create") => {
+            if let Some(p) = payload {
+                conn.execute(
+                    "INSERT OR IGNORE INTO chats (id, title, model, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        change.entity_id,
+                        p.get("title").and_then(Value::as_str).unwrap_or_default(),
+                        p.get("model").and_then(Value::as_str).unwrap_or_default(),
+                        p.get("created_at").and_then(Value::as_str).unwrap_or_default(),
+                        p.get("updated_at").and_then(Value::as_str).unwrap_or_default(),
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        ("chat", "update") => {
+            if let Some(title) = payload.as_ref().and_then(|p| p.get("title")).and_then(Value::as_str) {
+                // Last-writer-wins on the journal timestamp.
+                conn.execute(
+                    "UPDATE chats SET title = ?1, updated_at = ?2
+                     WHERE id = ?3 AND updated_at < ?2",
+                    params![title, change.recorded_at, change.entity_id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            if let Some(deleted) = payload.as_ref().and_then(|p| p.get("deleted_at")) {
+                conn.execute(
+                    "UPDATE chats SET deleted_at = ?1 WHERE id = ?2",
+                    params![deleted.as_str(), change.entity_id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        ("chat", "delete") => {
+            conn.execute("DELETE FROM chats WHERE id = ?1", params![change.entity_id])
+                .map_err(|e| e.to_string())?;
+        }
+        ("message", "create") => {
+            if let Some(p) = payload {
+                conn.execute(
+                    "INSERT OR IGNORE INTO messages (id, chat_id, role, content, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        change.entity_id,
+                        p.get("chat_id").and_then(Value::as_str).unwrap_or_default(),
+                        p.get("role").and_then(Value::as_str).unwrap_or_default(),
+                        p.get("content").and_then(Value::as_str).unwrap_or_default(),
+                        p.get("created_at").and_then(Value::as_str).unwrap_or_default(),
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        ("message", "update") => {
+            if let Some(p) = payload {
+                if let Some(content) = p.get("content").and_then(Value::as_str) {
+                    conn.execute(
+                        "UPDATE messages SET content = ?1, content_zstd = NULL, compressed = 0
+                         WHERE id = ?2",
+                        params![content, change.entity_id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+                if let Some(pinned) = p.get("pinned").and_then(Value::as_bool) {
+                    conn.execute(
+                        "UPDATE messages SET pinned = ?1 WHERE id = ?2",
+                        params![pinned as i64, change.entity_id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+                if let Some(deleted) = p.get("deleted_at") {
+                    conn.execute(
+                        "UPDATE messages SET deleted_at = ?1 WHERE id = ?2",
+                        params![deleted.as_str(), change.entity_id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        ("message", "delete") => {
+            conn.execute(
+                "DELETE FROM messages WHERE id = ?1",
+                params![change.entity_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replay the unseen tail of one peer's log file. Logs are append-only,
+/// so the number of lines already applied is the resume cursor.
+fn import_peer_log(conn: &Connection, peer_id: &str, path: &Path) -> Result<usize, String> {
+    let applied: i64 = conn
+        .query_row(
+            "SELECT applied_lines FROM sync_peers WHERE device_id = ?1",
+            params![peer_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(0);
+    let raw = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut imported = 0usize;
+    let mut total = 0i64;
+    for (index, line) in raw.lines().enumerate() {
+        total = index as i64 + 1;
+        if (index as i64) < applied || line.trim().is_empty() {
+            continue;
+        }
+        let change: ChangeEntry = serde_json::from_str(line).map_err(|e| e.to_string())?;
+        apply_change(conn, &change)?;
+        imported += 1;
+    }
+    conn.execute(
+        "INSERT INTO sync_peers (device_id, applied_lines, last_synced_at)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(device_id) DO UPDATE SET
+             applied_lines = excluded.applied_lines,
+             last_synced_at = excluded.last_synced_at",
+        params![peer_id, total.max(applied), db::now()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(imported)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncResult {
+    pub exported_through_seq: i64,
+    pub imported_changes: usize,
+    pub peers_seen: usize,
+}
+
+/// Publish local changes and replay peers' logs in one pass.
+#[tauri::command]
+pub fn sync_now(db: State<Db>) -> Result<SyncResult, String> {
+    let conn = db.conn();
+    let config = load_config(&conn)?.ok_or("sync is not configured")?;
+    let exported_through_seq = export_changes(&conn, &config)?;
+    let mut imported_changes = 0usize;
+    let mut peers_seen = 0usize;
+    for entry in fs::read_dir(&config.folder).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let Some(peer_id) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".log"))
+        else {
+            continue;
+        };
+        if peer_id == config.device_id {
+            continue;
+        }
+        peers_seen += 1;
+        imported_changes += import_peer_log(&conn, peer_id, &path)?;
+    }
+    Ok(SyncResult {
+        exported_through_seq,
+        imported_changes,
+        peers_seen,
+    })
+}
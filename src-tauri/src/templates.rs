@@ -0,0 +1,304 @@
+//! Declarative workflow templates: small pipelines that chain attachments,
+//! prompts and structured outputs into a reusable academic workflow
+//! (paper Q&A, peer review). Built-ins are seeded into the DB so users
+//! can edit them like their own templates.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+use crate::attachments;
+use crate::chat;
+use crate::db::{self, Db};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TemplateStep {
+    /// Attach a file (the workflow input) so its extracted text rides
+    /// along with the next prompt.
+    Ingest,
+    /// Run a prompt; `{{input}}` and `{{steps.<id>}}` placeholders are
+    /// substituted before sending. An optional JSON schema turns on
+    /// structured output mode.
+    Prompt {
+        id: String,
+        prompt: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<Value>,
+    },
+    /// Render the final artifact from prior step outputs.
+    Render { template: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub builtin: bool,
+    pub steps: Vec<TemplateStep>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateProgress {
+    pub template_id: String,
+    pub chat_id: String,
+    pub step: usize,
+    pub total: usize,
+    pub status: String,
+}
+
+fn builtin_templates() -> Vec<WorkflowTemplate> {
+    vec![
+        WorkflowTemplate {
+            id: "builtin-paper-qa".to_string(),
+            name: "Paper Q&A".to_string(),
+            description: "Ingest a paper and answer a question grounded in its text".to_string(),
+            builtin: true,
+            steps: vec![
+                TemplateStep::Ingest,
+                TemplateStep::Prompt {
+                    id: "answer".to_string(),
+                    prompt: "Using only the attached paper, answer the following question. \
+                             Quote the relevant passages.\n\nQuestion: {{input}}"
+                        .to_string(),
+                    format: None,
+                },
+                TemplateStep::Render {
+                    template: "{{steps.answer}}".to_string(),
+                },
+            ],
+        },
+        WorkflowTemplate {
+            id: "builtin-peer-review".to_string(),
+            name: "Peer Review".to_string(),
+            description: "Ingest a paper and produce a structured referee report".to_string(),
+            builtin: true,
+            steps: vec![
+                TemplateStep::Ingest,
+                TemplateStep::Prompt {
+                    id: "critique".to_string(),
+                    prompt: "Act as a rigorous but constructive peer reviewer for the attached \
+                             paper. Produce your critique as JSON."
+                        .to_string(),
+                    format: Some(serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "summary": { "type": "string" },
+                            "strengths": { "type": "array", "items": { "type": "string" } },
+                            "weaknesses": { "type": "array", "items": { "type": "string" } },
+                            "questions_for_authors": { "type": "array", "items": { "type": "string" } },
+                            "recommendation": { "type": "string" }
+                        },
+                        "required": ["summary", "strengths", "weaknesses", "recommendation"]
+                    })),
+                },
+                TemplateStep::Prompt {
+                    id: "report".to_string(),
+                    prompt: "Format the following review JSON as a polished referee report in \
+                             markdown with sections Summary, Strengths, Weaknesses, Questions \
+                             for the Authors, and Recommendation.\n\n{{steps.critique}}"
+                        .to_string(),
+                    format: None,
+                },
+                TemplateStep::Render {
+                    template: "{{steps.report}}".to_string(),
+                },
+            ],
+        },
+    ]
+}
+
+/// Seed built-in templates if they aren't in the DB yet; user edits to a
+/// seeded row are preserved on later startups.
+pub fn seed_builtins(db: &Db) -> Result<(), String> {
+    let conn = db.conn();
+    for template in builtin_templates() {
+        let definition = serde_json::to_string(&template.steps).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR IGNORE INTO templates (id, name, description, builtin, definition, created_at, updated_at)
+             VALUES (?1, ?2, ?3, 1, ?4, ?5, ?5)",
+            params![template.id, template.name, template.description, definition, db::now()],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn row_to_template(row: &rusqlite::Row) -> rusqlite::Result<(WorkflowTemplate, String)> {
+    let definition: String = row.get(4)?;
+    Ok((
+        WorkflowTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            builtin: row.get::<_, i64>(3)? != 0,
+            steps: Vec::new(),
+        },
+        definition,
+    ))
+}
+
+fn load_template(db: &Db, template_id: &str) -> Result<WorkflowTemplate, String> {
+    let conn = db.conn();
+    let (mut template, definition) = conn
+        .query_row(
+            "SELECT id, name, description, builtin, definition FROM templates WHERE id = ?1",
+            params![template_id],
+            row_to_template,
+        )
+        .map_err(|e| e.to_string())?;
+    template.steps = serde_json::from_str(&definition).map_err(|e| e.to_string())?;
+    Ok(template)
+}
+
+pub(crate) fn get_templates_internal(db: &Db) -> Result<Vec<WorkflowTemplate>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare("SELECT id, name, description, builtin, definition FROM templates ORDER BY name ASC")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], row_to_template)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    rows.into_iter()
+        .map(|(mut template, definition)| {
+            template.steps = serde_json::from_str(&definition).map_err(|e| e.to_string())?;
+            Ok(template)
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_templates(db: State<Db>) -> Result<Vec<WorkflowTemplate>, String> {
+    get_templates_internal(&db)
+}
+
+pub(crate) fn save_template_internal(
+    db: &Db,
+    id: &str,
+    name: &str,
+    description: &str,
+    steps: &[TemplateStep],
+) -> Result<WorkflowTemplate, String> {
+    let definition = serde_json::to_string(steps).map_err(|e| e.to_string())?;
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO templates (id, name, description, builtin, definition, created_at, updated_at)
+         VALUES (?1, ?2, ?3, 0, ?4, ?5, ?5)
+         ON CONFLICT(id) DO UPDATE SET
+             name = excluded.name,
+             description = excluded.description,
+             definition = excluded.definition,
+             updated_at = excluded.updated_at",
+        params![id, name, description, definition, db::now()],
+    )
+    .map_err(|e| e.to_string())?;
+    let builtin: bool = conn
+        .query_row(
+            "SELECT builtin FROM templates WHERE id = ?1",
+            params![id],
+            |row| Ok(row.get::<_, i64>(0)? != 0),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(WorkflowTemplate {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        builtin,
+        steps: steps.to_vec(),
+    })
+}
+
+/// Create or update a template. Built-in templates keep their id, so an
+/// edited built-in simply overwrites the seeded definition.
+#[tauri::command]
+pub fn save_template(
+    db: State<Db>,
+    id: Option<String>,
+    name: String,
+    description: String,
+    steps: Vec<TemplateStep>,
+) -> Result<WorkflowTemplate, String> {
+    let id = id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    save_template_internal(&db, &id, &name, &description, &steps)
+}
+
+#[tauri::command]
+pub fn delete_template(db: State<Db>, template_id: String) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute(
+        "DELETE FROM templates WHERE id = ?1 AND builtin = 0",
+        params![template_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn substitute(text: &str, input: &str, outputs: &[(String, String)]) -> String {
+    let mut result = text.replace("{{input}}", input);
+    for (id, output) in outputs {
+        result = result.replace(&format!("{{{{steps.{}}}}}", id), output);
+    }
+    result
+}
+
+/// Execute a template against a chat: `file_path` feeds `Ingest` steps,
+/// `input` fills `{{input}}` placeholders. Returns the rendered artifact
+/// (or the last prompt output when there is no Render step).
+#[tauri::command]
+pub async fn run_template(
+    app: AppHandle,
+    db: State<'_, Db>,
+    template_id: String,
+    chat_id: String,
+    model: String,
+    input: String,
+    file_path: Option<String>,
+) -> Result<String, String> {
+    let template = load_template(&db, &template_id)?;
+    let total = template.steps.len();
+    let mut outputs: Vec<(String, String)> = Vec::new();
+    let mut artifact = String::new();
+
+    for (index, step) in template.steps.iter().enumerate() {
+        let status = match step {
+            TemplateStep::Ingest => "ingesting",
+            TemplateStep::Prompt { .. } => "prompting",
+            TemplateStep::Render { .. } => "rendering",
+        };
+        let _ = app.emit(
+            "template-progress",
+            &TemplateProgress {
+                template_id: template_id.clone(),
+                chat_id: chat_id.clone(),
+                step: index + 1,
+                total,
+                status: status.to_string(),
+            },
+        );
+        match step {
+            TemplateStep::Ingest => {
+                let path = file_path
+                    .as_deref()
+                    .ok_or("template has an ingest step but no file was provided")?;
+                attachments::store_attachment(&app, &db, &chat_id, path)?;
+            }
+            TemplateStep::Prompt { id, prompt, format } => {
+                let content = substitute(prompt, &input, &outputs);
+                let message =
+                    chat::run_generation(&app, &db, &chat_id, &model, &content, format.clone())
+                        .await?;
+                artifact = message.content.clone();
+                outputs.push((id.clone(), message.content));
+            }
+            TemplateStep::Render { template } => {
+                artifact = substitute(template, &input, &outputs);
+            }
+        }
+    }
+    Ok(artifact)
+}
@@ -0,0 +1,192 @@
+//! Fine-tuning dataset export. Converts selected chats into JSONL
+//! training examples — OpenAI-style chat format (one example per
+//! assistant turn, with the full preceding conversation as context) or
+//! Alpaca instruction pairs — optionally keeping only thumbs-up
+//! assistant messages and stripping system prompts.
+
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::db::Db;
+
+#[derive(Debug, Clone)]
+struct TrainingMessage {
+    role: String,
+    content: String,
+    feedback: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrainingExport {
+    pub jsonl: String,
+    pub example_count: usize,
+    /// Lock records for any finalized source chats, so a dataset built
+    /// from locked conversations carries their timestamps and content
+    /// hashes as provenance.
+    pub locks: Vec<crate::chat::ChatLock>,
+}
+
+/// One OpenAI-chat example per assistant turn: the conversation up to
+/// and including that turn.
+fn openai_chat_examples(
+    messages: &[TrainingMessage],
+    only_thumbs_up: bool,
+    strip_system: bool,
+) -> Vec<String> {
+    let mut examples = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        if message.role != "assistant" {
+            continue;
+        }
+        if only_thumbs_up && message.feedback.as_deref() != Some("up") {
+            continue;
+        }
+        let turns: Vec<_> = messages[..=index]
+            .iter()
+            .filter(|m| !(strip_system && m.role == "system"))
+            .map(|m| json!({ "role": m.role, "content": m.content }))
+            .collect();
+        examples.push(json!({ "messages": turns }).to_string());
+    }
+    examples
+}
+
+/// Alpaca pairs: each assistant turn against the nearest preceding user
+/// message. Assistant turns without a user prompt are skipped.
+fn alpaca_examples(messages: &[TrainingMessage], only_thumbs_up: bool) -> Vec<String> {
+    let mut examples = Vec::new();
+    let mut last_user: Option<&str> = None;
+    for message in messages {
+        match message.role.as_str() {
+            "user" => last_user = Some(&message.content),
+            "assistant" => {
+                if only_thumbs_up && message.feedback.as_deref() != Some("up") {
+                    continue;
+                }
+                if let Some(instruction) = last_user {
+                    examples.push(
+                        json!({
+                            "instruction": instruction,
+                            "input": "",
+                            "output": message.content,
+                        })
+                        .to_string(),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    examples
+}
+
+/// Export the selected chats as fine-tuning JSONL. `format` is
+/// `openai_chat` or `alpaca`.
+#[tauri::command]
+pub fn export_training_dataset(
+    db: State<Db>,
+    chat_ids: Vec<String>,
+    format: String,
+    only_thumbs_up: bool,
+    strip_system: bool,
+) -> Result<TrainingExport, String> {
+    let conn = db.conn();
+    let mut lines = Vec::new();
+    for chat_id in &chat_ids {
+        let mut stmt = conn
+            .prepare(
+                "SELECT role, content, feedback, compressed, content_zstd FROM messages
+                 WHERE chat_id = ?1 AND deleted_at IS NULL ORDER BY created_at ASC",
+            )
+            .map_err(|e| e.to_string())?;
+        let messages = stmt
+            .query_map(params![chat_id], |row| {
+                Ok(TrainingMessage {
+                    role: row.get(0)?,
+                    content: crate::compression::stored(
+                        row.get(1)?,
+                        row.get::<_, i64>(3)? != 0,
+                        row.get(4)?,
+                    ),
+                    feedback: row.get(2)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        match format.as_str() {
+            "openai_chat" => {
+                lines.extend(openai_chat_examples(&messages, only_thumbs_up, strip_system))
+            }
+            "alpaca" => lines.extend(alpaca_examples(&messages, only_thumbs_up)),
+            other => return Err(format!("unknown export format: {}", other)),
+        }
+    }
+    drop(conn);
+    let mut locks = Vec::new();
+    for chat_id in &chat_ids {
+        if let Some(lock) = crate::chat::chat_lock(&db, chat_id)? {
+            locks.push(lock);
+        }
+    }
+    Ok(TrainingExport {
+        example_count: lines.len(),
+        jsonl: lines.join("\n"),
+        locks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(role: &str, content: &str, feedback: Option<&str>) -> TrainingMessage {
+        TrainingMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            feedback: feedback.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn openai_chat_one_example_per_assistant_turn() {
+        let messages = [
+            msg("system", "be brief", None),
+            msg("user", "hi", None),
+            msg("assistant", "hello", None),
+            msg("user", "bye", None),
+            msg("assistant", "goodbye", None),
+        ];
+        let examples = openai_chat_examples(&messages, false, true);
+        assert_eq!(examples.len(), 2);
+        assert!(!examples[0].contains("be brief"));
+        assert!(examples[1].contains("goodbye"));
+    }
+
+    #[test]
+    fn thumbs_up_filter_drops_unrated_turns() {
+        let messages = [
+            msg("user", "hi", None),
+            msg("assistant", "meh", Some("down")),
+            msg("user", "again", None),
+            msg("assistant", "great", Some("up")),
+        ];
+        let examples = openai_chat_examples(&messages, true, false);
+        assert_eq!(examples.len(), 1);
+        assert!(examples[0].contains("great"));
+    }
+
+    #[test]
+    fn alpaca_pairs_assistant_with_preceding_user() {
+        let messages = [
+            msg("assistant", "orphan", None),
+            msg("user", "question", None),
+            msg("assistant", "answer", None),
+        ];
+        let examples = alpaca_examples(&messages, false);
+        assert_eq!(examples.len(), 1);
+        assert!(examples[0].contains("\"instruction\":\"question\""));
+    }
+}
@@ -0,0 +1,171 @@
+//! Language detection and translation. `translate_message` renders any
+//! message into a target language with a configurable translation model;
+//! the opt-in auto-translate mode detects the language the user writes
+//! in and transparently translates assistant output back into it. Both
+//! the original and the translation are stored on the message.
+
+use rusqlite::params;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::compression;
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::settings;
+use crate::web;
+
+/// Settings key for the opt-in mode; "true" enables it.
+pub const AUTO_TRANSLATE_KEY: &str = "auto_translate";
+/// Settings key naming the model used for translations; the message's
+/// own model is used when unset.
+pub const TRANSLATION_MODEL_KEY: &str = "translation_model";
+
+/// ISO 639-3 code of the dominant language, or `None` when detection is
+/// not confident enough to act on (short or mixed-language text).
+pub fn detect_language(text: &str) -> Option<String> {
+    let info = whatlang::detect(text)?;
+    info.is_reliable().then(|| info.lang().code().to_string())
+}
+
+fn translation_model(db: &Db, fallback: Option<&str>) -> AppResult<String> {
+    settings::get(db, TRANSLATION_MODEL_KEY)
+        .or_else(|| fallback.map(str::to_string))
+        .ok_or_else(|| {
+            AppError::InvalidInput(
+                "no translation model configured and the message has none".to_string(),
+            )
+        })
+}
+
+async fn run_translation(model: &str, text: &str, target_lang: &str) -> AppResult<String> {
+    let translated = web::generate(
+        model,
+        &format!(
+            "Translate the following text into {}. Preserve formatting and code \
+             blocks untouched. Output only the translation.\n\n{}",
+            target_lang, text
+        ),
+    )
+    .await?;
+    Ok(translated.trim().to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Translation {
+    pub message_id: String,
+    pub target_lang: String,
+    pub text: String,
+}
+
+/// Translate a stored message into `target_lang`, persist the result in
+/// `translated_content` (the original stays in `content`) and return it.
+#[tauri::command]
+pub async fn translate_message(
+    db: State<'_, Db>,
+    message_id: String,
+    target_lang: String,
+) -> AppResult<Translation> {
+    let (content, model) = {
+        let conn = db.conn();
+        conn.query_row(
+            "SELECT content, model, compressed, content_zstd FROM messages WHERE id = ?1",
+            params![message_id],
+            |row| {
+                Ok((
+                    compression::stored(row.get(0)?, row.get::<_, i64>(2)? != 0, row.get(3)?),
+                    row.get::<_, Option<String>>(1)?,
+                ))
+            },
+        )?
+    };
+    let model = translation_model(&db, model.as_deref())?;
+    let text = run_translation(&model, &content, &target_lang).await?;
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE messages SET translated_content = ?1 WHERE id = ?2",
+        params![text, message_id],
+    )?;
+    Ok(Translation {
+        message_id,
+        target_lang,
+        text,
+    })
+}
+
+/// Background auto-translate hook, fired after an assistant message is
+/// persisted. When the mode is on and the assistant answered in a
+/// different language than the user wrote in, the answer is translated
+/// back, stored, and announced as a `message-translated` event. Failures
+/// are logged and swallowed: translation must never break generation.
+pub(crate) fn auto_translate_background(
+    app: &AppHandle,
+    user_content: &str,
+    message: &crate::chat::Message,
+) {
+    let app = app.clone();
+    let user_content = user_content.to_string();
+    let message = message.clone();
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<Db>();
+        if settings::get(&db, AUTO_TRANSLATE_KEY).as_deref() != Some("true") {
+            return;
+        }
+        let Some(user_lang) = detect_language(&user_content) else {
+            return;
+        };
+        {
+            let conn = db.conn();
+            let _ = conn.execute(
+                "UPDATE messages SET detected_lang = ?1 WHERE id = ?2",
+                params![user_lang, message.id],
+            );
+        }
+        if detect_language(&message.content).as_deref() == Some(&user_lang) {
+            return;
+        }
+        let model = match translation_model(&db, message.model.as_deref()) {
+            Ok(model) => model,
+            Err(e) => {
+                tracing::debug!("skipping auto-translate: {}", e);
+                return;
+            }
+        };
+        match run_translation(&model, &message.content, &user_lang).await {
+            Ok(text) => {
+                {
+                    let conn = db.conn();
+                    let _ = conn.execute(
+                        "UPDATE messages SET translated_content = ?1 WHERE id = ?2",
+                        params![text, message.id],
+                    );
+                }
+                let _ = app.emit(
+                    "message-translated",
+                    &Translation {
+                        message_id: message.id.clone(),
+                        target_lang: user_lang,
+                        text,
+                    },
+                );
+            }
+            Err(e) => tracing::debug!("auto-translate failed: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::detect_language;
+
+    #[test]
+    fn detects_clear_languages_and_passes_on_noise() {
+        assert_eq!(
+            detect_language(
+                "The quick brown fox jumps over the lazy dog, as everyone knows well."
+            )
+            .as_deref(),
+            Some("eng")
+        );
+        assert_eq!(detect_language(""), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! System tray integration: minimize-to-tray, a menu of recent chats and
+//! installed models, and an unread counter for generations that finish
+//! while the window is hidden.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tauri::menu::{MenuBuilder, MenuItem, SubmenuBuilder};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::chat;
+use crate::db::Db;
+use crate::ollama;
+
+pub struct TrayState {
+    tray: Mutex<Option<TrayIcon>>,
+    known_models: Mutex<Vec<String>>,
+    unread: AtomicUsize,
+}
+
+impl Default for TrayState {
+    fn default() -> Self {
+        TrayState {
+            tray: Mutex::new(None),
+            known_models: Mutex::new(Vec::new()),
+            unread: AtomicUsize::new(0),
+        }
+    }
+}
+
+const MAX_RECENT_CHATS: usize = 5;
+
+fn build_menu(app: &AppHandle) -> Result<tauri::menu::Menu<tauri::Wry>, tauri::Error> {
+    let mut recent = SubmenuBuilder::new(app, "Recent Chats");
+    let chats = chat::get_chats_internal(&app.state::<Db>()).unwrap_or_default();
+    for chat in chats.iter().take(MAX_RECENT_CHATS) {
+        recent = recent.item(&MenuItem::with_id(
+            app,
+            format!("open-chat:{}", chat.id),
+            &chat.title,
+            true,
+            None::<&str>,
+        )?);
+    }
+
+    let mut models = SubmenuBuilder::new(app, "Switch Model");
+    let model_names = app
+        .state::<TrayState>()
+        .known_models
+        .lock()
+        .unwrap()
+        .clone();
+    for name in &model_names {
+        models = models.item(&MenuItem::with_id(
+            app,
+            format!("switch-model:{}", name),
+            name,
+            true,
+            None::<&str>,
+        )?);
+    }
+
+    MenuBuilder::new(app)
+        .item(&MenuItem::with_id(app, "show", "Open CortexAI", true, None::<&str>)?)
+        .separator()
+        .items(&[&recent.build()?, &models.build()?])
+        .separator()
+        .item(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)
+        .build()
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        app.state::<TrayState>().clear_unread(app);
+    }
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    if let Some(chat_id) = id.strip_prefix("open-chat:") {
+        show_main_window(app);
+        let _ = app.emit("tray-open-chat", chat_id.to_string());
+    } else if let Some(model) = id.strip_prefix("switch-model:") {
+        let _ = app.emit("tray-switch-model", model.to_string());
+    } else {
+        match id {
+            "show" => show_main_window(app),
+            "quit" => app.exit(0),
+            _ => {}
+        }
+    }
+}
+
+/// Build the tray icon. Runs after setup so the DB and model list are
+/// available; the model submenu is filled in once list_models answers.
+pub fn init(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Ok(models) = ollama::list_models().await {
+            *app.state::<TrayState>().known_models.lock().unwrap() =
+                models.into_iter().map(|m| m.name).collect();
+        }
+        let menu = match build_menu(&app) {
+            Ok(menu) => menu,
+            Err(_) => return,
+        };
+        let tray = TrayIconBuilder::with_id("main-tray")
+            .icon(app.default_window_icon().cloned().unwrap_or_else(|| {
+                tauri::image::Image::new_owned(vec![0; 4], 1, 1)
+            }))
+            .tooltip("CortexAI")
+            .menu(&menu)
+            .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()))
+            .build(&app);
+        if let Ok(tray) = tray {
+            *app.state::<TrayState>().tray.lock().unwrap() = Some(tray);
+        }
+    });
+}
+
+/// Rebuild the tray menu, e.g. after chats were created or models pulled.
+#[tauri::command]
+pub fn refresh_tray_menu(app: AppHandle) -> Result<(), String> {
+    let state = app.state::<TrayState>();
+    let guard = state.tray.lock().unwrap();
+    if let Some(tray) = guard.as_ref() {
+        let menu = build_menu(&app).map_err(|e| e.to_string())?;
+        tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+impl TrayState {
+    fn clear_unread(&self, app: &AppHandle) {
+        self.unread.store(0, Ordering::Relaxed);
+        self.update_tooltip(app);
+    }
+
+    fn bump_unread(&self, app: &AppHandle) {
+        self.unread.fetch_add(1, Ordering::Relaxed);
+        self.update_tooltip(app);
+    }
+
+    fn update_tooltip(&self, app: &AppHandle) {
+        let unread = self.unread.load(Ordering::Relaxed);
+        let tooltip = if unread == 0 {
+            "CortexAI".to_string()
+        } else {
+            format!("CortexAI — {} unread response(s)", unread)
+        };
+        if let Some(tray) = app.state::<TrayState>().tray.lock().unwrap().as_ref() {
+            let _ = tray.set_tooltip(Some(tooltip));
+        }
+    }
+}
+
+/// Deliver an event to the frontend; when the main window is hidden the
+/// tray additionally counts it as unread so the user sees that a
+/// background generation finished.
+pub fn emit_or_notify<P: serde::Serialize + Clone>(app: &AppHandle, event: &str, payload: &P) {
+    let _ = app.emit(event, payload);
+    let hidden = app
+        .get_webview_window("main")
+        .map(|w| !w.is_visible().unwrap_or(true))
+        .unwrap_or(true);
+    if hidden {
+        app.state::<TrayState>().bump_unread(app);
+    }
+}
+
+/// Fire an OS notification when the window can't be seen (unfocused,
+/// minimized or hidden to tray); no-op while the user is looking at the
+/// app anyway.
+pub fn notify_if_unfocused(app: &AppHandle, title: &str, body: &str) {
+    use tauri_plugin_notification::NotificationExt;
+    let visible_and_focused = app
+        .get_webview_window("main")
+        .map(|w| w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false))
+        .unwrap_or(false);
+    if !visible_and_focused {
+        let _ = app
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show();
+    }
+}
+
+/// First ~line of a response, shortened for a notification body.
+pub fn preview(text: &str, max_chars: usize) -> String {
+    let first_line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    let mut preview: String = first_line.chars().take(max_chars).collect();
+    if first_line.chars().count() > max_chars {
+        preview.push('…');
+    }
+    preview
+}
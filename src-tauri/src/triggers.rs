@@ -0,0 +1,215 @@
+//! Per-chat automation triggers: when an assistant message completes in
+//! a bound chat, push it onward — POST it to a webhook, pipe it into a
+//! script, or append it to a file — so automation-sink chats (digests,
+//! monitors) don't need polling.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+use crate::chat::Message;
+use crate::db::{self, Db};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatTrigger {
+    pub id: String,
+    pub chat_id: String,
+    /// `webhook` (target is a URL), `script` (target is an executable
+    /// fed the message JSON on stdin) or `file` (target is appended to,
+    /// one JSON line per message).
+    pub kind: String,
+    pub target: String,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerOutcome {
+    pub trigger_id: String,
+    pub chat_id: String,
+    pub kind: String,
+    pub error: Option<String>,
+}
+
+#[tauri::command]
+pub fn add_trigger(
+    db: State<Db>,
+    chat_id: String,
+    kind: String,
+    target: String,
+) -> Result<ChatTrigger, String> {
+    if !matches!(kind.as_str(), "webhook" | "script" | "file") {
+        return Err(format!("unknown trigger kind: {}", kind));
+    }
+    let trigger = ChatTrigger {
+        id: Uuid::new_v4().to_string(),
+        chat_id,
+        kind,
+        target,
+        enabled: true,
+        created_at: db::now(),
+    };
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO chat_triggers (id, chat_id, kind, target, enabled, created_at)
+         VALUES (?1, ?2, ?3, ?4, 1, ?5)",
+        params![
+            trigger.id,
+            trigger.chat_id,
+            trigger.kind,
+            trigger.target,
+            trigger.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(trigger)
+}
+
+#[tauri::command]
+pub fn remove_trigger(db: State<Db>, trigger_id: String) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute("DELETE FROM chat_triggers WHERE id = ?1", params![trigger_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_trigger_enabled(db: State<Db>, trigger_id: String, enabled: bool) -> Result<(), String> {
+    let conn = db.conn();
+    conn.execute(
+        "UPDATE chat_triggers SET enabled = ?1 WHERE id = ?2",
+        params![enabled as i64, trigger_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_triggers(db: State<Db>, chat_id: String) -> Result<Vec<ChatTrigger>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, chat_id, kind, target, enabled, created_at
+             FROM chat_triggers WHERE chat_id = ?1 ORDER BY created_at ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let triggers = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(ChatTrigger {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                kind: row.get(2)?,
+                target: row.get(3)?,
+                enabled: row.get::<_, i64>(4)? != 0,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(triggers)
+}
+
+async fn run_trigger(trigger: &ChatTrigger, payload: &str) -> Result<(), String> {
+    match trigger.kind.as_str() {
+        "webhook" => {
+            let client = reqwest::Client::new();
+            let resp = client
+                .post(&trigger.target)
+                .header("content-type", "application/json")
+                .body(payload.to_string())
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !resp.status().is_success() {
+                return Err(format!("webhook returned {}", resp.status()));
+            }
+            Ok(())
+        }
+        "script" => {
+            let mut child = Command::new(&trigger.target)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| e.to_string())?;
+            if let Some(stdin) = child.stdin.as_mut() {
+                stdin
+                    .write_all(payload.as_bytes())
+                    .map_err(|e| e.to_string())?;
+            }
+            let status = child.wait().map_err(|e| e.to_string())?;
+            if !status.success() {
+                return Err(format!("script exited with {}", status));
+            }
+            Ok(())
+        }
+        "file" => {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&trigger.target)
+                .map_err(|e| e.to_string())?;
+            writeln!(file, "{}", payload).map_err(|e| e.to_string())
+        }
+        other => Err(format!("unknown trigger kind: {}", other)),
+    }
+}
+
+/// Fire all enabled triggers for the chat the message belongs to.
+/// Runs in the background; outcomes (including failures) are emitted as
+/// `trigger-fired` events rather than failing the generation.
+pub fn fire_assistant_message(app: &AppHandle, message: &Message) {
+    let app = app.clone();
+    let message = message.clone();
+    tauri::async_runtime::spawn(async move {
+        let triggers = {
+            let db = app.state::<Db>();
+            match get_triggers_internal(&db, &message.chat_id) {
+                Ok(triggers) => triggers,
+                Err(_) => return,
+            }
+        };
+        let payload = match serde_json::to_string(&message) {
+            Ok(payload) => payload,
+            Err(_) => return,
+        };
+        for trigger in triggers.into_iter().filter(|t| t.enabled) {
+            let outcome = TriggerOutcome {
+                trigger_id: trigger.id.clone(),
+                chat_id: trigger.chat_id.clone(),
+                kind: trigger.kind.clone(),
+                error: run_trigger(&trigger, &payload).await.err(),
+            };
+            let _ = app.emit("trigger-fired", &outcome);
+        }
+    });
+}
+
+fn get_triggers_internal(db: &Db, chat_id: &str) -> Result<Vec<ChatTrigger>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, chat_id, kind, target, enabled, created_at
+             FROM chat_triggers WHERE chat_id = ?1 AND enabled = 1",
+        )
+        .map_err(|e| e.to_string())?;
+    let triggers = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(ChatTrigger {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                kind: row.get(2)?,
+                target: row.get(3)?,
+                enabled: row.get::<_, i64>(4)? != 0,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(triggers)
+}
@@ -0,0 +1,242 @@
+//! Background folder watching: markdown files in watched folders are kept
+//! embedded in the knowledge base without manual re-ingestion.
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::knowledge;
+use crate::operations;
+
+/// Live `notify` watchers keyed by folder id.
+pub struct WatcherState(pub Mutex<HashMap<String, RecommendedWatcher>>);
+
+impl Default for WatcherState {
+    fn default() -> Self {
+        WatcherState(Mutex::new(HashMap::new()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchedFolder {
+    pub id: String,
+    pub path: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub folder_id: String,
+    pub path: String,
+    pub status: String,
+    pub detail: Option<String>,
+}
+
+fn emit_status(app: &AppHandle, folder_id: &str, path: &Path, status: &str, detail: Option<String>) {
+    let _ = app.emit(
+        "kb-sync-status",
+        &SyncStatus {
+            folder_id: folder_id.to_string(),
+            path: path.to_string_lossy().into_owned(),
+            status: status.to_string(),
+            detail,
+        },
+    );
+}
+
+fn is_markdown(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md") | Some("markdown")
+    )
+}
+
+async fn sync_file(app: &AppHandle, folder_id: &str, path: &Path, removed: bool) {
+    let db = app.state::<Db>();
+    let op = (!removed).then(|| {
+        operations::start(
+            app,
+            "ingest",
+            &format!("Indexing {}", path.file_name().unwrap_or_default().to_string_lossy()),
+        )
+    });
+    let result = if removed {
+        knowledge::remove_file(&db, path).map(|_| 0)
+    } else {
+        knowledge::ingest_file(&db, folder_id, path).await
+    };
+    if let Some(op) = &op {
+        operations::finish(app, op, if result.is_ok() { "done" } else { "failed" });
+    }
+    match result {
+        Ok(chunks) => {
+            if !removed {
+                crate::hooks::fire(
+                    app,
+                    "document-ingested",
+                    serde_json::json!({
+                        "folder_id": folder_id,
+                        "path": path.to_string_lossy(),
+                        "chunks": chunks,
+                    }),
+                );
+            }
+            emit_status(
+                app,
+                folder_id,
+                path,
+                if removed { "removed" } else { "synced" },
+                Some(format!("{} chunks", chunks)),
+            )
+        }
+        Err(e) => emit_status(app, folder_id, path, "error", Some(e)),
+    }
+}
+
+fn handle_event(app: AppHandle, folder_id: String, event: Event) {
+    let removed = matches!(event.kind, EventKind::Remove(_));
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return;
+    }
+    for path in event.paths {
+        if !is_markdown(&path) {
+            continue;
+        }
+        let app = app.clone();
+        let folder_id = folder_id.clone();
+        tauri::async_runtime::spawn(async move {
+            sync_file(&app, &folder_id, &path, removed).await;
+        });
+    }
+}
+
+fn start_watcher(app: &AppHandle, folder_id: &str, path: &Path) -> Result<RecommendedWatcher, String> {
+    let app = app.clone();
+    let folder_id = folder_id.to_string();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            handle_event(app.clone(), folder_id.clone(), event);
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(path, RecursiveMode::Recursive)
+        .map_err(|e| e.to_string())?;
+    Ok(watcher)
+}
+
+/// Walk a folder and ingest every markdown file already present.
+fn initial_scan(app: AppHandle, folder_id: String, root: PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        let mut pending = vec![root];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if is_markdown(&path) {
+                    sync_file(&app, &folder_id, &path, false).await;
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn add_watched_folder(
+    app: AppHandle,
+    db: State<Db>,
+    watchers: State<WatcherState>,
+    path: String,
+) -> Result<WatchedFolder, String> {
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err(format!("not a directory: {}", path));
+    }
+    let folder = WatchedFolder {
+        id: Uuid::new_v4().to_string(),
+        path: path.clone(),
+        created_at: db::now(),
+    };
+    {
+        let conn = db.conn();
+        conn.execute(
+            "INSERT INTO watched_folders (id, path, created_at) VALUES (?1, ?2, ?3)",
+            params![folder.id, folder.path, folder.created_at],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    let watcher = start_watcher(&app, &folder.id, &root)?;
+    watchers.0.lock().unwrap().insert(folder.id.clone(), watcher);
+    initial_scan(app, folder.id.clone(), root);
+    Ok(folder)
+}
+
+#[tauri::command]
+pub fn remove_watched_folder(
+    db: State<Db>,
+    watchers: State<WatcherState>,
+    folder_id: String,
+) -> Result<(), String> {
+    // Dropping the watcher stops it.
+    watchers.0.lock().unwrap().remove(&folder_id);
+    let conn = db.conn();
+    conn.execute(
+        "DELETE FROM kb_documents WHERE folder_id = ?1",
+        params![folder_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM watched_folders WHERE id = ?1",
+        params![folder_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_watched_folders(db: State<Db>) -> Result<Vec<WatchedFolder>, String> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare("SELECT id, path, created_at FROM watched_folders ORDER BY created_at ASC")
+        .map_err(|e| e.to_string())?;
+    let folders = stmt
+        .query_map([], |row| {
+            Ok(WatchedFolder {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(folders)
+}
+
+/// Restore watchers for folders registered in a previous session.
+pub fn restore_watchers(app: &AppHandle) -> Result<(), String> {
+    let folders = get_watched_folders(app.state::<Db>())?;
+    let watchers = app.state::<WatcherState>();
+    for folder in folders {
+        match start_watcher(app, &folder.id, Path::new(&folder.path)) {
+            Ok(watcher) => {
+                watchers.0.lock().unwrap().insert(folder.id, watcher);
+            }
+            Err(e) => emit_status(app, &folder.id, Path::new(&folder.path), "error", Some(e)),
+        }
+    }
+    Ok(())
+}
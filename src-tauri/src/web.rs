@@ -0,0 +1,285 @@
+//! Fetch-and-summarize for pasted links: download a page, strip
+//! boilerplate with a readability-style pass (no headless browser, just
+//! tag stripping with the obvious non-content regions removed), chunk
+//! long articles, and stream a model-written summary back as
+//! `summary-token` events. The source title and URL come back with the
+//! result for citation.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::{AppError, AppResult};
+use crate::ndjson::NdjsonDecoder;
+use crate::ollama::OLLAMA_BASE_URL;
+
+/// Character budget per summarization pass; longer pages are summarized
+/// chunk by chunk and the partial summaries combined in a final pass.
+const CHUNK_BUDGET: usize = 12_000;
+
+/// Regions that never contain article text.
+const SKIP_TAGS: [&str; 6] = ["script", "style", "nav", "header", "footer", "aside"];
+
+/// The page `<title>`, if present.
+pub fn extract_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let open_end = html[start..].find('>')? + start + 1;
+    let close = lower[open_end..].find("</title>")? + open_end;
+    let title = decode_entities(html[open_end..close].trim());
+    (!title.is_empty()).then_some(title)
+}
+
+pub(crate) fn decode_entities(text: &str) -> String {
+    text.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+}
+
+/// Readability-style text extraction: drop non-content regions and
+/// comments, turn block boundaries into paragraph breaks, strip the
+/// remaining tags and collapse whitespace.
+pub fn extract_text(html: &str) -> String {
+    let mut cleaned = html.to_string();
+    for tag in SKIP_TAGS {
+        loop {
+            let lower = cleaned.to_ascii_lowercase();
+            let Some(start) = lower.find(&format!("<{}", tag)) else {
+                break;
+            };
+            let close_marker = format!("</{}>", tag);
+            let end = match lower[start..].find(&close_marker) {
+                Some(offset) => start + offset + close_marker.len(),
+                None => break,
+            };
+            cleaned.replace_range(start..end, " ");
+        }
+    }
+    while let (Some(start), Some(end)) = (cleaned.find("<!--"), cleaned.find("-->")) {
+        if end < start {
+            break;
+        }
+        cleaned.replace_range(start..end + 3, " ");
+    }
+
+    // Block-level closers become paragraph breaks before tags vanish.
+    // `to_ascii_lowercase` preserves byte offsets, so indices into
+    // `cleaned` are valid in `lower` too.
+    let lower = cleaned.to_ascii_lowercase();
+    let mut text = String::with_capacity(cleaned.len());
+    let mut in_tag = false;
+    let mut tag_start = 0;
+    for (index, ch) in cleaned.char_indices() {
+        match ch {
+            '<' => {
+                in_tag = true;
+                tag_start = index;
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                let tag = &lower[tag_start..=index];
+                if tag.starts_with("</p")
+                    || tag.starts_with("</div")
+                    || tag.starts_with("</h")
+                    || tag.starts_with("</li")
+                    || tag.starts_with("<br")
+                {
+                    text.push('\n');
+                } else {
+                    text.push(' ');
+                }
+            }
+            _ if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+
+    let decoded = decode_entities(&text);
+    let mut paragraphs: Vec<String> = Vec::new();
+    for line in decoded.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            paragraphs.push(line);
+        }
+    }
+    paragraphs.join("\n")
+}
+
+/// Split extracted text into chunks under the per-pass budget along
+/// paragraph boundaries.
+fn chunk_text(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split('\n') {
+        if !current.is_empty() && current.len() + paragraph.len() > CHUNK_BUDGET {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(paragraph);
+        current.push('\n');
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryToken {
+    pub url: String,
+    pub token: String,
+    pub done: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UrlSummary {
+    pub url: String,
+    pub title: Option<String>,
+    pub summary: String,
+}
+
+/// One non-streaming /api/generate round, used for per-chunk passes and
+/// by other modules that need a quick model answer without a chat.
+pub(crate) async fn generate(model: &str, prompt: &str) -> AppResult<String> {
+    let client = reqwest::Client::new();
+    let response: Value = client
+        .post(format!("{}/api/generate", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({ "model": model, "prompt": prompt, "stream": false }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    response
+        .get("response")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Internal("malformed response from Ollama".to_string()))
+}
+
+/// Stream the final summary, emitting `summary-token` events keyed by URL.
+async fn stream_summary(app: &AppHandle, url: &str, model: &str, prompt: &str) -> AppResult<String> {
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{}/api/generate", OLLAMA_BASE_URL))
+        .json(&serde_json::json!({ "model": model, "prompt": prompt, "stream": true }))
+        .send()
+        .await?;
+    let mut summary = String::new();
+    let mut stream = resp.bytes_stream();
+    let mut decoder = NdjsonDecoder::new();
+    let mut emit = |value: Value| -> AppResult<()> {
+        let token = value
+            .get("response")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let done = value.get("done").and_then(Value::as_bool).unwrap_or(false);
+        summary.push_str(&token);
+        app.emit(
+            "summary-token",
+            &SummaryToken {
+                url: url.to_string(),
+                token,
+                done,
+            },
+        )?;
+        Ok(())
+    };
+    while let Some(chunk) = stream.next().await {
+        for value in decoder.push(&chunk?) {
+            emit(value)?;
+        }
+    }
+    if let Some(value) = decoder.finish() {
+        emit(value)?;
+    }
+    Ok(summary)
+}
+
+/// Fetch a page, extract its readable text and stream a model summary.
+#[tauri::command]
+pub async fn summarize_url(app: AppHandle, url: String, model: String) -> AppResult<UrlSummary> {
+    let client = reqwest::Client::new();
+    let html = client
+        .get(&url)
+        .header("User-Agent", "cortex-ai-desktop")
+        .send()
+        .await
+        .map_err(|e| AppError::Io(format!("could not fetch {}: {}", url, e)))?
+        .text()
+        .await
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    let title = extract_title(&html);
+    let text = extract_text(&html);
+    if text.trim().is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "no readable text found at {}",
+            url
+        )));
+    }
+
+    let chunks = chunk_text(&text);
+    let source = if chunks.len() == 1 {
+        chunks.into_iter().next().unwrap()
+    } else {
+        // Map-reduce: summarize each chunk, then summarize the summaries.
+        let mut parts = Vec::new();
+        for chunk in &chunks {
+            parts.push(
+                generate(
+                    &model,
+                    &format!("Summarize this section of a web page concisely:\n\n{}", chunk),
+                )
+                .await?,
+            );
+        }
+        parts.join("\n\n")
+    };
+    let prompt = format!(
+        "Summarize the following web page content. Lead with the key points.\n\nTitle: {}\nURL: {}\n\n{}",
+        title.as_deref().unwrap_or("(unknown)"),
+        url,
+        source
+    );
+    let summary = stream_summary(&app, &url, &model, &prompt).await?;
+    Ok(UrlSummary {
+        url,
+        title,
+        summary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_text, extract_title};
+
+    #[test]
+    fn strips_boilerplate_and_tags() {
+        let html = "<html><head><title>My Page</title><style>p{}</style></head>\
+                    <body><nav>Home | About</nav><p>First &amp; real paragraph.</p>\
+                    <script>alert(1)</script><p>Second one.</p><footer>(c)</footer></body></html>";
+        let text = extract_text(html);
+        assert!(text.contains("First & real paragraph."));
+        assert!(text.contains("Second one."));
+        assert!(!text.contains("alert"));
+        assert!(!text.contains("Home | About"));
+    }
+
+    #[test]
+    fn block_closers_become_paragraph_breaks() {
+        let text = extract_text("<p>one</p><p>two</p>");
+        assert_eq!(text, "one\ntwo");
+    }
+
+    #[test]
+    fn title_is_extracted_and_decoded() {
+        assert_eq!(
+            extract_title("<title>A &amp; B</title>"),
+            Some("A & B".to_string())
+        );
+        assert_eq!(extract_title("<p>no title</p>"), None);
+    }
+}
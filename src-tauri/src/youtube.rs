@@ -0,0 +1,228 @@
+//! YouTube transcript ingestion: pull the manual (or auto-generated)
+//! caption track for a video, store it as a chat attachment, and
+//! optionally chunk it into the knowledge base with timestamps kept
+//! inline so answers can cite "at 12:45".
+
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::attachments::{self, Attachment};
+use crate::db::Db;
+use crate::error::{AppError, AppResult};
+use crate::knowledge;
+use crate::web;
+
+/// The 11-character video id from any of the usual URL shapes
+/// (`watch?v=`, `youtu.be/`, `shorts/`, `embed/`).
+pub fn video_id(url: &str) -> Option<String> {
+    let candidate = if let Some(rest) = url.split("v=").nth(1) {
+        rest
+    } else if let Some(rest) = url
+        .split("youtu.be/")
+        .nth(1)
+        .or_else(|| url.split("/shorts/").nth(1))
+        .or_else(|| url.split("/embed/").nth(1))
+    {
+        rest
+    } else {
+        return None;
+    };
+    let id: String = candidate
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    (id.len() == 11).then_some(id)
+}
+
+/// `65.0` → `1:05`, `3725.0` → `1:02:05`.
+pub fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, (total % 3600) / 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CaptionSegment {
+    start: f64,
+    text: String,
+}
+
+/// Parse YouTube's timedtext XML (`<text start="12.3" dur="4.5">…</text>`).
+/// Caption text is double-encoded, so entities are decoded twice.
+fn parse_timedtext(xml: &str) -> Vec<CaptionSegment> {
+    let mut segments = Vec::new();
+    for part in xml.split("<text ").skip(1) {
+        let Some(attr_end) = part.find('>') else {
+            continue;
+        };
+        let attrs = &part[..attr_end];
+        let start = attrs
+            .split("start=\"")
+            .nth(1)
+            .and_then(|s| s.split('"').next())
+            .and_then(|s| s.parse::<f64>().ok());
+        let Some(start) = start else { continue };
+        let body = part[attr_end + 1..]
+            .split("</text>")
+            .next()
+            .unwrap_or_default();
+        let text = web::decode_entities(&web::decode_entities(body))
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !text.is_empty() {
+            segments.push(CaptionSegment { start, text });
+        }
+    }
+    segments
+}
+
+/// The `"captionTracks"` JSON array embedded in the watch page.
+fn extract_caption_tracks(html: &str) -> Option<Value> {
+    let marker = "\"captionTracks\":";
+    let start = html.find(marker)? + marker.len();
+    let bytes = html[start..].as_bytes();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, &b) in bytes.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'[' => depth += 1,
+            b']' => {
+                // A stray `]` before any `[` means the page doesn't
+                // have the structure we expect; bail rather than
+                // underflow.
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return serde_json::from_str(&html[start..=start + index]).ok();
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Pick the best track: a manual one when available, otherwise the
+/// auto-generated (`asr`) track.
+fn pick_track(tracks: &Value) -> Option<String> {
+    let tracks = tracks.as_array()?;
+    let manual = tracks
+        .iter()
+        .find(|t| t.get("kind").and_then(Value::as_str) != Some("asr"));
+    manual
+        .or_else(|| tracks.first())
+        .and_then(|t| t.get("baseUrl").and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+/// Render segments as timestamped lines, grouped into paragraphs so the
+/// knowledge-base chunker keeps timestamps attached to nearby speech.
+fn render_transcript(segments: &[CaptionSegment]) -> String {
+    let mut lines = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        if index > 0 && index % 10 == 0 {
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "[{}] {}",
+            format_timestamp(segment.start),
+            segment.text
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Fetch a video's captions, store the timestamped transcript as a chat
+/// attachment, and (when `ingest` is set) chunk it into the knowledge
+/// base under a `youtube:<id>` source.
+#[tauri::command]
+pub async fn fetch_youtube_transcript(
+    app: AppHandle,
+    db: State<'_, Db>,
+    chat_id: String,
+    url: String,
+    ingest: bool,
+) -> AppResult<Attachment> {
+    let id = video_id(&url)
+        .ok_or_else(|| AppError::InvalidInput(format!("not a YouTube video URL: {}", url)))?;
+    let client = reqwest::Client::new();
+    let html = client
+        .get(format!("https://www.youtube.com/watch?v={}", id))
+        .header("User-Agent", "cortex-ai-desktop")
+        .send()
+        .await?
+        .text()
+        .await?;
+    let tracks = extract_caption_tracks(&html)
+        .ok_or_else(|| AppError::NotFound(format!("no captions available for {}", url)))?;
+    let track_url = pick_track(&tracks)
+        .ok_or_else(|| AppError::NotFound(format!("no usable caption track for {}", url)))?;
+    let xml = client.get(&track_url).send().await?.text().await?;
+    let segments = parse_timedtext(&xml);
+    if segments.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "caption track for {} is empty",
+            url
+        )));
+    }
+    let transcript = render_transcript(&segments);
+
+    // Route through the normal attachment path so the transcript rides
+    // along with the next user message like any other file.
+    let temp_path = std::env::temp_dir().join(format!("{}-transcript.txt", id));
+    std::fs::write(&temp_path, &transcript)?;
+    let attachment = attachments::store_attachment(&app, &db, &chat_id, &temp_path.to_string_lossy())?;
+    let _ = std::fs::remove_file(&temp_path);
+
+    if ingest {
+        knowledge::ingest_text(&db, "youtube", &format!("youtube:{}", id), &transcript).await?;
+    }
+    Ok(attachment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_timestamp, parse_timedtext, video_id};
+
+    #[test]
+    fn video_id_from_common_url_shapes() {
+        let id = Some("dQw4w9WgXcQ".to_string());
+        assert_eq!(video_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ"), id);
+        assert_eq!(video_id("https://youtu.be/dQw4w9WgXcQ?t=43"), id);
+        assert_eq!(video_id("https://www.youtube.com/shorts/dQw4w9WgXcQ"), id);
+        assert_eq!(video_id("https://example.com/watch?v=short"), None);
+    }
+
+    #[test]
+    fn timestamps_render_with_and_without_hours() {
+        assert_eq!(format_timestamp(65.4), "1:05");
+        assert_eq!(format_timestamp(3725.0), "1:02:05");
+        assert_eq!(format_timestamp(0.0), "0:00");
+    }
+
+    #[test]
+    fn timedtext_segments_are_parsed_and_decoded() {
+        let xml = r#"<transcript><text start="12.3" dur="4">it&amp;#39;s  fine</text>
+                     <text start="16.3" dur="2">second line</text></transcript>"#;
+        let segments = parse_timedtext(xml);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "it's fine");
+        assert_eq!(segments[1].start, 16.3);
+    }
+}